@@ -0,0 +1,193 @@
+//! Derive macros for `rust_mdd`, see `rust_mdd::container`.
+
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::quote;
+use syn::parse_macro_input;
+use syn::Data;
+use syn::DeriveInput;
+use syn::Error;
+use syn::Expr;
+use syn::Fields;
+use syn::LitInt;
+use syn::LitStr;
+
+/// Implement `MddContainer`, `FromValue` and `ToValue` for a struct with
+/// named fields.
+///
+/// Struct attribute `#[mdd(key = .., schema_version = .., ext_version = ..)]`
+/// sets the container header, the versions default to 0. `total_field = ..`
+/// sets the field count of the header, which defaults to the number of
+/// fields up to the last one of the struct. Field attribute
+/// `#[mdd(index = .., name = "..")]` sets the position of the field, which
+/// defaults to its position in the struct, and the schema field name it is
+/// read by.
+#[proc_macro_derive(MddContainer, attributes(mdd))]
+pub fn derive_mdd_container(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+struct FieldAttrs {
+    ident: syn::Ident,
+    index: usize,
+    name: Option<LitStr>,
+}
+
+fn expand(input: DeriveInput) -> Result<proc_macro2::TokenStream, Error> {
+    let ident = &input.ident;
+    if !input.generics.params.is_empty() {
+        return Err(Error::new_spanned(
+            &input.generics,
+            "MddContainer cannot be derived for generic structs",
+        ));
+    }
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(Error::new_spanned(
+                    ident,
+                    "MddContainer can only be derived for structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(Error::new_spanned(
+                ident,
+                "MddContainer can only be derived for structs",
+            ))
+        }
+    };
+
+    let mut key: Option<Expr> = None;
+    let mut schema_version: Option<Expr> = None;
+    let mut ext_version: Option<Expr> = None;
+    let mut total_field: Option<Expr> = None;
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident("mdd")) {
+        attr.parse_nested_meta(|meta| {
+            let value = meta.value()?.parse::<Expr>()?;
+            if meta.path.is_ident("key") {
+                key = Some(value);
+            } else if meta.path.is_ident("schema_version") {
+                schema_version = Some(value);
+            } else if meta.path.is_ident("ext_version") {
+                ext_version = Some(value);
+            } else if meta.path.is_ident("total_field") {
+                total_field = Some(value);
+            } else {
+                return Err(meta.error("unknown mdd attribute"));
+            }
+            Ok(())
+        })?;
+    }
+    let key = key.ok_or_else(|| {
+        Error::new(
+            Span::call_site(),
+            "missing container key, add #[mdd(key = ..)]",
+        )
+    })?;
+    let schema_version = schema_version.map_or_else(|| quote!(0), |v| quote!(#v));
+    let ext_version = ext_version.map_or_else(|| quote!(0), |v| quote!(#v));
+    let total_field = total_field.map(|v| quote!(.total_field(#v)));
+
+    let mut attrs: Vec<FieldAttrs> = vec![];
+    for (position, field) in fields.iter().enumerate() {
+        let mut index = position;
+        let mut name = None;
+        for attr in field.attrs.iter().filter(|a| a.path().is_ident("mdd")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("index") {
+                    index = meta.value()?.parse::<LitInt>()?.base10_parse()?;
+                } else if meta.path.is_ident("name") {
+                    name = Some(meta.value()?.parse::<LitStr>()?);
+                } else {
+                    return Err(meta.error("unknown mdd attribute"));
+                }
+                Ok(())
+            })?;
+        }
+        let ident = field.ident.clone().expect("named field");
+        if let Some(other) = attrs.iter().find(|a| a.index == index) {
+            return Err(Error::new_spanned(
+                &ident,
+                format!("field index {} already used by '{}'", index, other.ident),
+            ));
+        }
+        attrs.push(FieldAttrs { ident, index, name });
+    }
+
+    let target = ident.to_string();
+    let reads = attrs.iter().map(|f| {
+        let ident = &f.ident;
+        let field = ident.to_string();
+        let index = f.index;
+        let name = match &f.name {
+            Some(name) => quote!(::std::option::Option::Some(#name)),
+            None => quote!(::std::option::Option::None),
+        };
+        quote! {
+            #ident: ::rust_mdd::container::read_field(container, #target, #field, #index, #name)?
+        }
+    });
+    let writes = attrs.iter().map(|f| {
+        let ident = &f.ident;
+        let index = f.index;
+        quote!(.field(#index, &self.#ident)?)
+    });
+
+    Ok(quote! {
+        impl<'a> ::rust_mdd::container::MddContainer<'a> for #ident {
+            const KEY: i32 = #key;
+            const SCHEMA_VERSION: u16 = #schema_version;
+            const EXT_VERSION: u16 = #ext_version;
+
+            fn from_container(
+                container: &::rust_mdd::mdd::Container<'a>,
+            ) -> ::std::result::Result<Self, ::rust_mdd::error::Error> {
+                ::rust_mdd::container::check_key(container, #target, Self::KEY)?;
+                ::std::result::Result::Ok(#ident {
+                    #(#reads,)*
+                })
+            }
+
+            fn to_container(
+                &self,
+            ) -> ::std::result::Result<::rust_mdd::mdd::Container<'static>, ::rust_mdd::error::Error> {
+                ::rust_mdd::container::ContainerBuilder::new(
+                    Self::KEY,
+                    Self::SCHEMA_VERSION,
+                    Self::EXT_VERSION,
+                )
+                #total_field
+                #(#writes)*
+                .build()
+            }
+        }
+
+        impl<'a> ::rust_mdd::mdd::FromValue<'a> for #ident {
+            fn from_value(
+                value: &::rust_mdd::mdd::Value<'a>,
+            ) -> ::std::result::Result<Self, ::rust_mdd::error::Error> {
+                ::rust_mdd::container::from_struct_value(value)
+            }
+        }
+
+        impl ::rust_mdd::mdd::ToValue for #ident {
+            fn field_type() -> ::rust_mdd::mdd::FieldType {
+                ::rust_mdd::mdd::FieldType::Struct
+            }
+
+            fn to_value(
+                &self,
+            ) -> ::std::result::Result<
+                ::std::option::Option<::rust_mdd::mdd::Value<'static>>,
+                ::rust_mdd::error::Error,
+            > {
+                ::rust_mdd::container::to_struct_value(self)
+            }
+        }
+    })
+}
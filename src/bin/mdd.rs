@@ -0,0 +1,70 @@
+use rust_mdd::error::Error;
+use rust_mdd::schema::SchemaRegistry;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+const USAGE: &str = "\
+Usage: mdd <command> [args]
+
+Commands:
+  diff <old> <new>  Compare two sets of container definitions. Each argument
+                    is a schema file (.xml, .json or .toml) or a directory of
+                    schema files. Exits with 1 if any change is breaking.";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(|a| a.as_str()).collect();
+    let result = match args.as_slice() {
+        ["diff", old, new] => diff(old, new),
+        ["help" | "-h" | "--help"] => {
+            println!("{}", USAGE);
+            return ExitCode::SUCCESS;
+        }
+        _ => {
+            eprintln!("{}", USAGE);
+            return ExitCode::from(2);
+        }
+    };
+    match result {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::from(2)
+        }
+    }
+}
+
+fn diff(old: &str, new: &str) -> Result<ExitCode, Error> {
+    let old = load(Path::new(old))?;
+    let new = load(Path::new(new))?;
+    let changes = old.diff(&new)?;
+    for change in &changes {
+        println!("{}", change);
+    }
+    let breaking = changes.iter().filter(|c| c.is_breaking()).count();
+    println!("{} changes, {} breaking", changes.len(), breaking);
+    match breaking {
+        0 => Ok(ExitCode::SUCCESS),
+        _ => Ok(ExitCode::from(1)),
+    }
+}
+
+/// Load the schema file at `path`, or every schema file in it if it is a
+/// directory.
+fn load(path: &Path) -> Result<SchemaRegistry, Error> {
+    if !path.is_dir() {
+        return SchemaRegistry::from_files(&[path]);
+    }
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(path)?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<Result<_, _>>()?;
+    paths.retain(|p| {
+        matches!(
+            p.extension().and_then(|e| e.to_str()),
+            Some("xml" | "json" | "toml")
+        )
+    });
+    paths.sort();
+    SchemaRegistry::from_files(&paths)
+}
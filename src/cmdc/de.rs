@@ -0,0 +1,842 @@
+//! Deserialize Rust values from cMDC with serde.
+//!
+//! The counterpart of `cmdc::ser`: structs are read from containers, by
+//! position or by schema field name, and the struct name is checked against
+//! the container key. Strings without escapes and `&str` fields borrow from
+//! the input. Empty fields read as `None`, and missing `Option` fields
+//! default to `None` as well. Like `cmdc::ser`, floats are rejected.
+
+use super::ser::StructName;
+use super::ser::DATE;
+use super::ser::DATETIME;
+use super::ser::DECIMAL;
+use super::ser::OBJECT_ID;
+use super::ser::TIME;
+use super::CMDC_CODEC;
+use crate::container::check_key;
+use crate::error::Error;
+use crate::mdd::Container;
+use crate::mdd::FieldType;
+use crate::schema::SchemaRegistry;
+use serde::de;
+use serde::de::value::BorrowedStrDeserializer;
+use serde::de::value::StrDeserializer;
+use serde::de::IntoDeserializer;
+use serde::Deserialize;
+use serde::Deserializer;
+use std::fmt;
+use std::str::FromStr;
+
+pub fn from_slice<'de, T: Deserialize<'de>>(data: &'de [u8]) -> Result<T, Error> {
+    deserialize(data, None)
+}
+
+/// Deserialize with struct fields read by their schema field name.
+pub fn from_slice_with_schema<'de, T: Deserialize<'de>>(
+    data: &'de [u8],
+    registry: &SchemaRegistry,
+) -> Result<T, Error> {
+    deserialize(data, Some(registry))
+}
+
+fn deserialize<'de, T: Deserialize<'de>>(
+    data: &'de [u8],
+    registry: Option<&SchemaRegistry>,
+) -> Result<T, Error> {
+    let mut containers = CMDC_CODEC.decode_containers(data)?.containers;
+    if let Some(registry) = registry {
+        for container in containers.iter_mut() {
+            container.schema = Some(registry.lookup_header(&container.header)?);
+        }
+    }
+    T::deserialize(ContainersDeserializer {
+        containers,
+        registry,
+    })
+}
+
+/// Consecutive containers, read as a sequence or as a single struct.
+struct ContainersDeserializer<'de, 'r> {
+    containers: Vec<Container<'de>>,
+    registry: Option<&'r SchemaRegistry>,
+}
+
+impl<'de, 'r> ContainersDeserializer<'de, 'r> {
+    fn single(self) -> Result<ContainerDeserializer<'de, 'r>, Error> {
+        let count = self.containers.len();
+        match <[Container; 1]>::try_from(self.containers) {
+            Ok([container]) => Ok(ContainerDeserializer {
+                container,
+                registry: self.registry,
+            }),
+            Err(_) => Err(Error::DecodeError(format!(
+                "Expected a single container but found {}",
+                count
+            ))),
+        }
+    }
+}
+
+impl<'de, 'r> de::Deserializer<'de> for ContainersDeserializer<'de, 'r> {
+    type Error = Error;
+
+    fn deserialize_any<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.containers.len() {
+            1 => self.single()?.deserialize_any(visitor),
+            _ => self.deserialize_seq(visitor),
+        }
+    }
+
+    fn deserialize_option<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.containers.is_empty() {
+            true => visitor.visit_none(),
+            false => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let registry = self.registry;
+        visitor.visit_seq(de::value::SeqDeserializer::new(
+            self.containers
+                .into_iter()
+                .map(|container| ContainerDeserializer {
+                    container,
+                    registry,
+                }),
+        ))
+    }
+
+    fn deserialize_struct<V: de::Visitor<'de>>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.single()?.deserialize_struct(name, fields, visitor)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct tuple tuple_struct map enum identifier
+        ignored_any
+    }
+}
+
+struct ContainerDeserializer<'de, 'r> {
+    container: Container<'de>,
+    registry: Option<&'r SchemaRegistry>,
+}
+
+impl<'de, 'r> ContainerDeserializer<'de, 'r> {
+    /// Check the container is the one named by the struct.
+    fn check_name(&self, name: &str) -> Result<(), Error> {
+        let name = StructName::parse(name)?;
+        match (self.registry, name.key) {
+            // Containers derived from the named one are accepted as well
+            (Some(registry), _) => {
+                if !registry.is_a(self.container.header.key, name.name) {
+                    let found = self.container.name().unwrap_or("unknown").to_string();
+                    return Err(Error::SchemaError(format!(
+                        "Container '{}' with key {} is not a '{}'",
+                        found, self.container.header.key, name.name
+                    )));
+                }
+                Ok(())
+            }
+            (None, Some(key)) => check_key(&self.container, name.name, key),
+            (None, None) => Ok(()),
+        }
+    }
+
+    fn field(&self, idx: usize) -> Option<FieldDeserializer<'de, 'r>> {
+        let field = self.container.fields.get(idx).filter(|f| !f.is_null)?;
+        let def = self
+            .container
+            .schema
+            .as_ref()
+            .and_then(|schema| schema.fields.get(idx));
+        Some(FieldDeserializer {
+            data: field.data,
+            field_type: def
+                .map(|d| d.field_type.clone())
+                .unwrap_or(FieldType::Unknown),
+            struct_name: def.and_then(|d| d.struct_name.clone()),
+            registry: self.registry,
+        })
+    }
+}
+
+impl<'de, 'r> de::Deserializer<'de> for ContainerDeserializer<'de, 'r> {
+    type Error = Error;
+
+    /// Read the container as a map of its non-null fields, keyed by schema
+    /// field name or by position.
+    fn deserialize_any<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let entries = (0..self.container.fields.len())
+            .filter_map(|idx| {
+                let key = match &self.container.schema {
+                    Some(schema) => schema.fields.get(idx)?.name.clone(),
+                    None => idx.to_string(),
+                };
+                Some((key, self.field(idx)?))
+            })
+            .collect::<Vec<_>>();
+        visitor.visit_map(de::value::MapDeserializer::new(entries.into_iter()))
+    }
+
+    fn deserialize_struct<V: de::Visitor<'de>>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.check_name(name)?;
+        let entries = fields
+            .iter()
+            .enumerate()
+            .filter_map(|(position, key)| {
+                let idx = match &self.container.schema {
+                    Some(schema) => schema.field_index(key)?,
+                    None => position,
+                };
+                Some((*key, self.field(idx)?))
+            })
+            .collect::<Vec<_>>();
+        visitor.visit_map(de::value::MapDeserializer::new(entries.into_iter()))
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map enum identifier ignored_any
+    }
+}
+
+impl<'de, 'r> IntoDeserializer<'de, Error> for ContainerDeserializer<'de, 'r> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+/// The data of a field or list element, typed by the Rust type it is read
+/// into, or by the schema for `deserialize_any`.
+struct FieldDeserializer<'de, 'r> {
+    data: &'de [u8],
+    field_type: FieldType,
+    /// Declared container of a struct field
+    struct_name: Option<String>,
+    registry: Option<&'r SchemaRegistry>,
+}
+
+impl<'de, 'r> FieldDeserializer<'de, 'r> {
+    fn text(&self) -> Result<&'de str, Error> {
+        Ok(std::str::from_utf8(self.data)?)
+    }
+
+    fn parse<T: FromStr>(&self, expected: &str) -> Result<T, Error> {
+        let text = self.text()?;
+        text.parse().map_err(|_| {
+            Error::TypeMismatch(format!(
+                "Value type mismatch, expected {} but found '{}'",
+                expected, text
+            ))
+        })
+    }
+
+    fn string(&self) -> Result<&'de str, Error> {
+        match self.data.first() {
+            Some(b'(') => CMDC_CODEC.decode_string(self.data),
+            _ => self.text(),
+        }
+    }
+
+    /// Decode the containers held by the field, with their schema attached.
+    fn containers(&self) -> Result<Vec<Container<'de>>, Error> {
+        let mut containers = CMDC_CODEC.decode_containers(self.data)?.containers;
+        if let Some(registry) = self.registry {
+            for container in containers.iter_mut() {
+                let header = &container.header;
+                let schema = match (registry.get(header.key), &self.struct_name) {
+                    (Some(_), _) => registry.lookup_header(header)?,
+                    (None, Some(declared)) => {
+                        let def = registry.get_by_name(declared).ok_or_else(|| {
+                            Error::SchemaError(format!("Unknown struct type '{}'", declared))
+                        })?;
+                        registry.lookup(def.key, header.schema_version, header.ext_version)?
+                    }
+                    (None, None) => registry.lookup_header(header)?,
+                };
+                container.schema = Some(schema);
+            }
+        }
+        Ok(containers)
+    }
+
+    fn element(&self, data: &'de [u8]) -> FieldDeserializer<'de, 'r> {
+        FieldDeserializer {
+            data,
+            field_type: self.field_type.clone(),
+            struct_name: self.struct_name.clone(),
+            registry: self.registry,
+        }
+    }
+
+    fn mismatch(&self, expected: &str) -> Error {
+        Error::TypeMismatch(format!(
+            "Value type mismatch, expected {} but found '{}'",
+            expected,
+            String::from_utf8_lossy(self.data)
+        ))
+    }
+}
+
+impl<'de, 'r> IntoDeserializer<'de, Error> for FieldDeserializer<'de, 'r> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+macro_rules! deserialize_number {
+    ($($method:ident => $visit:ident, $ty:ty;)*) => {
+        $(
+            fn $method<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+                visitor.$visit(self.parse::<$ty>(stringify!($ty))?)
+            }
+        )*
+    };
+}
+
+impl<'de, 'r> de::Deserializer<'de> for FieldDeserializer<'de, 'r> {
+    type Error = Error;
+
+    fn deserialize_any<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.data.first() {
+            None => return visitor.visit_unit(),
+            Some(b'{') => return self.deserialize_seq(visitor),
+            Some(b'(') => return self.deserialize_str(visitor),
+            Some(b'<') => {
+                let containers = self.containers()?;
+                return ContainersDeserializer {
+                    containers,
+                    registry: self.registry,
+                }
+                .deserialize_any(visitor);
+            }
+            _ => {}
+        }
+        match self.field_type {
+            FieldType::Bool => self.deserialize_bool(visitor),
+            FieldType::Int8 | FieldType::Int16 | FieldType::Int32 | FieldType::Int64 => {
+                self.deserialize_i64(visitor)
+            }
+            FieldType::UInt8 | FieldType::UInt16 | FieldType::UInt32 | FieldType::UInt64 => {
+                self.deserialize_u64(visitor)
+            }
+            FieldType::Unknown => {
+                if let Ok(v) = self.parse::<u64>("u64") {
+                    visitor.visit_u64(v)
+                } else if let Ok(v) = self.parse::<i64>("i64") {
+                    visitor.visit_i64(v)
+                } else {
+                    visitor.visit_borrowed_str(self.text()?)
+                }
+            }
+            _ => visitor.visit_borrowed_str(self.text()?),
+        }
+    }
+
+    fn deserialize_bool<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_bool(CMDC_CODEC.decode_bool(self.data)?)
+    }
+
+    deserialize_number! {
+        deserialize_i8 => visit_i8, i8;
+        deserialize_i16 => visit_i16, i16;
+        deserialize_i32 => visit_i32, i32;
+        deserialize_i64 => visit_i64, i64;
+        deserialize_u8 => visit_u8, u8;
+        deserialize_u16 => visit_u16, u16;
+        deserialize_u32 => visit_u32, u32;
+        deserialize_u64 => visit_u64, u64;
+    }
+
+    fn deserialize_f32<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_f64(visitor)
+    }
+
+    fn deserialize_f64<V: de::Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Error> {
+        Err(Error::DecodeError(
+            "Cannot deserialize cMDC decimals to floats without loss, use serde_decimal".into(),
+        ))
+    }
+
+    fn deserialize_char<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let s = self.string()?;
+        let mut chars = s.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => visitor.visit_char(c),
+            _ => Err(self.mismatch("char")),
+        }
+    }
+
+    fn deserialize_str<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_borrowed_str(self.string()?)
+    }
+
+    fn deserialize_string<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_bytes<V: de::Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Error> {
+        Err(Error::DecodeError(
+            "Cannot deserialize bytes from cMDC".into(),
+        ))
+    }
+
+    fn deserialize_byte_buf<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_option<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.data.is_empty() {
+            true => visitor.visit_none(),
+            false => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_unit<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: de::Visitor<'de>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        match name {
+            // cMDC types are read from their text
+            DATETIME | DATE | TIME | OBJECT_ID | DECIMAL => {
+                visitor.visit_newtype_struct(BorrowedStrDeserializer::<Error>::new(self.text()?))
+            }
+            _ => visitor.visit_newtype_struct(self),
+        }
+    }
+
+    /// Read a list `{..}`, or the containers of a struct field one by one.
+    fn deserialize_seq<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.data.first() {
+            Some(b'{') => {
+                let elements = CMDC_CODEC
+                    .split_list(self.data)?
+                    .into_iter()
+                    .map(|data| self.element(data))
+                    .collect::<Vec<_>>();
+                visitor.visit_seq(de::value::SeqDeserializer::new(elements.into_iter()))
+            }
+            Some(b'<') => ContainersDeserializer {
+                containers: self.containers()?,
+                registry: self.registry,
+            }
+            .deserialize_seq(visitor),
+            _ => Err(self.mismatch("list")),
+        }
+    }
+
+    fn deserialize_tuple<V: de::Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_map<V: de::Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Error> {
+        Err(Error::DecodeError(
+            "Cannot deserialize maps from cMDC".into(),
+        ))
+    }
+
+    fn deserialize_struct<V: de::Visitor<'de>>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        if self.data.first() != Some(&b'<') {
+            return Err(self.mismatch("Struct"));
+        }
+        ContainersDeserializer {
+            containers: self.containers()?,
+            registry: self.registry,
+        }
+        .deserialize_struct(name, fields, visitor)
+    }
+
+    /// Unit variants are written as strings.
+    fn deserialize_enum<V: de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_enum(StrDeserializer::<Error>::new(self.string()?))
+    }
+
+    fn deserialize_identifier<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_ignored_any<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+}
+
+/// Deserialize a cMDC value type written by `serialize_str_newtype`.
+pub(crate) fn deserialize_str_newtype<'de, D, T>(
+    deserializer: D,
+    name: &'static str,
+) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    struct NewtypeVisitor<T>(&'static str, std::marker::PhantomData<T>);
+
+    impl<'de, T> de::Visitor<'de> for NewtypeVisitor<T>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+    {
+        type Value = T;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "a {} string", self.0.trim_start_matches("$rust_mdd::"))
+        }
+
+        fn visit_str<E: de::Error>(self, v: &str) -> Result<T, E> {
+            v.parse().map_err(E::custom)
+        }
+
+        fn visit_newtype_struct<D: Deserializer<'de>>(
+            self,
+            deserializer: D,
+        ) -> Result<T, D::Error> {
+            deserializer.deserialize_str(self)
+        }
+    }
+
+    deserializer.deserialize_newtype_struct(name, NewtypeVisitor(name, std::marker::PhantomData))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmdc::serde_decimal;
+    use crate::cmdc::to_vec;
+    use crate::cmdc::to_vec_with_schema;
+    use crate::datetime::DateTime;
+    use crate::object_id::ObjectId;
+    use crate::schema::ContainerDef;
+    use crate::schema::FieldDef;
+    use bigdecimal::BigDecimal;
+    use serde::Serialize;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(rename = "Threshold:331")]
+    struct Threshold {
+        id: u32,
+        label: Option<String>,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    enum Status {
+        Active,
+        Closed,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(rename = "BalanceInfo:330:5222:2")]
+    struct BalanceInfo<'a> {
+        template_id: u32,
+        name: &'a str,
+        amount: Option<u32>,
+        tags: Vec<i32>,
+        thresholds: Vec<Threshold>,
+        primary: Option<Threshold>,
+        owner: ObjectId,
+        updated: DateTime,
+        #[serde(with = "serde_decimal")]
+        available: BigDecimal,
+        #[serde(with = "serde_decimal::option", default)]
+        limit: Option<BigDecimal>,
+        status: Status,
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let balance = BalanceInfo {
+            template_id: 200,
+            name: "main",
+            amount: None,
+            tags: vec![1, -2],
+            thresholds: vec![
+                Threshold {
+                    id: 1,
+                    label: Some("a,b".into()),
+                },
+                Threshold { id: 2, label: None },
+            ],
+            primary: Some(Threshold { id: 3, label: None }),
+            owner: ObjectId::new(0, 1, 5, 279),
+            updated: "2021-09-07T08:00:25.000000Z".parse().unwrap(),
+            available: "17485760.25".parse().unwrap(),
+            limit: None,
+            status: Status::Closed,
+        };
+        let data = to_vec(&balance).unwrap();
+        assert_eq!(
+            String::from_utf8(data.clone()).unwrap(),
+            "<1,11,0,330,5222,2>[200,(4:main),,{1,-2},\
+             {<1,2,1,331,0,0>[1,(3:a,b)],<1,2,1,331,0,0>[2,]},<1,2,1,331,0,0>[3,],\
+             0:1:5:279,2021-09-07T08:00:25.000000Z,17485760.25,,(6:Closed)]"
+        );
+
+        let decoded: BalanceInfo = from_slice(&data).unwrap();
+        assert_eq!(decoded, balance);
+        // Strings are borrowed from the input
+        assert!(data.as_ptr_range().contains(&decoded.name.as_ptr()));
+    }
+
+    #[test]
+    fn test_from_slice_missing_fields() {
+        #[derive(Debug, PartialEq, Deserialize)]
+        #[serde(rename = "Session:-6")]
+        struct Session {
+            id: u32,
+            name: Option<String>,
+            #[serde(default)]
+            tags: Vec<u32>,
+            score: Option<i8>,
+        }
+
+        // Fields beyond the body and empty fields are missing
+        let session: Session = from_slice(b"<1,2,0,-6,0,0>[7,]").unwrap();
+        assert_eq!(
+            session,
+            Session {
+                id: 7,
+                name: None,
+                tags: vec![],
+                score: None,
+            }
+        );
+
+        let err = from_slice::<Session>(b"<1,2,0,-6,0,0>[,(3:abc)]").unwrap_err();
+        assert_eq!(err.to_string(), "missing field `id`");
+    }
+
+    #[test]
+    fn test_from_slice_sequence() {
+        let data = b"<1,2,0,331,0,0>[1,]<1,2,0,331,0,0>[2,(1:x)]";
+        let thresholds: Vec<Threshold> = from_slice(data).unwrap();
+        assert_eq!(thresholds.len(), 2);
+        assert_eq!(thresholds[1].label.as_deref(), Some("x"));
+
+        let err = from_slice::<Threshold>(data).unwrap_err();
+        assert_eq!(err.to_string(), "Expected a single container but found 2");
+    }
+
+    #[test]
+    fn test_from_slice_errors() {
+        let err = from_slice::<Threshold>(b"<1,2,0,332,0,0>[1,]").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Container key 332 does not match 'Threshold' key 331"
+        );
+
+        let err = from_slice::<Threshold>(b"<1,2,0,331,0,0>[x,]").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Value type mismatch, expected u32 but found 'x'"
+        );
+
+        let err = from_slice::<Threshold>(b"<1,2,0,331,0,0>[-1,]").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Value type mismatch, expected u32 but found '-1'"
+        );
+
+        #[derive(Debug, Deserialize)]
+        #[serde(rename = "Wrapper:1")]
+        struct Wrapper {
+            _inner: Threshold,
+        }
+        let err = from_slice::<Wrapper>(b"<1,1,0,1,0,0>[5]").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Value type mismatch, expected Struct but found '5'"
+        );
+    }
+
+    #[test]
+    fn test_floats_rejected() {
+        #[derive(Debug, Serialize, Deserialize)]
+        #[serde(rename = "Rate:1")]
+        struct Rate {
+            value: f64,
+        }
+        let err = to_vec(&Rate { value: 0.1 }).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Cannot serialize floats to cMDC without loss, use serde_decimal"
+        );
+        let err = from_slice::<Rate>(b"<1,1,0,1,0,0>[0.1]").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Cannot deserialize cMDC decimals to floats without loss, use serde_decimal"
+        );
+
+        // Decimals round-trip through serde_decimal instead
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        #[serde(rename = "Rate:1")]
+        struct DecimalRate {
+            #[serde(with = "serde_decimal")]
+            value: BigDecimal,
+        }
+        let rate = DecimalRate {
+            value: "0.1".parse().unwrap(),
+        };
+        let data = to_vec(&rate).unwrap();
+        assert_eq!(data, b"<1,1,0,1,0,0>[0.1]");
+        assert_eq!(from_slice::<DecimalRate>(&data).unwrap(), rate);
+    }
+
+    fn registry() -> SchemaRegistry {
+        let mut registry = SchemaRegistry::new();
+        registry
+            .add(
+                ContainerDef::new("MtxBalanceInfo", 330)
+                    .field(FieldDef::new("TemplateId", FieldType::UInt32))
+                    .field(FieldDef::new("ResourceId", FieldType::UInt32))
+                    .field(FieldDef::new("Amount", FieldType::Decimal).introduced_in(5200)),
+            )
+            .unwrap();
+        registry
+            .add(
+                ContainerDef::new("CustomBalanceInfo", 5001)
+                    .base("MtxBalanceInfo")
+                    .field(FieldDef::new("Region", FieldType::String)),
+            )
+            .unwrap();
+        registry
+            .add(
+                ContainerDef::new("MtxResponseWallet", 263)
+                    .field(FieldDef::new("Result", FieldType::UInt32))
+                    .field(FieldDef::new_struct("BalanceArray", "MtxBalanceInfo").list()),
+            )
+            .unwrap();
+        registry.check().unwrap();
+        registry
+    }
+
+    #[test]
+    fn test_from_slice_with_schema() {
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        #[serde(rename = "MtxBalanceInfo::5222:2", rename_all = "PascalCase")]
+        struct Balance {
+            #[serde(with = "serde_decimal::option", default)]
+            amount: Option<BigDecimal>,
+            template_id: u32,
+        }
+
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        #[serde(rename = "MtxResponseWallet::5222:2", rename_all = "PascalCase")]
+        struct Wallet {
+            balance_array: Vec<Balance>,
+            result: u32,
+        }
+
+        let registry = registry();
+        let data = b"<1,2,0,263,5222,2>[0,{<1,3,1,330,5222,2>[200,4,1.5],<1,4,1,5001,5222,2>[201,4,,(2:EU)]}]";
+        let wallet: Wallet = from_slice_with_schema(data, &registry).unwrap();
+        assert_eq!(
+            wallet,
+            Wallet {
+                balance_array: vec![
+                    Balance {
+                        amount: Some("1.5".parse().unwrap()),
+                        template_id: 200,
+                    },
+                    Balance {
+                        amount: None,
+                        template_id: 201,
+                    },
+                ],
+                result: 0,
+            }
+        );
+
+        // Older schema versions don't have the Amount field
+        let data = b"<1,2,0,330,5100,0>[200,4]";
+        let balance: Balance = from_slice_with_schema(data, &registry).unwrap();
+        assert_eq!(balance.amount, None);
+
+        let data = to_vec_with_schema(&wallet, &registry).unwrap();
+        assert_eq!(
+            data,
+            b"<1,2,0,263,5222,2>[0,{<1,3,1,330,5222,2>[200,,1.5],<1,3,1,330,5222,2>[201,,]}]"
+        );
+
+        let err =
+            from_slice_with_schema::<Balance>(b"<1,1,0,263,5222,2>[0]", &registry).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Container 'MtxResponseWallet' with key 263 is not a 'MtxBalanceInfo'"
+        );
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_from_slice_any() {
+        let registry = registry();
+        let data = b"<1,3,0,330,5222,2>[200,,1.5]";
+        let value: serde_json::Value = from_slice_with_schema(data, &registry).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({"TemplateId": 200, "Amount": "1.5"})
+        );
+
+        let value: serde_json::Value = from_slice(data).unwrap();
+        assert_eq!(value, serde_json::json!({"0": 200, "2": "1.5"}));
+    }
+}
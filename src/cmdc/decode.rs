@@ -1,11 +1,13 @@
 use super::CmdcCodec;
 use crate::cmdc::CMDC_CODEC;
+use crate::cmdc::STRICT_CMDC_CODEC;
 use crate::error::Error;
 use crate::mdd::Container;
 use crate::mdd::Containers;
 use crate::mdd::Field;
 use crate::mdd::FieldType;
 use crate::mdd::Header;
+use crate::schema::SchemaRegistry;
 
 impl CmdcCodec {
     pub fn decode_containers<'a>(&self, data: &'a [u8]) -> Result<Containers<'a>, Error> {
@@ -21,6 +23,35 @@ impl CmdcCodec {
         Ok(containers)
     }
 
+    /// Decode containers and type their fields from the schema registry, so
+    /// `Field::decode_value()` works without setting field types by hand.
+    pub fn decode_with_schema<'a>(
+        &self,
+        data: &'a [u8],
+        registry: &SchemaRegistry,
+    ) -> Result<Containers<'a>, Error> {
+        let mut containers = self.decode_containers(data)?;
+        registry.apply(&mut containers)?;
+        Ok(containers)
+    }
+
+    /// Decode a list of structs `{<hdr>[..],<hdr>[..]}` into one container
+    /// per element. Each element is decoded as a whole container, header and
+    /// fields, only the field values are left to decode on demand.
+    pub fn decode_struct_list<'a>(&self, data: &'a [u8]) -> Result<Vec<Container<'a>>, Error> {
+        let mut containers = vec![];
+        for element in self.split_list(data)? {
+            let (container, offset) = self.decode_container(element)?;
+            if offset != element.len() {
+                return Err(Error::DecodeError(
+                    "Invalid cMDC struct list, element must be a single container".into(),
+                ));
+            }
+            containers.push(container);
+        }
+        Ok(containers)
+    }
+
     fn decode_container<'a>(&self, data: &'a [u8]) -> Result<(Container<'a>, usize), Error> {
         let mut idx = 0;
 
@@ -30,13 +61,17 @@ impl CmdcCodec {
 
         // Decode Body
         let slice = &data[idx..];
-        let (fields, offset) = self.decode_body(slice)?;
+        let (fields, offset) = self.decode_body(slice, header.depth)?;
         idx += offset;
 
-        Ok((Container { header, fields }, idx))
+        if self.strict {
+            self.check_field_count(&header, &fields)?;
+        }
+
+        Ok((Container::new(header, fields), idx))
     }
 
-    fn decode_header<'a>(&self, data: &'a [u8]) -> Result<(Header, usize), Error> {
+    fn decode_header(&self, data: &[u8]) -> Result<(Header, usize), Error> {
         let mut header = Header {
             version: 0,
             total_field: 0,
@@ -73,14 +108,14 @@ impl CmdcCodec {
                     })?;
 
                     match field_number {
-                        0 => header.version = v as u8,
-                        1 => header.total_field = v as u8,
-                        2 => header.depth = v as i8,
-                        3 => header.key = v as i32,
-                        4 => header.schema_version = v as u16,
+                        0 => header.version = self.narrow("version", v)?,
+                        1 => header.total_field = self.narrow("total_field", v)?,
+                        2 => header.depth = self.narrow("depth", v)?,
+                        3 => header.key = v,
+                        4 => header.schema_version = self.narrow("schema_version", v)?,
                         _ => {
                             return Err(Error::DecodeError(
-                                format!("Invalid cMDC header, 6 fields expected").into(),
+                                "Invalid cMDC header, 6 fields expected".to_string(),
                             ))
                         }
                     }
@@ -92,19 +127,16 @@ impl CmdcCodec {
                 }
                 c if c.is_ascii_digit() || c == b'-' => {}
                 c => {
-                    return Err(Error::DecodeError(
-                        format!(
-                            "Invalid cMDC character '{}' in header, numeric expected",
-                            c as char
-                        )
-                        .into(),
-                    ))
+                    return Err(Error::DecodeError(format!(
+                        "Invalid cMDC character '{}' in header, numeric expected",
+                        c as char
+                    )))
                 }
             }
             idx += 1;
         }
 
-        if complete == false {
+        if !complete {
             return Err(Error::DecodeError(
                 "Invalid cMDC header, missing '>'".into(),
             ));
@@ -119,12 +151,59 @@ impl CmdcCodec {
         let field_data = &data[mark..idx - 1];
         let v = Self::bytes_to_int(field_data)
             .map_err(|err| Error::DecodeError(format!("Invalid cMDC header, {}", err)))?;
-        header.ext_version = v as u16;
+        header.ext_version = self.narrow("ext_version", v)?;
 
         Ok((header, idx))
     }
 
-    fn decode_body<'a>(&self, data: &'a [u8]) -> Result<(Vec<Field<'a>>, usize), Error> {
+    /// Header values are truncated to their type, unless strict mode
+    /// rejects values out of range.
+    fn narrow<T: Narrow>(&self, name: &str, v: i32) -> Result<T, Error> {
+        if !self.strict {
+            return Ok(T::truncate(v));
+        }
+        T::try_from(v).map_err(|_| {
+            Error::DecodeError(format!("Invalid cMDC header, {} {} out of range", name, v))
+        })
+    }
+
+    /// Strict mode check of the number of fields against the header.
+    fn check_field_count(&self, header: &Header, fields: &[Field]) -> Result<(), Error> {
+        let count = match fields {
+            [field] if field.data.is_empty() => 0,
+            fields => fields.len(),
+        };
+        if count > header.total_field as usize {
+            return Err(Error::DecodeError(format!(
+                "Invalid cMDC body, {} fields found but header total_field is {}",
+                count, header.total_field
+            )));
+        }
+        Ok(())
+    }
+
+    /// Strict mode check of a nested header found while scanning a body.
+    /// Containers nested `level` bodies deep are at the parent depth + level.
+    fn check_nested_depth(&self, data: &[u8], depth: i8, level: i32) -> Result<(), Error> {
+        let (header, _) = self.decode_header(data)?;
+        let expected = depth as i32 + level;
+        if header.depth as i32 != expected {
+            return Err(Error::DecodeError(format!(
+                "Invalid cMDC header, nested container depth {} expected but found {}",
+                expected, header.depth
+            )));
+        }
+        Ok(())
+    }
+
+    fn codec(&self) -> &'static CmdcCodec {
+        match self.strict {
+            true => &STRICT_CMDC_CODEC,
+            false => &CMDC_CODEC,
+        }
+    }
+
+    fn decode_body<'a>(&self, data: &'a [u8], depth: i8) -> Result<(Vec<Field<'a>>, usize), Error> {
         let mut fields = vec![];
 
         if data.is_empty() {
@@ -169,13 +248,10 @@ impl CmdcCodec {
                     idx += len as usize; // skip the string field
                     round_mark = 0; // reset round mark
                 } else if !c.is_ascii_digit() {
-                    return Err(Error::DecodeError(
-                        format!(
-                            "Invalid character '{}', numeric expected for string length",
-                            c as char
-                        )
-                        .into(),
-                    ));
+                    return Err(Error::DecodeError(format!(
+                        "Invalid character '{}', numeric expected for string length",
+                        c as char
+                    )));
                 }
                 idx += 1;
                 continue;
@@ -189,35 +265,40 @@ impl CmdcCodec {
                 b'[' => square += 1,
                 b']' => square -= 1,
                 b'<' => {
+                    if self.strict {
+                        self.check_nested_depth(&data[idx..], depth, square)?;
+                    }
                     is_container = true;
                     angle += 1;
                 }
                 b'>' => angle -= 1,
                 b'{' => {
+                    // Only a list at the start of the field makes it multi-value,
+                    // lists inside nested containers belong to those containers.
+                    if idx == mark {
+                        is_multi = true;
+                    }
                     curly += 1;
-                    is_multi = true;
                 }
                 b'}' => curly -= 1,
-                b',' => {
-                    if square == 1 && angle == 0 && curly == 0 {
-                        // Extract fields
-                        let field_data = &data[mark..idx];
-                        // println!("field_data: {:?}", std::str::from_utf8(field_data));
-
-                        mark = idx + 1;
-                        let field = Field {
-                            data: field_data,
-                            field_type: FieldType::Unknown,
-                            value: None,
-                            codec: Some(&CMDC_CODEC),
-                            is_multi,
-                            is_container,
-                            is_null: field_data.len() == 0,
-                        };
-                        fields.push(field);
-                        is_multi = false;
-                        is_container = false;
-                    }
+                b',' if square == 1 && angle == 0 && curly == 0 => {
+                    // Extract fields
+                    let field_data = &data[mark..idx];
+                    // println!("field_data: {:?}", std::str::from_utf8(field_data));
+
+                    mark = idx + 1;
+                    let field = Field {
+                        data: field_data,
+                        field_type: FieldType::Unknown,
+                        value: None,
+                        codec: Some(self.codec()),
+                        is_multi,
+                        is_container,
+                        is_null: field_data.is_empty(),
+                    };
+                    fields.push(field);
+                    is_multi = false;
+                    is_container = false;
                 }
                 _ => {}
             }
@@ -245,24 +326,120 @@ impl CmdcCodec {
             data: field_data,
             field_type: FieldType::Unknown,
             value: None,
-            codec: Some(&CMDC_CODEC),
+            codec: Some(self.codec()),
             is_multi,
             is_container,
-            is_null: field_data.len() == 0,
+            is_null: field_data.is_empty(),
         };
         fields.push(field);
 
         Ok((fields, idx))
     }
 
+    /// Split a multi-value field `{a,b,c}` into the data of each element.
+    pub fn split_list<'a>(&self, data: &'a [u8]) -> Result<Vec<&'a [u8]>, Error> {
+        if data.first() != Some(&b'{') {
+            return Err(Error::DecodeError(
+                "Invalid cMDC list, first character must be '{'".into(),
+            ));
+        }
+        if data.len() < 2 || data[data.len() - 1] != b'}' {
+            return Err(Error::DecodeError(
+                "Invalid cMDC list, last character must be '}'".into(),
+            ));
+        }
+
+        let body = &data[1..data.len() - 1];
+        let mut elements = vec![];
+        if body.is_empty() {
+            return Ok(elements);
+        }
+
+        let mut idx = 0;
+        let mut mark = 0;
+        let mut square = 0;
+        let mut angle = 0;
+        let mut curly = 0;
+
+        while idx < body.len() {
+            match body[idx] {
+                b'(' => idx = Self::skip_string(body, idx)?,
+                b'[' => square += 1,
+                b']' => square -= 1,
+                b'<' => angle += 1,
+                b'>' => angle -= 1,
+                b'{' => curly += 1,
+                b'}' => curly -= 1,
+                b',' if square == 0 && angle == 0 && curly == 0 => {
+                    elements.push(&body[mark..idx]);
+                    mark = idx + 1;
+                }
+                _ => {}
+            }
+            idx += 1;
+        }
+
+        if square != 0 || angle != 0 || curly != 0 {
+            return Err(Error::DecodeError(
+                "Invalid cMDC list, unbalanced brackets".into(),
+            ));
+        }
+        elements.push(&body[mark..]);
+
+        Ok(elements)
+    }
+
+    /// Return the index of the ')' closing the string field starting at `idx`.
+    fn skip_string(data: &[u8], idx: usize) -> Result<usize, Error> {
+        let mut end = idx + 1;
+        while end < data.len() && data[end].is_ascii_digit() {
+            end += 1;
+        }
+        match data.get(end) {
+            Some(b')') if end == idx + 1 => return Ok(end),
+            Some(b':') => {}
+            _ => {
+                return Err(Error::DecodeError(
+                    "Invalid cMDC list, mismatch string length".into(),
+                ))
+            }
+        }
+
+        let len = Self::bytes_to_int(&data[idx + 1..end])? as usize;
+        end += len + 1;
+        if data.get(end) != Some(&b')') {
+            return Err(Error::DecodeError(
+                "Invalid cMDC list, mismatch string length".into(),
+            ));
+        }
+        Ok(end)
+    }
+
     pub fn bytes_to_int(data: &[u8]) -> Result<i32, Error> {
         let str_data = std::str::from_utf8(data)?;
-        str_data.parse::<i32>().map_err(|_| {
-            Error::DecodeError(format!("Invalid digit found in '{}'", str_data).into())
-        })
+        str_data
+            .parse::<i32>()
+            .map_err(|_| Error::DecodeError(format!("Invalid digit found in '{}'", str_data)))
     }
 }
 
+/// Header field types, with the truncation of lenient decoding.
+trait Narrow: TryFrom<i32> {
+    fn truncate(v: i32) -> Self;
+}
+
+macro_rules! impl_narrow {
+    ($($ty:ty),*) => {
+        $(impl Narrow for $ty {
+            fn truncate(v: i32) -> Self {
+                v as $ty
+            }
+        })*
+    };
+}
+
+impl_narrow!(u8, i8, u16);
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -380,10 +557,10 @@ mod tests {
         assert_eq!(container.fields[2].data, b"<1,2,0,452,5222,2>[100]");
         assert_eq!(container.fields[3].data, b"4");
 
-        assert_eq!(container.fields[0].is_container, false);
-        assert_eq!(container.fields[1].is_container, false);
-        assert_eq!(container.fields[2].is_container, true);
-        assert_eq!(container.fields[3].is_container, false);
+        assert!(!container.fields[0].is_container);
+        assert!(!container.fields[1].is_container);
+        assert!(container.fields[2].is_container);
+        assert!(!container.fields[3].is_container);
     }
 
     #[test]
@@ -400,12 +577,160 @@ mod tests {
         assert_eq!(container.fields[4].data, b"300");
         assert_eq!(container.fields[5].data, b"{4,5}");
 
-        assert_eq!(container.fields[0].is_multi, false);
-        assert_eq!(container.fields[1].is_multi, true);
-        assert_eq!(container.fields[2].is_multi, false);
-        assert_eq!(container.fields[3].is_multi, false);
-        assert_eq!(container.fields[4].is_multi, false);
-        assert_eq!(container.fields[5].is_multi, true);
+        assert!(!container.fields[0].is_multi);
+        assert!(container.fields[1].is_multi);
+        assert!(!container.fields[2].is_multi);
+        assert!(!container.fields[3].is_multi);
+        assert!(!container.fields[4].is_multi);
+        assert!(container.fields[5].is_multi);
+    }
+
+    #[test]
+    fn test_list_inside_container_is_not_multi() {
+        let data = b"<1,18,0,-6,5222,2>[<1,2,1,452,5222,2>[{1,2}],{<1,2,1,452,5222,2>[{3}]}]";
+        let containers = CMDC_CODEC.decode_containers(data).unwrap();
+        let container = &containers.containers[0];
+
+        assert_eq!(container.fields.len(), 2);
+        assert!(!container.fields[0].is_multi);
+        assert!(container.fields[0].is_container);
+        assert!(container.fields[1].is_multi);
+        assert!(container.fields[1].is_container);
+    }
+
+    #[test]
+    fn test_split_list() {
+        let elements = CMDC_CODEC.split_list(b"{1,2,3}").unwrap();
+        assert_eq!(elements, [b"1", b"2", b"3"]);
+
+        let elements = CMDC_CODEC.split_list(b"{}").unwrap();
+        assert!(elements.is_empty());
+
+        let elements = CMDC_CODEC.split_list(b"{,2,}").unwrap();
+        assert_eq!(elements, [&b""[..], b"2", b""]);
+    }
+
+    #[test]
+    fn test_split_list_with_reserved_char() {
+        let elements = CMDC_CODEC
+            .split_list(b"{(4:a,}b),(),(0:),(3:{<[)}")
+            .unwrap();
+        assert_eq!(elements, [&b"(4:a,}b)"[..], b"()", b"(0:)", b"(3:{<[)"]);
+    }
+
+    #[test]
+    fn test_split_list_of_containers() {
+        let elements = CMDC_CODEC
+            .split_list(b"{<1,5,1,330,5222,2>[200,4,1],<1,5,1,330,5222,2>[202,{6,7},(2:],)]}")
+            .unwrap();
+        assert_eq!(
+            elements,
+            [
+                &b"<1,5,1,330,5222,2>[200,4,1]"[..],
+                b"<1,5,1,330,5222,2>[202,{6,7},(2:],)]"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_split_invalid_list() {
+        let err = CMDC_CODEC.split_list(b"1,2}").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid cMDC list, first character must be '{'"
+        );
+        let err = CMDC_CODEC.split_list(b"{1,2").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid cMDC list, last character must be '}'"
+        );
+        let err = CMDC_CODEC
+            .split_list(b"{<1,2,1,452,5222,2>[1,2}")
+            .unwrap_err();
+        assert_eq!(err.to_string(), "Invalid cMDC list, unbalanced brackets");
+        let err = CMDC_CODEC.split_list(b"{(5:abc)}").unwrap_err();
+        assert_eq!(err.to_string(), "Invalid cMDC list, mismatch string length");
+    }
+
+    #[test]
+    fn test_decode_with_schema() {
+        use crate::schema::{ContainerDef, FieldDef};
+
+        let mut registry = SchemaRegistry::new();
+        registry
+            .add(
+                ContainerDef::new("Example", -6)
+                    .field(FieldDef::new("Version", FieldType::UInt8))
+                    .field(FieldDef::new("Offset", FieldType::Int32))
+                    .field(FieldDef::new("Name", FieldType::String))
+                    .field(FieldDef::new("Count", FieldType::UInt32))
+                    .field(FieldDef::new("Flag", FieldType::Int8))
+                    .field(FieldDef::new_struct("Nested", "Nested")),
+            )
+            .unwrap();
+        registry
+            .add(
+                ContainerDef::new("Nested", 452).field(FieldDef::new("Amount", FieldType::Decimal)),
+            )
+            .unwrap();
+
+        let data = b"<1,18,0,-6,5222,2>[1,-20,(5:three),4,,<1,2,0,452,5222,2>[100]]";
+        let mut containers = CMDC_CODEC.decode_with_schema(data, &registry).unwrap();
+        let container = &mut containers.containers[0];
+
+        let field0 = container.fields[0].decode_value().unwrap().unwrap();
+        assert_eq!(field0.as_uint8(), Some(1));
+        let field1 = container.fields[1].decode_value().unwrap().unwrap();
+        assert_eq!(field1.as_int32(), Some(-20));
+        let field2 = container.fields[2].decode_value().unwrap().unwrap();
+        assert_eq!(field2.as_string(), Some("three"));
+        let field3 = container.fields[3].decode_value().unwrap().unwrap();
+        assert_eq!(field3.as_uint32(), Some(4));
+        assert!(container.fields[4].decode_value().unwrap().is_none());
+
+        let field5 = container.fields[5].decode_value().unwrap().unwrap();
+        let mut nested = field5.as_struct().unwrap().clone();
+        let amount = nested.containers[0].fields[0]
+            .decode_value()
+            .unwrap()
+            .unwrap();
+        assert_eq!(amount.as_decimal().unwrap().to_string(), "100");
+    }
+
+    #[test]
+    fn test_decode_struct_list() {
+        let data = b"{<1,5,1,330,5222,2>[200,4,1],<1,5,1,330,5222,2>[202,6,2]}";
+        let containers = CMDC_CODEC.decode_struct_list(data).unwrap();
+        assert_eq!(containers.len(), 2);
+
+        assert_eq!(containers[0].header.key, 330);
+        assert_eq!(containers[0].header.depth, 1);
+        assert_eq!(containers[0].fields.len(), 3);
+        assert_eq!(containers[0].fields[0].data, b"200");
+        assert_eq!(containers[1].fields[0].data, b"202");
+        assert_eq!(containers[1].fields[2].data, b"2");
+    }
+
+    #[test]
+    fn test_decode_empty_struct_list() {
+        let containers = CMDC_CODEC.decode_struct_list(b"{}").unwrap();
+        assert!(containers.is_empty());
+    }
+
+    #[test]
+    fn test_decode_invalid_struct_list() {
+        let err = CMDC_CODEC.decode_struct_list(b"{1,2}").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid cMDC header, first character must be '<'"
+        );
+        let err = CMDC_CODEC
+            .decode_struct_list(b"{<1,1,1,330,5222,2>[1]<1,1,1,330,5222,2>[2]}")
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid cMDC struct list, element must be a single container"
+        );
     }
 
     #[test]
@@ -567,4 +892,85 @@ mod tests {
         let err = CMDC_CODEC.decode_containers(data).unwrap_err();
         assert_eq!(err.to_string(), "Invalid cMDC body, mismatch string length");
     }
+
+    #[test]
+    fn test_strict_decode() {
+        let data = b"<1,8,0,-6,5222,2>[,,,(5:AMF-1),(4:eMBB),(11:SouthWestUK),1]<1,1,0,-5,5222,2>[1000001]<1,7,0,263,5222,2>[2,{<1,5,1,330,5222,2>[200,4,1],<1,5,1,330,5222,2>[202,6,2]},(6:555555)]<1,11,0,626,5222,2>[{1,3,1},,{<1,17,1,624,5222,2>[17485824.0,200,1,(21:Data: Asset + Overage),0:1:5:277,(7:2000000),3,0,,,,,,,,0]},0:1:5:144,{(13:HXS0:1:52:409)}]<1,29,0,208,5222,2>[]";
+        let containers = CmdcCodec::new_strict().decode_containers(data).unwrap();
+        assert_eq!(containers.containers.len(), 5);
+        assert!(STRICT_CMDC_CODEC.is_strict());
+        assert!(!CmdcCodec::default().is_strict());
+
+        // Nested fields decode with the strict codec as well
+        let mut field = containers.containers[2].fields[1].clone();
+        field.field_type = FieldType::Struct;
+        let value = field.decode_value().unwrap().unwrap();
+        assert_eq!(value.as_list().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_strict_decode_invalid_header() {
+        let cases: [(&[u8], &str); 6] = [
+            (
+                b"<256,1,0,-6,5222,2>[1]",
+                "Invalid cMDC header, version 256 out of range",
+            ),
+            (
+                b"<1,-1,0,-6,5222,2>[1]",
+                "Invalid cMDC header, total_field -1 out of range",
+            ),
+            (
+                b"<1,1,128,-6,5222,2>[1]",
+                "Invalid cMDC header, depth 128 out of range",
+            ),
+            (
+                b"<1,1,0,-6,65536,2>[1]",
+                "Invalid cMDC header, schema_version 65536 out of range",
+            ),
+            (
+                b"<1,1,0,-6,5222,-2>[1]",
+                "Invalid cMDC header, ext_version -2 out of range",
+            ),
+            (
+                b"<1,2,0,-6,5222,2>[1,2,3]",
+                "Invalid cMDC body, 3 fields found but header total_field is 2",
+            ),
+        ];
+
+        for (data, expected) in cases {
+            let err = STRICT_CMDC_CODEC.decode_containers(data).unwrap_err();
+            assert_eq!(err.to_string(), expected);
+            // Lenient decoding accepts the same data
+            CMDC_CODEC.decode_containers(data).unwrap();
+        }
+
+        // An empty body has no fields
+        STRICT_CMDC_CODEC
+            .decode_containers(b"<1,0,0,1257,5222,2>[]")
+            .unwrap();
+    }
+
+    #[test]
+    fn test_strict_decode_nested_depth() {
+        let cases: [(&[u8], &str); 3] = [
+            (
+                b"<1,3,0,263,5222,2>[2,{<1,5,2,330,5222,2>[200,4,1]},(6:555555)]",
+                "Invalid cMDC header, nested container depth 1 expected but found 2",
+            ),
+            (
+                b"<1,2,1,1277,5222,2>[4,<1,6,1,-11,5222,2>[800000.0]]",
+                "Invalid cMDC header, nested container depth 2 expected but found 1",
+            ),
+            (
+                b"<1,1,0,263,5222,2>[{<1,1,1,330,5222,2>[<1,1,1,-11,5222,2>[1]]}]",
+                "Invalid cMDC header, nested container depth 2 expected but found 1",
+            ),
+        ];
+
+        for (data, expected) in cases {
+            let err = STRICT_CMDC_CODEC.decode_containers(data).unwrap_err();
+            assert_eq!(err.to_string(), expected);
+            CMDC_CODEC.decode_containers(data).unwrap();
+        }
+    }
 }
@@ -1,4 +1,6 @@
 use super::CmdcCodec;
+use crate::codec::is_native;
+use crate::codec::Codec;
 use crate::error::Error;
 use crate::mdd::Container;
 use crate::mdd::Containers;
@@ -27,6 +29,21 @@ impl CmdcCodec {
         len
     }
 
+    /// Encode containers as a list of structs `{<hdr>[..],<hdr>[..]}`.
+    pub fn encode_struct_list(&self, containers: &[Container]) -> Result<Vec<u8>, Error> {
+        let mut buffer = Vec::new();
+        buffer.write_all(b"{")?;
+        for (i, container) in containers.iter().enumerate() {
+            if i > 0 {
+                buffer.write_all(b",")?;
+            }
+            self.encode_container(&mut buffer, container)?;
+        }
+        buffer.write_all(b"}")?;
+
+        Ok(buffer)
+    }
+
     fn encode_container<W: Write>(
         &self,
         buffer: &mut W,
@@ -63,7 +80,7 @@ impl CmdcCodec {
 
     #[inline]
     fn get_header_len(&self, _header: &Header) -> usize {
-        return 4 + 1 + 1 + 7 + 4 + 3 + 6 + 2;
+        4 + 1 + 1 + 7 + 4 + 3 + 6 + 2
     }
 
     fn encode_body<W: Write>(&self, buffer: &mut W, fields: &[Field]) -> Result<(), Error> {
@@ -72,7 +89,17 @@ impl CmdcCodec {
             if i > 0 {
                 buffer.write_all(b",")?;
             }
-            buffer.write_all(field.data)?;
+            // Fields built from values have no data yet, fields decoded by
+            // another codec have data in its format
+            let encode = match field.data.is_empty() {
+                true => field.value.is_some(),
+                false => !is_native(self, field),
+            };
+            if encode && !field.is_null {
+                buffer.write_all(&self.encode_field(field)?)?;
+            } else {
+                buffer.write_all(field.data)?;
+            }
         }
         buffer.write_all(b"]")?;
 
@@ -93,6 +120,9 @@ impl CmdcCodec {
 mod tests {
     use super::*;
     use crate::cmdc::CMDC_CODEC;
+    use crate::codec::Codec;
+    use crate::mdd::FieldType;
+    use crate::mdd::Value;
     use std::io::BufWriter;
 
     #[test]
@@ -113,6 +143,7 @@ mod tests {
                     Field::raw("(5:three)".as_bytes()),
                     Field::raw("400000".as_bytes()),
                 ],
+                schema: None,
             }],
         };
 
@@ -124,4 +155,81 @@ mod tests {
         let encoded = buffer.into_inner().unwrap();
         assert_eq!(encoded, b"<1,18,0,-6,5222,2>[1,20,(5:three),400000]");
     }
+
+    #[test]
+    fn test_encode_struct_list() {
+        let data = b"{<1,5,1,330,5222,2>[200,4,1],<1,5,1,330,5222,2>[202,6,2]}";
+        let containers = CMDC_CODEC.decode_struct_list(data).unwrap();
+        let encoded = CMDC_CODEC.encode_struct_list(&containers).unwrap();
+        assert_eq!(encoded, data);
+
+        let encoded = CMDC_CODEC.encode_struct_list(&[]).unwrap();
+        assert_eq!(encoded, b"{}");
+    }
+
+    #[test]
+    fn test_decode_nested_struct_list_field() {
+        let data = b"<1,7,0,263,5222,2>[2,{<1,5,1,330,5222,2>[200,4,1],<1,5,1,330,5222,2>[202,6,2]},(6:555555)]";
+        let containers = CMDC_CODEC.decode_containers(data).unwrap();
+        let field = &containers.containers[0].fields[1];
+        assert!(field.is_multi);
+        assert!(field.is_container);
+
+        let mut elements = CMDC_CODEC.decode_struct_list(field.data).unwrap();
+        elements[1].fields[0].field_type = FieldType::UInt32;
+        let value = elements[1].fields[0].decode_value().unwrap().unwrap();
+        assert_eq!(value.as_uint32(), Some(202));
+
+        let encoded = CMDC_CODEC.encode(&containers).unwrap();
+        assert_eq!(encoded, data);
+    }
+
+    #[test]
+    fn test_encode_body_field_sources() {
+        // Fields built from values have no data
+        let value_field = Field {
+            value: Some(Value::UInt32(7)),
+            is_null: false,
+            ..crate::container::null_field(FieldType::UInt32)
+        };
+        // Null fields are written empty even with a value
+        let null_field = Field {
+            value: Some(Value::UInt32(8)),
+            ..crate::container::null_field(FieldType::UInt32)
+        };
+        // Data in another wire format is encoded from its value
+        let json_field = Field {
+            data: br#""a,b""#,
+            field_type: FieldType::String,
+            codec: Some(&crate::json::value::JSON_VALUE_CODEC),
+            is_null: false,
+            ..crate::container::null_field(FieldType::String)
+        };
+        // cMDC data is written as is, without decoding
+        let raw_field = Field::raw(b"not-a-number");
+
+        let mut buffer = Vec::new();
+        CMDC_CODEC
+            .encode_body(
+                &mut buffer,
+                &[value_field, null_field, json_field, raw_field],
+            )
+            .unwrap();
+        assert_eq!(buffer, b"[7,,(3:a,b),not-a-number]");
+    }
+
+    #[test]
+    fn test_encode_body_invalid_value() {
+        let field = Field {
+            value: Some(Value::String("7".to_string())),
+            is_null: false,
+            ..crate::container::null_field(FieldType::UInt32)
+        };
+        let mut buffer = Vec::new();
+        let err = CMDC_CODEC.encode_body(&mut buffer, &[field]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Value type mismatch, expected UInt32 but found String"
+        );
+    }
 }
@@ -1,7 +1,20 @@
+#[cfg(feature = "serde")]
+pub mod de;
 pub mod decode;
 pub mod encode;
+#[cfg(feature = "serde")]
+pub mod ser;
+#[cfg(feature = "serde")]
+pub mod serde_decimal;
 pub mod value;
 
+#[cfg(feature = "serde")]
+pub use de::{from_slice, from_slice_with_schema};
+#[cfg(feature = "serde")]
+pub use ser::{to_vec, to_vec_with_schema};
+
+use crate::codec::field_value;
+use crate::codec::is_native;
 use crate::codec::Codec;
 use crate::error::Error;
 use crate::mdd::Containers;
@@ -10,12 +23,40 @@ use crate::mdd::FieldType;
 use crate::mdd::Value;
 use std::io::BufWriter;
 
-pub static CMDC_CODEC: CmdcCodec = CmdcCodec {};
+pub static CMDC_CODEC: CmdcCodec = CmdcCodec::new();
+
+/// Codec rejecting headers that disagree with the data, see
+/// `CmdcCodec::new_strict`.
+pub static STRICT_CMDC_CODEC: CmdcCodec = CmdcCodec::new_strict();
+
+#[derive(Debug, Clone, Default)]
+pub struct CmdcCodec {
+    strict: bool,
+}
+
+impl CmdcCodec {
+    pub const fn new() -> Self {
+        CmdcCodec { strict: false }
+    }
+
+    /// Codec rejecting header values out of range for their type, bodies
+    /// with more fields than `total_field`, and nested containers whose
+    /// `depth` is not the parent depth + 1. Nested headers are checked in the
+    /// same pass as the body.
+    pub const fn new_strict() -> Self {
+        CmdcCodec { strict: true }
+    }
 
-#[derive(Debug, Clone)]
-pub struct CmdcCodec {}
+    pub fn is_strict(&self) -> bool {
+        self.strict
+    }
+}
 
 impl Codec for CmdcCodec {
+    fn name(&self) -> &'static str {
+        "cmdc"
+    }
+
     fn decode<'a>(&self, data: &'a [u8]) -> Result<Containers<'a>, Error> {
         self.decode_containers(data)
     }
@@ -33,19 +74,12 @@ impl Codec for CmdcCodec {
     }
 
     fn decode_field<'a>(&self, field: &Field<'a>) -> Result<Value<'a>, Error> {
-        match field.field_type {
-            FieldType::Struct => Ok(Value::Struct(self.decode_struct(field.data)?)),
-            FieldType::String => Ok(Value::String(self.decode_string(field.data)?.to_string())),
-            FieldType::Int8 => Ok(Value::Int8(self.decode_int8(field.data)?)),
-            FieldType::Int16 => Ok(Value::Int16(self.decode_int16(field.data)?)),
-            FieldType::Int32 => Ok(Value::Int32(self.decode_int32(field.data)?)),
-            FieldType::Int64 => Ok(Value::Int64(self.decode_int64(field.data)?)),
-            FieldType::UInt8 => Ok(Value::UInt8(self.decode_uint8(field.data)?)),
-            FieldType::UInt16 => Ok(Value::UInt16(self.decode_uint16(field.data)?)),
-            FieldType::UInt32 => Ok(Value::UInt32(self.decode_uint32(field.data)?)),
-            FieldType::UInt64 => Ok(Value::UInt64(self.decode_uint64(field.data)?)),
-            _ => todo!(),
+        if field.is_multi {
+            return Ok(Value::List(
+                self.decode_list(&field.field_type, field.data)?,
+            ));
         }
+        self.decode_value(&field.field_type, field.data)
     }
 
     fn encode_field(&self, field: &Field) -> Result<Vec<u8>, Error> {
@@ -53,24 +87,78 @@ impl Codec for CmdcCodec {
         if field.is_null {
             return Ok(vec![]);
         }
-        // If field has data, use it
-        if !field.data.is_empty() {
+        // If field has cMDC data, use it
+        if !field.data.is_empty() && is_native(self, field) {
             return Ok(field.data.to_vec());
         }
         // Encode field value
-        let field_value = field.get_value()?.unwrap();
-        match field.field_type {
-            FieldType::Struct => Ok(self.encode_struct(field_value.as_struct().unwrap())?),
-            FieldType::String => Ok(self.encode_string(field_value.as_string().unwrap())?),
-            FieldType::Int8 => Ok(self.encode_int8(field_value.as_int8().unwrap())?),
-            FieldType::Int16 => Ok(self.encode_int16(field_value.as_int16().unwrap())?),
-            FieldType::Int32 => Ok(self.encode_int32(field_value.as_int32().unwrap())?),
-            FieldType::Int64 => Ok(self.encode_int64(field_value.as_int64().unwrap())?),
-            FieldType::UInt8 => Ok(self.encode_uint8(field_value.as_uint8().unwrap())?),
-            FieldType::UInt16 => Ok(self.encode_uint16(field_value.as_uint16().unwrap())?),
-            FieldType::UInt32 => Ok(self.encode_uint32(field_value.as_uint32().unwrap())?),
-            FieldType::UInt64 => Ok(self.encode_uint64(field_value.as_uint64().unwrap())?),
-            _ => todo!(),
+        let field_value = field_value(self, field)?;
+        self.encode_value(&field.field_type, &field_value)
+    }
+}
+
+impl CmdcCodec {
+    pub fn decode_value<'a>(
+        &self,
+        field_type: &FieldType,
+        data: &'a [u8],
+    ) -> Result<Value<'a>, Error> {
+        match field_type {
+            FieldType::Struct => Ok(Value::Struct(self.decode_struct(data)?)),
+            FieldType::String => Ok(Value::String(self.decode_string(data)?.to_string())),
+            FieldType::Int8 => Ok(Value::Int8(self.decode_int8(data)?)),
+            FieldType::Int16 => Ok(Value::Int16(self.decode_int16(data)?)),
+            FieldType::Int32 => Ok(Value::Int32(self.decode_int32(data)?)),
+            FieldType::Int64 => Ok(Value::Int64(self.decode_int64(data)?)),
+            FieldType::UInt8 => Ok(Value::UInt8(self.decode_uint8(data)?)),
+            FieldType::UInt16 => Ok(Value::UInt16(self.decode_uint16(data)?)),
+            FieldType::UInt32 => Ok(Value::UInt32(self.decode_uint32(data)?)),
+            FieldType::UInt64 => Ok(Value::UInt64(self.decode_uint64(data)?)),
+            FieldType::Bool => Ok(Value::Bool(self.decode_bool(data)?)),
+            FieldType::Decimal => Ok(Value::Decimal(self.decode_decimal(data)?)),
+            FieldType::DateTime => Ok(Value::DateTime(self.decode_datetime(data)?)),
+            FieldType::Date => Ok(Value::Date(self.decode_date(data)?)),
+            FieldType::Time => Ok(Value::Time(self.decode_time(data)?)),
+            FieldType::ObjectId => Ok(Value::ObjectId(self.decode_object_id(data)?)),
+            FieldType::Unknown => Err(Error::UnsupportedFieldType(
+                "Unsupported field type Unknown, cannot decode field".into(),
+            )),
+        }
+    }
+
+    pub fn encode_value(&self, field_type: &FieldType, value: &Value) -> Result<Vec<u8>, Error> {
+        if let Value::List(values) = value {
+            return self.encode_list(field_type, values);
+        }
+        let mismatch = || {
+            Error::TypeMismatch(format!(
+                "Value type mismatch, expected {:?} but found {}",
+                field_type,
+                value.type_name()
+            ))
+        };
+        match field_type {
+            FieldType::Struct => self.encode_struct(value.as_struct().ok_or_else(mismatch)?),
+            FieldType::String => self.encode_string(value.as_string().ok_or_else(mismatch)?),
+            FieldType::Int8 => self.encode_int8(value.as_int8().ok_or_else(mismatch)?),
+            FieldType::Int16 => self.encode_int16(value.as_int16().ok_or_else(mismatch)?),
+            FieldType::Int32 => self.encode_int32(value.as_int32().ok_or_else(mismatch)?),
+            FieldType::Int64 => self.encode_int64(value.as_int64().ok_or_else(mismatch)?),
+            FieldType::UInt8 => self.encode_uint8(value.as_uint8().ok_or_else(mismatch)?),
+            FieldType::UInt16 => self.encode_uint16(value.as_uint16().ok_or_else(mismatch)?),
+            FieldType::UInt32 => self.encode_uint32(value.as_uint32().ok_or_else(mismatch)?),
+            FieldType::UInt64 => self.encode_uint64(value.as_uint64().ok_or_else(mismatch)?),
+            FieldType::Bool => self.encode_bool(value.as_bool().ok_or_else(mismatch)?),
+            FieldType::Decimal => self.encode_decimal(value.as_decimal().ok_or_else(mismatch)?),
+            FieldType::DateTime => self.encode_datetime(value.as_datetime().ok_or_else(mismatch)?),
+            FieldType::Date => self.encode_date(value.as_date().ok_or_else(mismatch)?),
+            FieldType::Time => self.encode_time(value.as_time().ok_or_else(mismatch)?),
+            FieldType::ObjectId => {
+                self.encode_object_id(value.as_object_id().ok_or_else(mismatch)?)
+            }
+            FieldType::Unknown => Err(Error::UnsupportedFieldType(
+                "Unsupported field type Unknown, cannot encode field".into(),
+            )),
         }
     }
 }
@@ -126,7 +214,7 @@ mod tests {
         assert_eq!(field3.unwrap().as_uint32().unwrap(), 4);
 
         // field 4 is null
-        assert_eq!(container.fields[4].decode_value().unwrap().is_none(), true);
+        assert!(container.fields[4].decode_value().unwrap().is_none());
 
         // field 5 as struct
         let field5 = container.fields[5].decode_value().unwrap().unwrap();
@@ -135,4 +223,230 @@ mod tests {
         assert_eq!(nested_container.containers[0].fields.len(), 1);
         assert_eq!(nested_container.containers[0].fields[0].data, b"100");
     }
+
+    #[test]
+    fn test_decode_encode_decimal_field() {
+        let data = b"<1,5,1,330,5222,2>[4,200,1,17485760.0,-17485824.25]";
+        let mut containers = CMDC_CODEC.decode_containers(data).unwrap();
+        let container = &mut containers.containers[0];
+
+        container.fields[3].field_type = FieldType::Decimal;
+        container.fields[4].field_type = FieldType::Decimal;
+
+        let field3 = container.fields[3].decode_value().unwrap().unwrap();
+        assert_eq!(field3.as_decimal().unwrap().to_string(), "17485760.0");
+        let field4 = container.fields[4].decode_value().unwrap().unwrap();
+        assert_eq!(field4.as_decimal().unwrap().to_string(), "-17485824.25");
+
+        let field = Field {
+            data: b"",
+            field_type: FieldType::Decimal,
+            value: container.fields[4].value.clone(),
+            codec: Some(&CMDC_CODEC),
+            is_multi: false,
+            is_container: false,
+            is_null: false,
+        };
+        let encoded = CMDC_CODEC.encode_field(&field).unwrap();
+        assert_eq!(encoded, b"-17485824.25");
+    }
+
+    #[test]
+    fn test_decode_bool_field() {
+        let data = b"<1,2,1,627,5222,2>[(13:HXS0:1:52:408),1]";
+        let mut containers = CMDC_CODEC.decode_containers(data).unwrap();
+        let container = &mut containers.containers[0];
+
+        container.fields[1].field_type = FieldType::Bool;
+        let field1 = container.fields[1].decode_value().unwrap().unwrap();
+        assert!(field1.as_bool().unwrap());
+    }
+
+    #[test]
+    fn test_decode_datetime_field() {
+        let data = b"<1,14,1,209,5222,2>[,1000,2,1,4,2021-09-07T08:00:25.000000Z,2021-10-07T08:00:25.000000Z]";
+        let mut containers = CMDC_CODEC.decode_containers(data).unwrap();
+        let container = &mut containers.containers[0];
+
+        container.fields[5].field_type = FieldType::DateTime;
+        let field5 = container.fields[5].decode_value().unwrap().unwrap();
+        let v = field5.as_datetime().unwrap();
+        assert_eq!((v.year, v.month, v.day), (2021, 9, 7));
+        assert_eq!((v.hour, v.minute, v.second), (8, 0, 25));
+
+        let field = Field {
+            data: b"",
+            field_type: FieldType::DateTime,
+            value: container.fields[5].value.clone(),
+            codec: Some(&CMDC_CODEC),
+            is_multi: false,
+            is_container: false,
+            is_null: false,
+        };
+        let encoded = CMDC_CODEC.encode_field(&field).unwrap();
+        assert_eq!(encoded, b"2021-09-07T08:00:25.000000Z");
+    }
+
+    #[test]
+    fn test_decode_date_time_field() {
+        let data = b"<1,18,0,-6,5222,2>[,(6:value2),3,2021-09-07T08:00:25.000001Z,2021-10-31,09:13:02.667997Z,88,5.5,]";
+        let mut containers = CMDC_CODEC.decode_containers(data).unwrap();
+        let container = &mut containers.containers[0];
+
+        container.fields[4].field_type = FieldType::Date;
+        container.fields[5].field_type = FieldType::Time;
+
+        let field4 = container.fields[4].decode_value().unwrap().unwrap();
+        assert_eq!(field4.as_date().unwrap().to_string(), "2021-10-31");
+        let field5 = container.fields[5].decode_value().unwrap().unwrap();
+        assert_eq!(field5.as_time().unwrap().microsecond, 667997);
+
+        let encoded = CMDC_CODEC.encode(&containers).unwrap();
+        assert_eq!(encoded, data);
+    }
+
+    #[test]
+    fn test_decode_object_id_field() {
+        let data = b"<1,29,0,208,5222,2>[0:1:5:279,(7:1000001),0:1:5:283,,4]";
+        let mut containers = CMDC_CODEC.decode_containers(data).unwrap();
+        let container = &mut containers.containers[0];
+
+        container.fields[0].field_type = FieldType::ObjectId;
+        container.fields[2].field_type = FieldType::ObjectId;
+
+        let field0 = *container.fields[0]
+            .decode_value()
+            .unwrap()
+            .unwrap()
+            .as_object_id()
+            .unwrap();
+        let field2 = *container.fields[2]
+            .decode_value()
+            .unwrap()
+            .unwrap()
+            .as_object_id()
+            .unwrap();
+        assert_eq!(field0.to_string(), "0:1:5:279");
+        assert!(field0 < field2);
+    }
+
+    #[test]
+    fn test_decode_list_field() {
+        let data = b"<1,18,0,-6,5222,2>[0,{1,2,3},,,300,{4,5}]";
+        let mut containers = CMDC_CODEC.decode_containers(data).unwrap();
+        let container = &mut containers.containers[0];
+
+        container.fields[1].field_type = FieldType::Int32;
+        let field1 = container.fields[1].decode_value().unwrap().unwrap();
+        let values: Vec<i32> = field1
+            .as_list()
+            .unwrap()
+            .iter()
+            .map(|v| v.as_ref().unwrap().as_int32().unwrap())
+            .collect();
+        assert_eq!(values, [1, 2, 3]);
+
+        let field = Field {
+            data: b"",
+            field_type: FieldType::Int32,
+            value: container.fields[1].value.clone(),
+            codec: Some(&CMDC_CODEC),
+            is_multi: true,
+            is_container: false,
+            is_null: false,
+        };
+        let encoded = CMDC_CODEC.encode_field(&field).unwrap();
+        assert_eq!(encoded, b"{1,2,3}");
+    }
+
+    #[test]
+    fn test_decode_unknown_field_type() {
+        let data = b"<1,18,0,-6,5222,2>[1,{1,2}]";
+        let mut containers = CMDC_CODEC.decode_containers(data).unwrap();
+        let container = &mut containers.containers[0];
+
+        let err = container.fields[0].decode_value().unwrap_err();
+        assert!(matches!(err, Error::UnsupportedFieldType(_)));
+        assert_eq!(
+            err.to_string(),
+            "Unsupported field type Unknown, cannot decode field"
+        );
+
+        let err = container.fields[1].decode_value().unwrap_err();
+        assert!(matches!(err, Error::UnsupportedFieldType(_)));
+    }
+
+    #[test]
+    fn test_encode_unknown_field_type() {
+        let field = Field {
+            data: b"",
+            field_type: FieldType::Unknown,
+            value: Some(Value::Int32(1)),
+            codec: Some(&CMDC_CODEC),
+            is_multi: false,
+            is_container: false,
+            is_null: false,
+        };
+        let err = CMDC_CODEC.encode_field(&field).unwrap_err();
+        assert!(matches!(err, Error::UnsupportedFieldType(_)));
+        assert_eq!(
+            err.to_string(),
+            "Unsupported field type Unknown, cannot encode field"
+        );
+    }
+
+    #[test]
+    fn test_encode_value_type_mismatch() {
+        let field = Field {
+            data: b"",
+            field_type: FieldType::Int32,
+            value: Some(Value::String("foo".into())),
+            codec: Some(&CMDC_CODEC),
+            is_multi: false,
+            is_container: false,
+            is_null: false,
+        };
+        let err = CMDC_CODEC.encode_field(&field).unwrap_err();
+        assert!(matches!(err, Error::TypeMismatch(_)));
+        assert_eq!(
+            err.to_string(),
+            "Value type mismatch, expected Int32 but found String"
+        );
+    }
+
+    #[test]
+    fn test_encode_list_element_type_mismatch() {
+        let field = Field {
+            data: b"",
+            field_type: FieldType::Bool,
+            value: Some(Value::List(vec![
+                Some(Value::Bool(true)),
+                Some(Value::UInt8(1)),
+            ])),
+            codec: Some(&CMDC_CODEC),
+            is_multi: true,
+            is_container: false,
+            is_null: false,
+        };
+        let err = CMDC_CODEC.encode_field(&field).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Value type mismatch, expected Bool but found UInt8"
+        );
+    }
+
+    #[test]
+    fn test_encode_field_not_decoded() {
+        let field = Field {
+            data: b"",
+            field_type: FieldType::Int32,
+            value: None,
+            codec: Some(&CMDC_CODEC),
+            is_multi: false,
+            is_container: false,
+            is_null: false,
+        };
+        let err = CMDC_CODEC.encode_field(&field).unwrap_err();
+        assert_eq!(err.to_string(), "Field not decoded yet");
+    }
 }
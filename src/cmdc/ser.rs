@@ -0,0 +1,663 @@
+//! Serialize Rust values to cMDC with serde.
+//!
+//! Structs become containers, named `Name:key` or
+//! `Name:key:schema_version:ext_version` with `#[serde(rename = "..")]`.
+//! Without a schema the fields are written in declaration order, with a
+//! schema they are placed by name, padded to the schema's field count, and
+//! the key may be left out of the struct name, as in
+//! `MtxBalanceInfo::5222:2`. `None` is written as an empty field, sequences
+//! as `{..}` lists and nested structs as nested containers. A sequence of
+//! structs at the top level is written as consecutive containers.
+//!
+//! Floats are rejected since they cannot be written as decimals without
+//! loss, use `BigDecimal` fields with [`serde_decimal`](super::serde_decimal)
+//! instead.
+
+use super::CMDC_CODEC;
+use crate::codec::Codec;
+use crate::container::null_field;
+use crate::container::set_depth;
+use crate::datetime::Date;
+use crate::datetime::DateTime;
+use crate::datetime::Time;
+use crate::error::Error;
+use crate::mdd::Container;
+use crate::mdd::Containers;
+use crate::mdd::Field;
+use crate::mdd::FieldType;
+use crate::mdd::Header;
+use crate::mdd::Value;
+use crate::object_id::ObjectId;
+use crate::schema::ContainerSchema;
+use crate::schema::SchemaRegistry;
+use bigdecimal::BigDecimal;
+use serde::ser;
+use serde::Serialize;
+use std::sync::Arc;
+
+/// Newtype struct names marking values written with their cMDC type
+/// rather than as strings.
+pub(crate) const DATETIME: &str = "$rust_mdd::DateTime";
+pub(crate) const DATE: &str = "$rust_mdd::Date";
+pub(crate) const TIME: &str = "$rust_mdd::Time";
+pub(crate) const OBJECT_ID: &str = "$rust_mdd::ObjectId";
+pub(crate) const DECIMAL: &str = "$rust_mdd::Decimal";
+
+pub fn to_vec<T: ?Sized + Serialize>(value: &T) -> Result<Vec<u8>, Error> {
+    serialize(value, None)
+}
+
+/// Serialize with the layout of the containers taken from the registry.
+pub fn to_vec_with_schema<T: ?Sized + Serialize>(
+    value: &T,
+    registry: &SchemaRegistry,
+) -> Result<Vec<u8>, Error> {
+    serialize(value, Some(registry))
+}
+
+fn serialize<T: ?Sized + Serialize>(
+    value: &T,
+    registry: Option<&SchemaRegistry>,
+) -> Result<Vec<u8>, Error> {
+    let value = value.serialize(ValueSerializer { registry })?;
+    let containers = match value {
+        Some(Value::Struct(containers)) => containers.containers,
+        Some(Value::List(values)) => values
+            .into_iter()
+            .map(|value| match value {
+                Some(Value::Struct(containers)) => Ok(containers.containers),
+                _ => Err(top_level_error()),
+            })
+            .collect::<Result<Vec<_>, Error>>()?
+            .into_iter()
+            .flatten()
+            .collect(),
+        _ => return Err(top_level_error()),
+    };
+    CMDC_CODEC.encode(&Containers { containers })
+}
+
+fn top_level_error() -> Error {
+    Error::EncodeError("Top level value must be a struct or a sequence of structs".into())
+}
+
+/// Container key and versions from a struct name such as
+/// `MtxBalanceInfo:330:5222:2`.
+pub(crate) struct StructName<'a> {
+    pub name: &'a str,
+    pub key: Option<i32>,
+    pub schema_version: u16,
+    pub ext_version: u16,
+}
+
+impl<'a> StructName<'a> {
+    pub fn parse(s: &'a str) -> Result<Self, Error> {
+        let err = || {
+            Error::SchemaError(format!(
+                "Invalid struct name '{}', Name:key or Name:key:schema_version:ext_version expected",
+                s
+            ))
+        };
+        let parts: Vec<&str> = s.split(':').collect();
+        let key = |key: &str| match key {
+            "" => Ok(None),
+            key => key.parse().map(Some).map_err(|_| err()),
+        };
+        let (key, schema_version, ext_version) = match parts.as_slice() {
+            [_] => (None, 0, 0),
+            [_, k] => (key(k)?, 0, 0),
+            [_, k, sv, ev] => (
+                key(k)?,
+                sv.parse().map_err(|_| err())?,
+                ev.parse().map_err(|_| err())?,
+            ),
+            _ => return Err(err()),
+        };
+        Ok(StructName {
+            name: parts[0],
+            key,
+            schema_version,
+            ext_version,
+        })
+    }
+}
+
+struct ValueSerializer<'r> {
+    registry: Option<&'r SchemaRegistry>,
+}
+
+type Output = Option<Value<'static>>;
+
+impl<'r> ser::Serializer for ValueSerializer<'r> {
+    type Ok = Output;
+    type Error = Error;
+    type SerializeSeq = SeqSerializer<'r>;
+    type SerializeTuple = SeqSerializer<'r>;
+    type SerializeTupleStruct = SeqSerializer<'r>;
+    type SerializeTupleVariant = ser::Impossible<Output, Error>;
+    type SerializeMap = ser::Impossible<Output, Error>;
+    type SerializeStruct = StructSerializer<'r>;
+    type SerializeStructVariant = ser::Impossible<Output, Error>;
+
+    fn serialize_bool(self, v: bool) -> Result<Output, Error> {
+        Ok(Some(Value::Bool(v)))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Output, Error> {
+        Ok(Some(Value::Int8(v)))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Output, Error> {
+        Ok(Some(Value::Int16(v)))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Output, Error> {
+        Ok(Some(Value::Int32(v)))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Output, Error> {
+        Ok(Some(Value::Int64(v)))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Output, Error> {
+        Ok(Some(Value::UInt8(v)))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Output, Error> {
+        Ok(Some(Value::UInt16(v)))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Output, Error> {
+        Ok(Some(Value::UInt32(v)))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Output, Error> {
+        Ok(Some(Value::UInt64(v)))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Output, Error> {
+        self.serialize_f64(v as f64)
+    }
+
+    fn serialize_f64(self, _v: f64) -> Result<Output, Error> {
+        Err(Error::EncodeError(
+            "Cannot serialize floats to cMDC without loss, use serde_decimal".into(),
+        ))
+    }
+
+    fn serialize_char(self, v: char) -> Result<Output, Error> {
+        Ok(Some(Value::String(v.to_string())))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Output, Error> {
+        Ok(Some(Value::String(v.to_string())))
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<Output, Error> {
+        Err(unsupported("bytes"))
+    }
+
+    fn serialize_none(self) -> Result<Output, Error> {
+        Ok(None)
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<Output, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Output, Error> {
+        Ok(None)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Output, Error> {
+        Ok(None)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Output, Error> {
+        Ok(Some(Value::String(variant.to_string())))
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<Output, Error> {
+        let parse: fn(&str) -> Result<Value<'static>, Error> = match name {
+            DATETIME => |s| Ok(Value::DateTime(s.parse::<DateTime>()?)),
+            DATE => |s| Ok(Value::Date(s.parse::<Date>()?)),
+            TIME => |s| Ok(Value::Time(s.parse::<Time>()?)),
+            OBJECT_ID => |s| Ok(Value::ObjectId(s.parse::<ObjectId>()?)),
+            DECIMAL => |s| Ok(Value::Decimal(s.parse::<BigDecimal>()?)),
+            _ => return value.serialize(self),
+        };
+        match value.serialize(self)? {
+            Some(Value::String(s)) => parse(&s).map(Some),
+            _ => Err(Error::EncodeError(format!("Invalid value for {}", name))),
+        }
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<Output, Error> {
+        Err(unsupported("enum variants with data"))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SeqSerializer<'r>, Error> {
+        Ok(SeqSerializer {
+            registry: self.registry,
+            values: Vec::with_capacity(len.unwrap_or(0)),
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SeqSerializer<'r>, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<SeqSerializer<'r>, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Error> {
+        Err(unsupported("enum variants with data"))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Error> {
+        Err(unsupported("maps"))
+    }
+
+    fn serialize_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<StructSerializer<'r>, Error> {
+        let name = StructName::parse(name)?;
+        let mut header = Header {
+            version: 1,
+            total_field: 0,
+            depth: 0,
+            key: 0,
+            schema_version: name.schema_version,
+            ext_version: name.ext_version,
+        };
+
+        let schema = match self.registry {
+            Some(registry) => {
+                let def = registry.get_by_name(name.name).ok_or_else(|| {
+                    Error::SchemaError(format!("Unknown container '{}'", name.name))
+                })?;
+                header.key = name.key.unwrap_or(def.key);
+                Some(registry.lookup_header(&header)?)
+            }
+            None => {
+                header.key = name.key.ok_or_else(|| {
+                    Error::SchemaError(format!(
+                        "Missing container key in struct name '{}'",
+                        name.name
+                    ))
+                })?;
+                None
+            }
+        };
+
+        Ok(StructSerializer {
+            registry: self.registry,
+            header,
+            fields: Vec::with_capacity(len),
+            schema,
+            position: 0,
+        })
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Error> {
+        Err(unsupported("enum variants with data"))
+    }
+}
+
+fn unsupported(what: &str) -> Error {
+    Error::EncodeError(format!("Cannot serialize {} to cMDC", what))
+}
+
+struct SeqSerializer<'r> {
+    registry: Option<&'r SchemaRegistry>,
+    values: Vec<Option<Value<'static>>>,
+}
+
+impl<'r> ser::SerializeSeq for SeqSerializer<'r> {
+    type Ok = Output;
+    type Error = Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        let value = value.serialize(ValueSerializer {
+            registry: self.registry,
+        })?;
+        if let Some(Value::List(_)) = value {
+            return Err(unsupported("nested sequences"));
+        }
+        self.values.push(value);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Output, Error> {
+        Ok(Some(Value::List(self.values)))
+    }
+}
+
+impl<'r> ser::SerializeTuple for SeqSerializer<'r> {
+    type Ok = Output;
+    type Error = Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Output, Error> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+impl<'r> ser::SerializeTupleStruct for SeqSerializer<'r> {
+    type Ok = Output;
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Output, Error> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+struct StructSerializer<'r> {
+    registry: Option<&'r SchemaRegistry>,
+    header: Header,
+    fields: Vec<Field<'static>>,
+    schema: Option<Arc<ContainerSchema>>,
+    position: usize,
+}
+
+impl<'r> ser::SerializeStruct for StructSerializer<'r> {
+    type Ok = Output;
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        let idx = match &self.schema {
+            Some(schema) => schema.field_index(key).ok_or_else(|| {
+                Error::UnknownField(format!(
+                    "Unknown field '{}' in container '{}'",
+                    key, schema.name
+                ))
+            })?,
+            None => self.position,
+        };
+        self.position += 1;
+
+        let value = value.serialize(ValueSerializer {
+            registry: self.registry,
+        })?;
+        if self.fields.len() <= idx {
+            self.fields
+                .resize_with(idx + 1, || null_field(FieldType::Unknown));
+        }
+        let field = &mut self.fields[idx];
+        field.field_type = value.as_ref().map(field_type).unwrap_or(FieldType::Unknown);
+        field.is_multi = matches!(value, Some(Value::List(_)));
+        field.is_container = field.field_type == FieldType::Struct;
+        field.is_null = value.is_none();
+        field.value = value;
+        Ok(())
+    }
+
+    fn skip_field(&mut self, _key: &'static str) -> Result<(), Error> {
+        self.position += 1;
+        Ok(())
+    }
+
+    fn end(mut self) -> Result<Output, Error> {
+        if let Some(schema) = &self.schema {
+            self.fields
+                .resize_with(schema.fields.len(), || null_field(FieldType::Unknown));
+        }
+        self.header.total_field = u8::try_from(self.fields.len()).map_err(|_| {
+            Error::EncodeError(format!(
+                "Too many fields {} in container with key {}",
+                self.fields.len(),
+                self.header.key
+            ))
+        })?;
+        let mut container = Container::new(self.header, self.fields);
+        set_depth(&mut container, 0);
+        Ok(Some(Value::Struct(Containers {
+            containers: vec![container],
+        })))
+    }
+}
+
+/// Field type written for a value, the element type for lists.
+fn field_type(value: &Value) -> FieldType {
+    match value {
+        Value::Struct(_) => FieldType::Struct,
+        Value::String(_) => FieldType::String,
+        Value::Int8(_) => FieldType::Int8,
+        Value::Int16(_) => FieldType::Int16,
+        Value::Int32(_) => FieldType::Int32,
+        Value::Int64(_) => FieldType::Int64,
+        Value::UInt8(_) => FieldType::UInt8,
+        Value::UInt16(_) => FieldType::UInt16,
+        Value::UInt32(_) => FieldType::UInt32,
+        Value::UInt64(_) => FieldType::UInt64,
+        Value::Bool(_) => FieldType::Bool,
+        Value::Decimal(_) => FieldType::Decimal,
+        Value::DateTime(_) => FieldType::DateTime,
+        Value::Date(_) => FieldType::Date,
+        Value::Time(_) => FieldType::Time,
+        Value::ObjectId(_) => FieldType::ObjectId,
+        Value::List(values) => values
+            .iter()
+            .flatten()
+            .next()
+            .map(field_type)
+            .unwrap_or(FieldType::Unknown),
+    }
+}
+
+/// Serialize a cMDC value type as its text, wrapped in a newtype struct
+/// named `name` so the cMDC serializer writes it with its own type.
+pub(crate) fn serialize_str_newtype<S: ser::Serializer, T: std::fmt::Display>(
+    serializer: S,
+    name: &'static str,
+    value: &T,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_newtype_struct(name, &value.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::ContainerDef;
+    use crate::schema::FieldDef;
+    use serde::Serialize;
+
+    #[derive(Serialize)]
+    #[serde(rename = "Threshold:331")]
+    struct Threshold {
+        id: u32,
+        label: Option<String>,
+    }
+
+    #[derive(Serialize)]
+    #[serde(rename = "BalanceInfo:330:5222:2")]
+    struct BalanceInfo {
+        template_id: u32,
+        name: String,
+        amount: Option<u32>,
+        tags: Vec<i32>,
+        thresholds: Vec<Threshold>,
+        primary: Option<Threshold>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        note: Option<String>,
+        owner: ObjectId,
+        updated: DateTime,
+        active: bool,
+    }
+
+    fn balance() -> BalanceInfo {
+        BalanceInfo {
+            template_id: 200,
+            name: "main".into(),
+            amount: None,
+            tags: vec![1, -2],
+            thresholds: vec![
+                Threshold {
+                    id: 1,
+                    label: Some("low".into()),
+                },
+                Threshold { id: 2, label: None },
+            ],
+            primary: Some(Threshold { id: 3, label: None }),
+            note: None,
+            owner: ObjectId::new(0, 1, 5, 279),
+            updated: "2021-09-07T08:00:25.000000Z".parse().unwrap(),
+            active: true,
+        }
+    }
+
+    #[test]
+    fn test_to_vec() {
+        let data = to_vec(&balance()).unwrap();
+        assert_eq!(
+            String::from_utf8(data).unwrap(),
+            "<1,10,0,330,5222,2>[200,(4:main),,{1,-2},\
+             {<1,2,1,331,0,0>[1,(3:low)],<1,2,1,331,0,0>[2,]},<1,2,1,331,0,0>[3,],,\
+             0:1:5:279,2021-09-07T08:00:25.000000Z,1]"
+        );
+    }
+
+    #[test]
+    fn test_to_vec_sequence() {
+        let thresholds = [
+            Threshold { id: 1, label: None },
+            Threshold { id: 2, label: None },
+        ];
+        let data = to_vec(&thresholds).unwrap();
+        assert_eq!(data, b"<1,2,0,331,0,0>[1,]<1,2,0,331,0,0>[2,]");
+    }
+
+    #[test]
+    fn test_to_vec_with_schema() {
+        #[derive(Serialize)]
+        #[serde(rename = "MtxBalanceInfo", rename_all = "PascalCase")]
+        struct Balance {
+            amount: u32,
+            template_id: u32,
+        }
+
+        let mut registry = SchemaRegistry::new();
+        registry
+            .add(
+                ContainerDef::new("MtxBalanceInfo", 330)
+                    .field(FieldDef::new("TemplateId", FieldType::UInt32))
+                    .field(FieldDef::new("ResourceId", FieldType::UInt32))
+                    .field(FieldDef::new("Amount", FieldType::UInt32))
+                    .field(FieldDef::new("Limit", FieldType::UInt32)),
+            )
+            .unwrap();
+
+        let balance = Balance {
+            amount: 10,
+            template_id: 200,
+        };
+        let data = to_vec_with_schema(&balance, &registry).unwrap();
+        assert_eq!(data, b"<1,4,0,330,0,0>[200,,10,]");
+
+        #[derive(Serialize)]
+        #[serde(rename = "MtxBalanceInfo")]
+        struct Unknown {
+            region: u32,
+        }
+        let err = to_vec_with_schema(&Unknown { region: 1 }, &registry).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Unknown field 'region' in container 'MtxBalanceInfo'"
+        );
+    }
+
+    #[test]
+    fn test_to_vec_errors() {
+        #[derive(Serialize)]
+        struct NoKey {
+            id: u32,
+        }
+        let err = to_vec(&NoKey { id: 1 }).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Missing container key in struct name 'NoKey'"
+        );
+
+        #[derive(Serialize)]
+        #[serde(rename = "Nested:1")]
+        struct Nested {
+            values: Vec<Vec<u32>>,
+        }
+        let err = to_vec(&Nested {
+            values: vec![vec![1]],
+        })
+        .unwrap_err();
+        assert_eq!(err.to_string(), "Cannot serialize nested sequences to cMDC");
+
+        #[derive(Serialize)]
+        #[serde(rename = "Rate:1")]
+        struct Rate {
+            value: f64,
+        }
+        let err = to_vec(&Rate { value: 0.1 }).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Cannot serialize floats to cMDC without loss, use serde_decimal"
+        );
+
+        let err = to_vec(&1u32).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Top level value must be a struct or a sequence of structs"
+        );
+
+        #[derive(Serialize)]
+        #[serde(rename = "Bad:x")]
+        struct BadName {}
+        let err = to_vec(&BadName {}).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid struct name 'Bad:x', Name:key or Name:key:schema_version:ext_version expected"
+        );
+    }
+}
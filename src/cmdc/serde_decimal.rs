@@ -0,0 +1,68 @@
+//! Serde with-module for `BigDecimal` fields, written as cMDC decimals and
+//! as strings in other formats.
+//!
+//! ```ignore
+//! #[derive(Serialize, Deserialize)]
+//! struct Balance {
+//!     #[serde(with = "rust_mdd::cmdc::serde_decimal")]
+//!     amount: BigDecimal,
+//!     #[serde(with = "rust_mdd::cmdc::serde_decimal::option", default)]
+//!     limit: Option<BigDecimal>,
+//! }
+//! ```
+
+use super::de::deserialize_str_newtype;
+use super::ser::serialize_str_newtype;
+use super::ser::DECIMAL;
+use bigdecimal::BigDecimal;
+use serde::Deserializer;
+use serde::Serializer;
+
+pub fn serialize<S: Serializer>(value: &BigDecimal, serializer: S) -> Result<S::Ok, S::Error> {
+    serialize_str_newtype(serializer, DECIMAL, value)
+}
+
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<BigDecimal, D::Error> {
+    deserialize_str_newtype(deserializer, DECIMAL)
+}
+
+/// For `Option<BigDecimal>` fields.
+pub mod option {
+    use bigdecimal::BigDecimal;
+    use serde::Deserialize;
+    use serde::Deserializer;
+    use serde::Serialize;
+    use serde::Serializer;
+
+    struct Decimal<'a>(&'a BigDecimal);
+
+    impl<'a> Serialize for Decimal<'a> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            super::serialize(self.0, serializer)
+        }
+    }
+
+    struct OwnedDecimal(BigDecimal);
+
+    impl<'de> Deserialize<'de> for OwnedDecimal {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            super::deserialize(deserializer).map(OwnedDecimal)
+        }
+    }
+
+    pub fn serialize<S: Serializer>(
+        value: &Option<BigDecimal>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match value {
+            Some(v) => serializer.serialize_some(&Decimal(v)),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<BigDecimal>, D::Error> {
+        Ok(Option::<OwnedDecimal>::deserialize(deserializer)?.map(|v| v.0))
+    }
+}
@@ -1,16 +1,66 @@
 use crate::cmdc::CmdcCodec;
 use crate::cmdc::Containers;
 use crate::codec::Codec;
+use crate::datetime::Date;
+use crate::datetime::DateTime;
+use crate::datetime::Time;
 use crate::error::Error;
+use crate::mdd::FieldType;
+use crate::mdd::Value;
+use crate::object_id::ObjectId;
+use bigdecimal::BigDecimal;
 use core::str::from_utf8;
+use std::str::FromStr;
 
 impl CmdcCodec {
     pub fn decode_struct<'a>(&self, data: &'a [u8]) -> Result<Containers<'a>, Error> {
-        Ok(self.decode(data)?)
+        self.decode(data)
     }
 
     pub fn encode_struct(&self, containers: &Containers) -> Result<Vec<u8>, Error> {
-        Ok(self.encode(containers)?)
+        self.encode(containers)
+    }
+
+    pub fn decode_list<'a>(
+        &self,
+        field_type: &FieldType,
+        data: &'a [u8],
+    ) -> Result<Vec<Option<Value<'a>>>, Error> {
+        let mut values = vec![];
+        for element in self.split_list(data)? {
+            if element.is_empty() {
+                values.push(None);
+            } else {
+                values.push(Some(self.decode_value(field_type, element)?));
+            }
+        }
+        Ok(values)
+    }
+
+    pub fn encode_list(
+        &self,
+        field_type: &FieldType,
+        values: &[Option<Value>],
+    ) -> Result<Vec<u8>, Error> {
+        // `{}` is an empty list, so a single empty element has no form
+        if let [None] = values {
+            return Err(Error::EncodeError(
+                "Cannot encode a list of a single empty element, it would decode as an empty list"
+                    .into(),
+            ));
+        }
+        let mut data = Vec::new();
+        data.push(b'{');
+        for (i, value) in values.iter().enumerate() {
+            if i > 0 {
+                data.push(b',');
+            }
+            if let Some(value) = value {
+                data.extend_from_slice(&self.encode_value(field_type, value)?);
+            }
+        }
+        data.push(b'}');
+        Ok(data)
     }
 
     pub fn decode_string<'a>(&self, data: &'a [u8]) -> Result<&'a str, Error> {
@@ -24,17 +74,24 @@ impl CmdcCodec {
         for (idx, &c) in data.iter().enumerate().skip(1) {
             if c == b':' {
                 let temp = &data[1..idx];
-                let len = Self::bytes_to_int(temp)? as usize;
-
-                if idx + 1 + len > data.len() {
-                    return Err(Error::DecodeError(
-                        format!("Invalid string length, {} is too long", len).into(),
-                    ));
+                let len = usize::try_from(Self::bytes_to_int(temp)?).map_err(|_| {
+                    Error::DecodeError(format!(
+                        "Invalid string length '{}'",
+                        String::from_utf8_lossy(temp)
+                    ))
+                })?;
+
+                if idx + 1 + len >= data.len() {
+                    return Err(Error::DecodeError(format!(
+                        "Invalid string length, {} is too long",
+                        len
+                    )));
                 }
                 if data[idx + 1 + len] != b')' {
-                    return Err(Error::DecodeError(
-                        format!("Invalid string length, {} is too short", len).into(),
-                    ));
+                    return Err(Error::DecodeError(format!(
+                        "Invalid string length, {} is too short",
+                        len
+                    )));
                 }
                 let str = from_utf8(&data[idx + 1..idx + 1 + len])?;
                 return Ok(str);
@@ -125,11 +182,77 @@ impl CmdcCodec {
     pub fn encode_uint64(&self, v: u64) -> Result<Vec<u8>, Error> {
         Ok(v.to_string().into_bytes())
     }
+
+    pub fn decode_bool(&self, data: &[u8]) -> Result<bool, Error> {
+        match data {
+            b"0" => Ok(false),
+            b"1" => Ok(true),
+            _ => Err(Error::DecodeError(format!(
+                "Invalid bool value '{}', 0 or 1 expected",
+                String::from_utf8_lossy(data)
+            ))),
+        }
+    }
+
+    pub fn encode_bool(&self, v: bool) -> Result<Vec<u8>, Error> {
+        Ok(if v { b"1".to_vec() } else { b"0".to_vec() })
+    }
+
+    /// Only the plain form decimals are written in is accepted, `1e-3` or
+    /// `+5` would encode back as `0.001` and `5`.
+    pub fn decode_decimal(&self, data: &[u8]) -> Result<BigDecimal, Error> {
+        let s = from_utf8(data)?;
+        let v = BigDecimal::from_str(s)?;
+        if v.to_string() != s {
+            return Err(Error::DecodeError(format!("Invalid decimal value '{}'", s)));
+        }
+        Ok(v)
+    }
+
+    pub fn encode_decimal(&self, v: &BigDecimal) -> Result<Vec<u8>, Error> {
+        Ok(v.to_string().into_bytes())
+    }
+
+    pub fn decode_datetime(&self, data: &[u8]) -> Result<DateTime, Error> {
+        let s = from_utf8(data)?;
+        s.parse::<DateTime>()
+    }
+
+    pub fn encode_datetime(&self, v: &DateTime) -> Result<Vec<u8>, Error> {
+        Ok(v.to_string().into_bytes())
+    }
+
+    pub fn decode_date(&self, data: &[u8]) -> Result<Date, Error> {
+        let s = from_utf8(data)?;
+        s.parse::<Date>()
+    }
+
+    pub fn encode_date(&self, v: &Date) -> Result<Vec<u8>, Error> {
+        Ok(v.to_string().into_bytes())
+    }
+
+    pub fn decode_time(&self, data: &[u8]) -> Result<Time, Error> {
+        let s = from_utf8(data)?;
+        s.parse::<Time>()
+    }
+
+    pub fn encode_time(&self, v: &Time) -> Result<Vec<u8>, Error> {
+        Ok(v.to_string().into_bytes())
+    }
+
+    pub fn decode_object_id(&self, data: &[u8]) -> Result<ObjectId, Error> {
+        let s = from_utf8(data)?;
+        s.parse::<ObjectId>()
+    }
+
+    pub fn encode_object_id(&self, v: &ObjectId) -> Result<Vec<u8>, Error> {
+        Ok(v.to_string().into_bytes())
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    // use super::*;
+    use super::*;
     use crate::cmdc::CMDC_CODEC;
 
     #[test]
@@ -147,6 +270,79 @@ mod tests {
         assert_eq!(encoded, data);
     }
 
+    #[test]
+    fn test_encode_decode_list() {
+        let data = b"{1,3,1}";
+        let values = CMDC_CODEC.decode_list(&FieldType::UInt8, data).unwrap();
+        assert_eq!(values.len(), 3);
+        assert_eq!(values[0].as_ref().unwrap().as_uint8(), Some(1));
+        assert_eq!(values[1].as_ref().unwrap().as_uint8(), Some(3));
+        assert_eq!(values[2].as_ref().unwrap().as_uint8(), Some(1));
+        let encoded = CMDC_CODEC.encode_list(&FieldType::UInt8, &values).unwrap();
+        assert_eq!(encoded, data);
+    }
+
+    #[test]
+    fn test_encode_decode_string_list() {
+        let data = b"{(13:HXS0:1:52:409),,(4:a,}b)}";
+        let values = CMDC_CODEC.decode_list(&FieldType::String, data).unwrap();
+        assert_eq!(values.len(), 3);
+        assert_eq!(
+            values[0].as_ref().unwrap().as_string(),
+            Some("HXS0:1:52:409")
+        );
+        assert!(values[1].is_none());
+        assert_eq!(values[2].as_ref().unwrap().as_string(), Some("a,}b"));
+        let encoded = CMDC_CODEC.encode_list(&FieldType::String, &values).unwrap();
+        assert_eq!(encoded, data);
+    }
+
+    #[test]
+    fn test_encode_decode_empty_list() {
+        let values = CMDC_CODEC.decode_list(&FieldType::Int32, b"{}").unwrap();
+        assert!(values.is_empty());
+        let encoded = CMDC_CODEC.encode_list(&FieldType::Int32, &values).unwrap();
+        assert_eq!(encoded, b"{}");
+    }
+
+    #[test]
+    fn test_encode_single_empty_element_list() {
+        let err = CMDC_CODEC
+            .encode_list(&FieldType::Int32, &[None])
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Cannot encode a list of a single empty element, it would decode as an empty list"
+        );
+
+        let encoded = CMDC_CODEC
+            .encode_list(&FieldType::Int32, &[None, None])
+            .unwrap();
+        assert_eq!(encoded, b"{,}");
+        let values = CMDC_CODEC.decode_list(&FieldType::Int32, &encoded).unwrap();
+        assert_eq!(values.len(), 2);
+    }
+
+    #[test]
+    fn test_encode_decode_struct_list() {
+        let data = b"{<1,5,1,330,5222,2>[200,4,1],<1,5,1,330,5222,2>[202,6,2]}";
+        let values = CMDC_CODEC.decode_list(&FieldType::Struct, data).unwrap();
+        assert_eq!(values.len(), 2);
+        let second = values[1].as_ref().unwrap().as_struct().unwrap();
+        assert_eq!(second.containers[0].header.key, 330);
+        assert_eq!(second.containers[0].fields[0].data, b"202");
+        let encoded = CMDC_CODEC.encode_list(&FieldType::Struct, &values).unwrap();
+        assert_eq!(encoded, data);
+    }
+
+    #[test]
+    fn test_decode_invalid_list_element() {
+        let err = CMDC_CODEC
+            .decode_list(&FieldType::Int8, b"{1,200}")
+            .unwrap_err();
+        assert_eq!(err.to_string(), "number too large to fit in target type");
+    }
+
     #[test]
     fn test_encode_decode_string() {
         let data = b"(5:three)";
@@ -170,6 +366,17 @@ mod tests {
         assert_eq!(err, "Invalid string length, 3 is too short");
     }
 
+    #[test]
+    fn test_decode_invalid_string_3() {
+        let data = b"(5:three";
+        let err = CMDC_CODEC.decode_string(data).err().unwrap().to_string();
+        assert_eq!(err, "Invalid string length, 5 is too long");
+
+        let data = b"(-1:three)";
+        let err = CMDC_CODEC.decode_string(data).err().unwrap().to_string();
+        assert_eq!(err, "Invalid string length '-1'");
+    }
+
     #[test]
     fn test_encode_decode_int8() {
         let data = b"-125";
@@ -241,4 +448,158 @@ mod tests {
         let encoded = CMDC_CODEC.encode_uint64(v).unwrap();
         assert_eq!(encoded, data);
     }
+
+    #[test]
+    fn test_encode_decode_bool() {
+        let v = CMDC_CODEC.decode_bool(b"1").unwrap();
+        assert!(v);
+        let encoded = CMDC_CODEC.encode_bool(v).unwrap();
+        assert_eq!(encoded, b"1");
+
+        let v = CMDC_CODEC.decode_bool(b"0").unwrap();
+        assert!(!v);
+        let encoded = CMDC_CODEC.encode_bool(v).unwrap();
+        assert_eq!(encoded, b"0");
+    }
+
+    #[test]
+    fn test_decode_invalid_bool() {
+        let err = CMDC_CODEC.decode_bool(b"true").err().unwrap().to_string();
+        assert_eq!(err, "Invalid bool value 'true', 0 or 1 expected");
+        let err = CMDC_CODEC.decode_bool(b"2").err().unwrap().to_string();
+        assert_eq!(err, "Invalid bool value '2', 0 or 1 expected");
+        let err = CMDC_CODEC.decode_bool(b"01").err().unwrap().to_string();
+        assert_eq!(err, "Invalid bool value '01', 0 or 1 expected");
+    }
+
+    #[test]
+    fn test_encode_decode_decimal() {
+        let data = b"17485824.0";
+        let v = CMDC_CODEC.decode_decimal(data).unwrap();
+        assert_eq!(v, BigDecimal::from(17485824));
+        let encoded = CMDC_CODEC.encode_decimal(&v).unwrap();
+        assert_eq!(encoded, data);
+    }
+
+    #[test]
+    fn test_encode_decode_negative_decimal() {
+        let data = b"-1258291032.242187";
+        let v = CMDC_CODEC.decode_decimal(data).unwrap();
+        assert_eq!(v, BigDecimal::from_str("-1258291032.242187").unwrap());
+        let encoded = CMDC_CODEC.encode_decimal(&v).unwrap();
+        assert_eq!(encoded, data);
+    }
+
+    #[test]
+    fn test_encode_decode_decimal_scale() {
+        let data = b"5.50";
+        let v = CMDC_CODEC.decode_decimal(data).unwrap();
+        let (_, scale) = v.as_bigint_and_exponent();
+        assert_eq!(scale, 2);
+        let encoded = CMDC_CODEC.encode_decimal(&v).unwrap();
+        assert_eq!(encoded, data);
+
+        let data = b"0.0";
+        let v = CMDC_CODEC.decode_decimal(data).unwrap();
+        let encoded = CMDC_CODEC.encode_decimal(&v).unwrap();
+        assert_eq!(encoded, data);
+    }
+
+    #[test]
+    fn test_decode_non_canonical_decimal() {
+        for data in ["1.5e3", "-2.5E-3", "1E10", "+5", "05", ".5", "5.", "-0"] {
+            let err = CMDC_CODEC
+                .decode_decimal(data.as_bytes())
+                .err()
+                .unwrap()
+                .to_string();
+            assert_eq!(err, format!("Invalid decimal value '{}'", data));
+        }
+    }
+
+    #[test]
+    fn test_decode_invalid_decimal() {
+        let err = CMDC_CODEC
+            .decode_decimal(b"1.2.3")
+            .err()
+            .unwrap()
+            .to_string();
+        assert_eq!(err, "invalid digit found in string");
+    }
+
+    #[test]
+    fn test_encode_decode_datetime() {
+        let data = b"2021-09-07T08:00:25.000001Z";
+        let v = CMDC_CODEC.decode_datetime(data).unwrap();
+        assert_eq!(v, DateTime::utc(2021, 9, 7, 8, 0, 25, 1).unwrap());
+        let encoded = CMDC_CODEC.encode_datetime(&v).unwrap();
+        assert_eq!(encoded, data);
+    }
+
+    #[test]
+    fn test_decode_invalid_datetime() {
+        let err = CMDC_CODEC
+            .decode_datetime(b"2021-09-31T08:00:25.000000Z")
+            .err()
+            .unwrap()
+            .to_string();
+        assert_eq!(err, "Invalid datetime value '2021-09-31T08:00:25.000000Z'");
+    }
+
+    #[test]
+    fn test_encode_decode_date() {
+        let data = b"2021-10-31";
+        let v = CMDC_CODEC.decode_date(data).unwrap();
+        assert_eq!(v, Date::new(2021, 10, 31).unwrap());
+        let encoded = CMDC_CODEC.encode_date(&v).unwrap();
+        assert_eq!(encoded, data);
+    }
+
+    #[test]
+    fn test_decode_invalid_date() {
+        let err = CMDC_CODEC
+            .decode_date(b"2021-02-29")
+            .err()
+            .unwrap()
+            .to_string();
+        assert_eq!(err, "Invalid date value '2021-02-29'");
+    }
+
+    #[test]
+    fn test_encode_decode_time() {
+        let data = b"09:13:02.667997Z";
+        let v = CMDC_CODEC.decode_time(data).unwrap();
+        assert_eq!(v, Time::new(9, 13, 2, 667997, Some(0)).unwrap());
+        let encoded = CMDC_CODEC.encode_time(&v).unwrap();
+        assert_eq!(encoded, data);
+    }
+
+    #[test]
+    fn test_decode_invalid_time() {
+        let err = CMDC_CODEC
+            .decode_time(b"25:13:02.667997Z")
+            .err()
+            .unwrap()
+            .to_string();
+        assert_eq!(err, "Invalid time value '25:13:02.667997Z'");
+    }
+
+    #[test]
+    fn test_encode_decode_object_id() {
+        let data = b"0:1:5:277";
+        let v = CMDC_CODEC.decode_object_id(data).unwrap();
+        assert_eq!(v, ObjectId::new(0, 1, 5, 277));
+        let encoded = CMDC_CODEC.encode_object_id(&v).unwrap();
+        assert_eq!(encoded, data);
+    }
+
+    #[test]
+    fn test_decode_invalid_object_id() {
+        let err = CMDC_CODEC
+            .decode_object_id(b"0:1:5")
+            .err()
+            .unwrap()
+            .to_string();
+        assert_eq!(err, "Invalid object id value '0:1:5'");
+    }
 }
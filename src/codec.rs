@@ -1,12 +1,52 @@
 use crate::error::Error;
 use crate::mdd::Containers;
 use crate::mdd::Field;
+use crate::mdd::FieldType;
 use crate::mdd::Value;
+use std::borrow::Cow;
 
 pub trait Codec: std::fmt::Debug {
+    /// Name of the wire format. Codecs with the same name share the raw data
+    /// of decoded fields, by default only codecs of the same type do.
+    fn name(&self) -> &'static str {
+        std::any::type_name::<Self>()
+    }
+
     fn decode<'a>(&self, data: &'a [u8]) -> Result<Containers<'a>, Error>;
     fn encode(&self, containers: &Containers) -> Result<Vec<u8>, Error>;
 
     fn decode_field<'a>(&self, field: &Field<'a>) -> Result<Value<'a>, Error>;
     fn encode_field(&self, field: &Field) -> Result<Vec<u8>, Error>;
 }
+
+/// Whether the raw data of the field is in the wire format of `codec`.
+/// Fields without a codec, such as `Field::raw()`, are taken as is.
+pub(crate) fn is_native(codec: &dyn Codec, field: &Field) -> bool {
+    field.codec.is_none_or(|c| c.name() == codec.name())
+}
+
+/// Value of a field to encode in another wire format than its raw data.
+/// Fields decoded by another codec must be typed, for example with
+/// `SchemaRegistry::apply()`, to be decoded here.
+pub(crate) fn field_value<'a, 'b>(
+    codec: &dyn Codec,
+    field: &'b Field<'a>,
+) -> Result<Cow<'b, Value<'a>>, Error> {
+    let source = match field.codec {
+        Some(source) if field.value.is_none() && !field.data.is_empty() => source,
+        _ => {
+            return field
+                .get_value()?
+                .map(Cow::Borrowed)
+                .ok_or_else(|| Error::EncodeError("Field has no value".into()))
+        }
+    };
+    if field.field_type == FieldType::Unknown {
+        return Err(Error::EncodeError(format!(
+            "Cannot encode untyped {} field as {}, field type is unknown",
+            source.name(),
+            codec.name()
+        )));
+    }
+    Ok(Cow::Owned(source.decode_field(field)?))
+}
@@ -0,0 +1,436 @@
+//! Rust code generation from container definitions.
+//!
+//! Generates a struct implementing `MddContainer` for every container in a
+//! registry, with the fields of one schema version. Meant to be run from a
+//! build script:
+//!
+//! ```ignore
+//! // build.rs
+//! let registry = SchemaRegistry::from_xml_files(&["mtx_definitions.xml"])?;
+//! let out = Path::new(&std::env::var("OUT_DIR")?).join("mdd.rs");
+//! Generator::new(&registry, 5222, 2).write_to(out)?;
+//!
+//! // lib.rs
+//! mod mdd {
+//!     include!(concat!(env!("OUT_DIR"), "/mdd.rs"));
+//! }
+//! ```
+//!
+//! Fields are public, named in snake case, and hold `Option<T>` for single
+//! values and `Vec<T>` for lists. Fields are read by schema field name when
+//! the container has a schema attached, by position otherwise. A container
+//! that others derive from also gets an `Any<Name>` enum holding any of
+//! them, and struct fields declared with it hold `Structs<Any<Name>>` since
+//! the field may hold several containers.
+
+use crate::error::Error;
+use crate::mdd::FieldType;
+use crate::schema::ContainerDef;
+use crate::schema::FieldDef;
+use crate::schema::SchemaRegistry;
+use std::path::Path;
+
+/// Write a line of generated code.
+macro_rules! emit {
+    ($out:expr, $($arg:tt)*) => {{
+        $out.push_str(&format!($($arg)*));
+        $out.push('\n');
+    }};
+}
+
+const IMPORTS: &str = "#[allow(unused_imports)]
+use rust_mdd::bigdecimal::BigDecimal;
+#[allow(unused_imports)]
+use rust_mdd::container::{
+    check_key, from_struct_value, read_field, to_struct_value, ContainerBuilder, MddContainer,
+    Structs,
+};
+#[allow(unused_imports)]
+use rust_mdd::datetime::{Date, DateTime, Time};
+use rust_mdd::error::Error;
+use rust_mdd::mdd::{Container, FieldType, FromValue, ToValue, Value};
+#[allow(unused_imports)]
+use rust_mdd::object_id::ObjectId;
+";
+
+/// Generates Rust code for the containers of a registry at one
+/// `schema_version` and `ext_version`.
+#[derive(Debug)]
+pub struct Generator<'r> {
+    registry: &'r SchemaRegistry,
+    schema_version: u16,
+    ext_version: u16,
+}
+
+impl<'r> Generator<'r> {
+    pub fn new(registry: &'r SchemaRegistry, schema_version: u16, ext_version: u16) -> Self {
+        Generator {
+            registry,
+            schema_version,
+            ext_version,
+        }
+    }
+
+    /// Generate the code for all containers, ordered by key.
+    pub fn generate(&self) -> Result<String, Error> {
+        let mut out = String::new();
+        emit!(
+            out,
+            "// @generated by rust_mdd::codegen for schema version {}, extension version {}.",
+            self.schema_version,
+            self.ext_version
+        );
+        out.push_str("// Do not edit.\n\n");
+        out.push_str(IMPORTS);
+
+        for container in self.registry.containers() {
+            self.generate_struct(&mut out, container)?;
+            let derived = self.derived(container);
+            if !derived.is_empty() {
+                self.generate_enum(&mut out, container, &derived)?;
+            }
+        }
+        Ok(out)
+    }
+
+    /// Write the generated code to a file, leaving it untouched when the
+    /// code is unchanged so build scripts don't trigger rebuilds.
+    pub fn write_to<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        let code = self.generate()?;
+        let path = path.as_ref();
+        if std::fs::read_to_string(path).ok().as_deref() != Some(code.as_str()) {
+            std::fs::write(path, code)?;
+        }
+        Ok(())
+    }
+
+    /// Containers derived from the container, ordered by key.
+    fn derived(&self, container: &ContainerDef) -> Vec<&'r ContainerDef> {
+        self.registry
+            .containers()
+            .into_iter()
+            .filter(|c| c.key != container.key && self.registry.is_a(c.key, &container.name))
+            .collect()
+    }
+
+    fn generate_struct(&self, out: &mut String, container: &ContainerDef) -> Result<(), Error> {
+        let schema = self
+            .registry
+            .lookup(container.key, self.schema_version, self.ext_version)?;
+        let name = &container.name;
+        let fields = schema
+            .fields
+            .iter()
+            .map(|f| Ok((rust_field_name(&f.name), self.rust_type(f)?)))
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        emit!(out, "\n/// `{}`, container key {}.", name, container.key);
+        emit!(out, "#[derive(Debug, Clone, Default, PartialEq)]");
+        if fields.is_empty() {
+            emit!(out, "pub struct {} {{}}", name);
+        } else {
+            emit!(out, "pub struct {} {{", name);
+            for (def, (field, ty)) in schema.fields.iter().zip(&fields) {
+                emit!(out, "    /// `{}`", def.name);
+                emit!(out, "    pub {}: {},", field, ty);
+            }
+            emit!(out, "}}");
+        }
+
+        emit!(
+            out,
+            "\nimpl<'a> MddContainer<'a> for {} {{\n    const KEY: i32 = {};\n    const SCHEMA_VERSION: u16 = {};\n    const EXT_VERSION: u16 = {};\n",
+            name, container.key, self.schema_version, self.ext_version
+        );
+        emit!(
+            out,
+            "    fn from_container(container: &Container<'a>) -> Result<Self, Error> {{"
+        );
+        emit!(
+            out,
+            "        check_key(container, \"{}\", Self::KEY)?;",
+            name
+        );
+        if fields.is_empty() {
+            emit!(out, "        Ok({} {{}})", name);
+        } else {
+            emit!(out, "        Ok({} {{", name);
+            for (idx, (def, (field, _))) in schema.fields.iter().zip(&fields).enumerate() {
+                emit!(
+                    out,
+                    "            {}: read_field(container, \"{}\", \"{}\", {}, Some(\"{}\"))?,",
+                    field,
+                    name,
+                    field.trim_start_matches("r#"),
+                    idx,
+                    def.name
+                );
+            }
+            emit!(out, "        }})");
+        }
+        emit!(out, "    }}\n");
+        emit!(
+            out,
+            "    fn to_container(&self) -> Result<Container<'static>, Error> {{"
+        );
+        emit!(
+            out,
+            "        ContainerBuilder::new(Self::KEY, Self::SCHEMA_VERSION, Self::EXT_VERSION)"
+        );
+        if let Ok(total_field) = u8::try_from(schema.fields.len()) {
+            emit!(out, "            .total_field({})", total_field);
+        }
+        for (idx, (field, _)) in fields.iter().enumerate() {
+            emit!(out, "            .field({}, &self.{})?", idx, field);
+        }
+        emit!(out, "            .build()\n    }}\n}}");
+
+        write_value_impls(out, name);
+        Ok(())
+    }
+
+    fn generate_enum(
+        &self,
+        out: &mut String,
+        container: &ContainerDef,
+        derived: &[&ContainerDef],
+    ) -> Result<(), Error> {
+        let name = format!("Any{}", container.name);
+        let variants: Vec<&ContainerDef> = std::iter::once(container)
+            .chain(derived.iter().copied())
+            .collect();
+
+        emit!(
+            out,
+            "\n/// `{}` or a container derived from it.",
+            container.name
+        );
+        emit!(out, "#[derive(Debug, Clone, PartialEq)]");
+        emit!(
+            out,
+            "#[allow(clippy::large_enum_variant, clippy::enum_variant_names)]"
+        );
+        emit!(out, "pub enum {} {{", name);
+        for v in &variants {
+            emit!(out, "    {}({}),", v.name, v.name);
+        }
+        emit!(out, "}}");
+
+        emit!(
+            out,
+            "\nimpl<'a> MddContainer<'a> for {} {{\n    const KEY: i32 = {}::KEY;\n    const SCHEMA_VERSION: u16 = {};\n    const EXT_VERSION: u16 = {};\n",
+            name, container.name, self.schema_version, self.ext_version
+        );
+        emit!(
+            out,
+            "    fn from_container(container: &Container<'a>) -> Result<Self, Error> {{"
+        );
+        emit!(out, "        match container.header.key {{");
+        for v in &variants {
+            emit!(
+                out,
+                "            {} => {}::from_container(container).map({}::{}),",
+                v.key,
+                v.name,
+                name,
+                v.name
+            );
+        }
+        emit!(
+            out,
+            "            key => Err(Error::TypeMismatch(format!(\n                \"Container key {{}} is not a '{}'\",\n                key\n            ))),",
+            container.name
+        );
+        emit!(out, "        }}\n    }}\n");
+        emit!(
+            out,
+            "    fn to_container(&self) -> Result<Container<'static>, Error> {{"
+        );
+        emit!(out, "        match self {{");
+        for v in &variants {
+            emit!(
+                out,
+                "            {}::{}(v) => v.to_container(),",
+                name,
+                v.name
+            );
+        }
+        emit!(out, "        }}\n    }}\n}}");
+
+        write_value_impls(out, &name);
+        Ok(())
+    }
+
+    fn rust_type(&self, field: &FieldDef) -> Result<String, Error> {
+        let ty = match (&field.field_type, &field.struct_name) {
+            (FieldType::Struct, Some(struct_name)) => {
+                let def = self.registry.get_by_name(struct_name).ok_or_else(|| {
+                    Error::SchemaError(format!(
+                        "Unknown struct type '{}' for field '{}'",
+                        struct_name, field.name
+                    ))
+                })?;
+                // Derived containers can't be told apart from the declared
+                // one before decoding, and several may share the field
+                if !self.derived(def).is_empty() {
+                    let ty = format!("Structs<Any{}>", def.name);
+                    return Ok(match field.is_list {
+                        true => format!("Vec<{}>", ty),
+                        false => ty,
+                    });
+                }
+                def.name.clone()
+            }
+            (field_type, _) => rust_scalar_type(field_type)
+                .ok_or_else(|| {
+                    Error::SchemaError(format!(
+                        "Cannot generate a Rust type for field '{}' of type {:?}",
+                        field.name, field_type
+                    ))
+                })?
+                .to_string(),
+        };
+        Ok(match field.is_list {
+            true => format!("Vec<{}>", ty),
+            false => format!("Option<{}>", ty),
+        })
+    }
+}
+
+fn write_value_impls(out: &mut String, name: &str) {
+    emit!(
+        out,
+        "\nimpl<'a> FromValue<'a> for {} {{\n    fn from_value(value: &Value<'a>) -> Result<Self, Error> {{\n        from_struct_value(value)\n    }}\n}}",
+        name
+    );
+    emit!(
+        out,
+        "\nimpl ToValue for {} {{\n    fn field_type() -> FieldType {{\n        FieldType::Struct\n    }}\n\n    fn to_value(&self) -> Result<Option<Value<'static>>, Error> {{\n        to_struct_value(self)\n    }}\n}}",
+        name
+    );
+}
+
+fn rust_scalar_type(field_type: &FieldType) -> Option<&'static str> {
+    match field_type {
+        FieldType::String => Some("String"),
+        FieldType::Int8 => Some("i8"),
+        FieldType::Int16 => Some("i16"),
+        FieldType::Int32 => Some("i32"),
+        FieldType::Int64 => Some("i64"),
+        FieldType::UInt8 => Some("u8"),
+        FieldType::UInt16 => Some("u16"),
+        FieldType::UInt32 => Some("u32"),
+        FieldType::UInt64 => Some("u64"),
+        FieldType::Bool => Some("bool"),
+        FieldType::Decimal => Some("BigDecimal"),
+        FieldType::DateTime => Some("DateTime"),
+        FieldType::Date => Some("Date"),
+        FieldType::Time => Some("Time"),
+        FieldType::ObjectId => Some("ObjectId"),
+        FieldType::Struct | FieldType::Unknown => None,
+    }
+}
+
+/// Snake case field name, `IsPrimary` becomes `is_primary` and
+/// `SMSLimit` becomes `sms_limit`. Keywords are written as raw identifiers.
+fn rust_field_name(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut field = String::with_capacity(name.len() + 4);
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            let prev = chars[i - 1];
+            let next_lower = chars.get(i + 1).is_some_and(|n| n.is_lowercase());
+            if prev.is_lowercase() || prev.is_ascii_digit() || (prev.is_uppercase() && next_lower) {
+                field.push('_');
+            }
+        }
+        field.extend(c.to_lowercase());
+    }
+    match field.as_str() {
+        "as" | "break" | "const" | "continue" | "crate" | "else" | "enum" | "extern" | "false"
+        | "fn" | "for" | "if" | "impl" | "in" | "let" | "loop" | "match" | "mod" | "move"
+        | "mut" | "pub" | "ref" | "return" | "static" | "struct" | "trait" | "true" | "type"
+        | "unsafe" | "use" | "where" | "while" | "async" | "await" | "dyn" | "abstract"
+        | "become" | "box" | "do" | "final" | "macro" | "override" | "priv" | "typeof"
+        | "unsized" | "virtual" | "yield" | "try" => format!("r#{}", field),
+        "self" | "super" | "Self" => format!("{}_", field),
+        _ => field,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rust_field_name() {
+        let cases = [
+            ("TemplateId", "template_id"),
+            ("IsPrimary", "is_primary"),
+            ("SMSLimit", "sms_limit"),
+            ("Ipv4Address", "ipv4_address"),
+            ("ExternalID", "external_id"),
+            ("Type", "r#type"),
+            ("Self", "self_"),
+        ];
+        for (name, expected) in cases {
+            assert_eq!(rust_field_name(name), expected);
+        }
+    }
+
+    #[test]
+    fn test_generate() {
+        let mut registry = SchemaRegistry::new();
+        registry
+            .add(
+                ContainerDef::new("MtxBalanceInfo", 330)
+                    .field(FieldDef::new("TemplateId", FieldType::UInt32))
+                    .field(FieldDef::new("Amount", FieldType::Decimal).introduced_in(5300)),
+            )
+            .unwrap();
+        registry
+            .add(
+                ContainerDef::new("MtxResponseWallet", 263)
+                    .field(FieldDef::new_struct("BalanceArray", "MtxBalanceInfo").list())
+                    .field(FieldDef::new("Type", FieldType::String)),
+            )
+            .unwrap();
+
+        let code = Generator::new(&registry, 5222, 2).generate().unwrap();
+        // Fields introduced in later versions are left out
+        assert!(code.contains("pub struct MtxBalanceInfo {\n    /// `TemplateId`\n    pub template_id: Option<u32>,\n}"));
+        assert!(code.contains("    pub balance_array: Vec<MtxBalanceInfo>,\n"));
+        assert!(code.contains(
+            "            r#type: read_field(container, \"MtxResponseWallet\", \"type\", 1, Some(\"Type\"))?,\n"
+        ));
+        assert!(code.contains("            .field(1, &self.r#type)?\n"));
+        assert!(!code.contains("enum"));
+    }
+
+    #[test]
+    fn test_generate_unknown_type() {
+        let mut registry = SchemaRegistry::new();
+        registry
+            .add(ContainerDef::new("A", 1).field(FieldDef::new("B", FieldType::Unknown)))
+            .unwrap();
+        let err = Generator::new(&registry, 0, 0).generate().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Cannot generate a Rust type for field 'B' of type Unknown"
+        );
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn test_generate_snapshot() {
+        // tests/codegen.rs compiles this file and round-trips the bench
+        // payloads with it
+        let registry = SchemaRegistry::from_toml_files(&["tests/data/bench_schema.toml"]).unwrap();
+        let code = Generator::new(&registry, 5222, 2).generate().unwrap();
+        let expected = std::fs::read_to_string("tests/data/bench_generated.rs").unwrap();
+        assert!(
+            code == expected,
+            "Generated code differs from tests/data/bench_generated.rs, regenerate it if the change is intended"
+        );
+    }
+}
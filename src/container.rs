@@ -0,0 +1,452 @@
+//! Mapping between Rust structs and containers.
+//!
+//! ```ignore
+//! use rust_mdd::container::MddContainer;
+//!
+//! #[derive(MddContainer)]
+//! #[mdd(key = 330, schema_version = 5222, ext_version = 2)]
+//! struct BalanceInfo {
+//!     template_id: u32,
+//!     #[mdd(index = 3, name = "AvailableAmount")]
+//!     available: Option<BigDecimal>,
+//!     thresholds: Vec<Threshold>,
+//! }
+//! ```
+//!
+//! Fields map to the container field at their position in the struct,
+//! unless `index` is given. With `name`, a container that has a schema
+//! attached is read by field name instead, so the struct keeps working
+//! across schema versions. `Option` fields read null as `None`, `Vec`
+//! fields map to list fields and nested derived structs to struct fields.
+//! The struct attribute `total_field` sets the field count written in the
+//! header, which otherwise ends at the last field of the struct.
+
+use crate::cmdc::CMDC_CODEC;
+use crate::codec::Codec;
+use crate::error::Error;
+use crate::mdd::Container;
+use crate::mdd::Containers;
+use crate::mdd::Field;
+use crate::mdd::FieldType;
+use crate::mdd::FromValue;
+use crate::mdd::Header;
+use crate::mdd::ToValue;
+use crate::mdd::Value;
+
+#[cfg(feature = "derive")]
+pub use rust_mdd_derive::MddContainer;
+
+/// A Rust type mapped to a container, usually implemented with
+/// `#[derive(MddContainer)]`.
+pub trait MddContainer<'a>: Sized {
+    const KEY: i32;
+    const SCHEMA_VERSION: u16;
+    const EXT_VERSION: u16;
+
+    fn from_container(container: &Container<'a>) -> Result<Self, Error>;
+    fn to_container(&self) -> Result<Container<'static>, Error>;
+
+    /// Decode cMDC data holding a single container.
+    fn from_cmdc(data: &'a [u8]) -> Result<Self, Error> {
+        let containers = CMDC_CODEC.decode(data)?;
+        match containers.containers.as_slice() {
+            [container] => Self::from_container(container),
+            containers => Err(Error::DecodeError(format!(
+                "Expected a single container but found {}",
+                containers.len()
+            ))),
+        }
+    }
+
+    fn to_cmdc(&self) -> Result<Vec<u8>, Error> {
+        let containers = Containers {
+            containers: vec![self.to_container()?],
+        };
+        CMDC_CODEC.encode(&containers)
+    }
+}
+
+/// Builds a container from values, fields left out are null.
+#[derive(Debug)]
+pub struct ContainerBuilder {
+    header: Header,
+    total_field: Option<u8>,
+    fields: Vec<Field<'static>>,
+}
+
+impl ContainerBuilder {
+    pub fn new(key: i32, schema_version: u16, ext_version: u16) -> Self {
+        ContainerBuilder {
+            header: Header {
+                version: 1,
+                total_field: 0,
+                depth: 0,
+                key,
+                schema_version,
+                ext_version,
+            },
+            total_field: None,
+            fields: vec![],
+        }
+    }
+
+    /// Set the `total_field` of the header, such as the field count of the
+    /// container definition. It defaults to the number of fields up to the
+    /// last one set, fields after it are left out of the body.
+    pub fn total_field(mut self, total_field: u8) -> Self {
+        self.total_field = Some(total_field);
+        self
+    }
+
+    /// Set the field at `idx` to the value.
+    pub fn field<T: ToValue>(mut self, idx: usize, value: &T) -> Result<Self, Error> {
+        if self.fields.len() <= idx {
+            self.fields
+                .resize_with(idx + 1, || null_field(FieldType::Unknown));
+        }
+        let field = &mut self.fields[idx];
+        *field = null_field(T::field_type());
+        field.is_multi = T::is_list();
+        field.is_container = T::field_type() == FieldType::Struct;
+        field.value = value.to_value()?;
+        field.is_null = field.value.is_none();
+        Ok(self)
+    }
+
+    /// Finish the container, numbering the depth of nested containers from
+    /// this one.
+    pub fn build(mut self) -> Result<Container<'static>, Error> {
+        let count = self.fields.len();
+        self.header.total_field = match self.total_field {
+            Some(total_field) if count > total_field as usize => {
+                return Err(Error::EncodeError(format!(
+                    "Field {} is beyond total_field {} of container with key {}",
+                    count - 1,
+                    total_field,
+                    self.header.key
+                )))
+            }
+            Some(total_field) => total_field,
+            None => u8::try_from(count).map_err(|_| {
+                Error::EncodeError(format!(
+                    "Too many fields {} in container with key {}",
+                    count, self.header.key
+                ))
+            })?,
+        };
+        let mut container = Container::new(self.header, self.fields);
+        set_depth(&mut container, 0);
+        Ok(container)
+    }
+}
+
+pub(crate) fn null_field(field_type: FieldType) -> Field<'static> {
+    Field {
+        data: b"",
+        field_type,
+        value: None,
+        codec: Some(&CMDC_CODEC),
+        is_multi: false,
+        is_container: false,
+        is_null: true,
+    }
+}
+
+pub(crate) fn set_depth(container: &mut Container, depth: i8) {
+    container.header.depth = depth;
+    for value in container.fields.iter_mut().filter_map(|f| f.value.as_mut()) {
+        set_value_depth(value, depth + 1);
+    }
+}
+
+fn set_value_depth(value: &mut Value, depth: i8) {
+    match value {
+        Value::Struct(containers) => {
+            for container in containers.containers.iter_mut() {
+                set_depth(container, depth);
+            }
+        }
+        Value::List(values) => {
+            for value in values.iter_mut().flatten() {
+                set_value_depth(value, depth);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Read a struct field from a container, by name when the container has a
+/// schema attached and the field is named, by position otherwise.
+#[doc(hidden)]
+pub fn read_field<'a, T: FromValue<'a> + ToValue>(
+    container: &Container<'a>,
+    target: &str,
+    field: &str,
+    idx: usize,
+    name: Option<&str>,
+) -> Result<T, Error> {
+    let idx = match name {
+        Some(name) if container.schema.is_some() => container.field_index(name)?,
+        _ => idx,
+    };
+    container.get_at(idx).map_err(|e| {
+        Error::TypeMismatch(format!("Invalid field '{}' of '{}', {}", field, target, e))
+    })
+}
+
+/// Check a container has the key of the type it is read into.
+#[doc(hidden)]
+pub fn check_key(container: &Container, target: &str, key: i32) -> Result<(), Error> {
+    if container.header.key != key {
+        return Err(Error::TypeMismatch(format!(
+            "Container key {} does not match '{}' key {}",
+            container.header.key, target, key
+        )));
+    }
+    Ok(())
+}
+
+/// Read a struct value holding a single container into `T`.
+#[doc(hidden)]
+pub fn from_struct_value<'a, T: MddContainer<'a>>(value: &Value<'a>) -> Result<T, Error> {
+    match value {
+        Value::Struct(containers) if containers.containers.len() == 1 => {
+            T::from_container(&containers.containers[0])
+        }
+        _ => Err(Error::TypeMismatch(format!(
+            "Value type mismatch, expected Struct holding a single container but found {}",
+            value.type_name()
+        ))),
+    }
+}
+
+/// Write `T` as a struct value.
+#[doc(hidden)]
+pub fn to_struct_value<'a, T: MddContainer<'a>>(
+    value: &T,
+) -> Result<Option<Value<'static>>, Error> {
+    Ok(Some(Value::Struct(Containers {
+        containers: vec![value.to_container()?],
+    })))
+}
+
+/// The containers held by a single struct field or list element. A value
+/// may hold several consecutive containers, such as containers derived from
+/// the declared one, read with an `MddContainer` dispatching on the key.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Structs<T>(pub Vec<T>);
+
+impl<T> Default for Structs<T> {
+    fn default() -> Self {
+        Structs(vec![])
+    }
+}
+
+/// Null fields read as no containers.
+impl<'a, T: MddContainer<'a>> FromValue<'a> for Structs<T> {
+    fn from_value(value: &Value<'a>) -> Result<Self, Error> {
+        match value {
+            Value::Struct(containers) => containers
+                .containers
+                .iter()
+                .map(T::from_container)
+                .collect::<Result<Vec<_>, Error>>()
+                .map(Structs),
+            _ => Err(Error::TypeMismatch(format!(
+                "Value type mismatch, expected Struct but found {}",
+                value.type_name()
+            ))),
+        }
+    }
+
+    fn from_null() -> Option<Self> {
+        Some(Structs(vec![]))
+    }
+}
+
+/// No containers are written as null.
+impl<T: MddContainer<'static>> ToValue for Structs<T> {
+    fn field_type() -> FieldType {
+        FieldType::Struct
+    }
+
+    fn to_value(&self) -> Result<Option<Value<'static>>, Error> {
+        if self.0.is_empty() {
+            return Ok(None);
+        }
+        let containers = self
+            .0
+            .iter()
+            .map(|v| v.to_container())
+            .collect::<Result<Vec<_>, Error>>()?;
+        Ok(Some(Value::Struct(Containers { containers })))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmdc::STRICT_CMDC_CODEC;
+    use crate::datetime::DateTime;
+    use crate::object_id::ObjectId;
+    use crate::schema::ContainerDef;
+    use crate::schema::FieldDef;
+    use crate::schema::SchemaRegistry;
+    use bigdecimal::BigDecimal;
+    use rust_mdd_derive::MddContainer;
+
+    #[derive(Debug, PartialEq, MddContainer)]
+    #[mdd(key = 330, schema_version = 5222, ext_version = 2)]
+    struct BalanceInfo {
+        template_id: u32,
+        resource_id: u32,
+        #[mdd(index = 3, name = "Amount")]
+        amount: Option<BigDecimal>,
+    }
+
+    #[derive(Debug, PartialEq, MddContainer)]
+    #[mdd(key = 263, schema_version = 5222, ext_version = 2)]
+    struct ResponseWallet {
+        result: u32,
+        balances: Vec<BalanceInfo>,
+        external_id: Option<String>,
+        tags: Vec<Option<String>>,
+        owner: ObjectId,
+        primary: Option<BalanceInfo>,
+        updated: Option<DateTime>,
+    }
+
+    #[derive(Debug, PartialEq, MddContainer)]
+    #[mdd(key = -6, total_field = 8)]
+    struct SessionContext {
+        #[mdd(index = 2)]
+        priority: u8,
+    }
+
+    fn wallet() -> ResponseWallet {
+        ResponseWallet {
+            result: 2,
+            balances: vec![
+                BalanceInfo {
+                    template_id: 200,
+                    resource_id: 4,
+                    amount: Some("17485760.5".parse().unwrap()),
+                },
+                BalanceInfo {
+                    template_id: 202,
+                    resource_id: 6,
+                    amount: None,
+                },
+            ],
+            external_id: Some("555555".to_string()),
+            tags: vec![Some("a".to_string()), None],
+            owner: ObjectId::new(0, 1, 5, 277),
+            primary: None,
+            updated: Some("2021-09-09T16:37:19.000000Z".parse().unwrap()),
+        }
+    }
+
+    #[test]
+    fn test_to_cmdc() {
+        let data = wallet().to_cmdc().unwrap();
+        assert_eq!(
+            std::str::from_utf8(&data).unwrap(),
+            "<1,7,0,263,5222,2>[2,{<1,4,1,330,5222,2>[200,4,,17485760.5],<1,4,1,330,5222,2>[202,6,,]},(6:555555),{(1:a),},0:1:5:277,,2021-09-09T16:37:19.000000Z]"
+        );
+        // Nested containers carry their depth
+        STRICT_CMDC_CODEC.decode_containers(&data).unwrap();
+    }
+
+    #[test]
+    fn test_from_cmdc() {
+        let data = b"<1,7,0,263,5222,2>[2,{<1,4,1,330,5222,2>[200,4,,17485760.5],<1,4,1,330,5222,2>[202,6]},(6:555555),{(1:a),},0:1:5:277,,2021-09-09T16:37:19.000000Z]";
+        let wallet_data = ResponseWallet::from_cmdc(data).unwrap();
+        assert_eq!(wallet_data, wallet());
+
+        let data = wallet().to_cmdc().unwrap();
+        assert_eq!(ResponseWallet::from_cmdc(&data).unwrap(), wallet());
+
+        let session = SessionContext::from_cmdc(b"<1,8,0,-6,5222,2>[,,2,(5:AMF-1)]").unwrap();
+        assert_eq!(session, SessionContext { priority: 2 });
+        let container = session.to_container().unwrap();
+        assert_eq!(container.header.key, -6);
+        assert_eq!(container.header.total_field, 8);
+        assert_eq!(container.fields.len(), 3);
+    }
+
+    #[test]
+    fn test_builder_total_field() {
+        let container = ContainerBuilder::new(330, 5222, 2)
+            .total_field(4)
+            .field(0, &7u32)
+            .unwrap()
+            .field(1, &9u32)
+            .unwrap()
+            .build()
+            .unwrap();
+        let containers = Containers {
+            containers: vec![container],
+        };
+        let data = CMDC_CODEC.encode(&containers).unwrap();
+        assert_eq!(data, b"<1,4,0,330,5222,2>[7,9]");
+
+        let err = ContainerBuilder::new(330, 5222, 2)
+            .total_field(1)
+            .field(1, &9u32)
+            .unwrap()
+            .build()
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Field 1 is beyond total_field 1 of container with key 330"
+        );
+    }
+
+    #[test]
+    fn test_from_container_by_name() {
+        // A newer schema version adds a field before Amount
+        let mut registry = SchemaRegistry::new();
+        registry
+            .add(
+                ContainerDef::new("MtxBalanceInfo", 330)
+                    .field(FieldDef::new("TemplateId", FieldType::UInt32))
+                    .field(FieldDef::new("ResourceId", FieldType::UInt32))
+                    .field(FieldDef::new("ClassId", FieldType::UInt32))
+                    .field(FieldDef::new("Reserved", FieldType::String).introduced_in(5300))
+                    .field(FieldDef::new("Amount", FieldType::Decimal)),
+            )
+            .unwrap();
+
+        let data = b"<1,5,0,330,5300,2>[200,4,1,(4:test),1.5]";
+        let containers = CMDC_CODEC.decode_with_schema(data, &registry).unwrap();
+        let balance = BalanceInfo::from_container(&containers.containers[0]).unwrap();
+        assert_eq!(balance.amount, Some("1.5".parse().unwrap()));
+
+        // Without a schema fields are read by position
+        let containers = CMDC_CODEC.decode_containers(data).unwrap();
+        let err = BalanceInfo::from_container(&containers.containers[0]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid field 'amount' of 'BalanceInfo', invalid digit found in string"
+        );
+    }
+
+    #[test]
+    fn test_from_container_errors() {
+        let err = BalanceInfo::from_cmdc(b"<1,3,0,263,5222,2>[1,2,3]").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Container key 263 does not match 'BalanceInfo' key 330"
+        );
+
+        let err = BalanceInfo::from_cmdc(b"<1,3,0,330,5222,2>[1,,3]").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid field 'resource_id' of 'BalanceInfo', value is null"
+        );
+
+        let err =
+            BalanceInfo::from_cmdc(b"<1,3,0,330,5222,2>[1]<1,3,0,330,5222,2>[1]").unwrap_err();
+        assert_eq!(err.to_string(), "Expected a single container but found 2");
+    }
+}
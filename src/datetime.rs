@@ -0,0 +1,725 @@
+use crate::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// A cMDC datetime value such as `2021-09-07T08:00:25.000000Z`.
+///
+/// The fraction always has 6 digits and a zero UTC offset is only accepted
+/// as `Z`, the forms values are written in, so a decoded value encodes back
+/// to the same bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DateTime {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+    pub microsecond: u32,
+    /// Offset from UTC in minutes, `Some(0)` is written as `Z` and `None`
+    /// means no offset designator.
+    pub offset: Option<i16>,
+}
+
+impl DateTime {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        year: u16,
+        month: u8,
+        day: u8,
+        hour: u8,
+        minute: u8,
+        second: u8,
+        microsecond: u32,
+        offset: Option<i16>,
+    ) -> Result<Self, Error> {
+        let v = DateTime {
+            year,
+            month,
+            day,
+            hour,
+            minute,
+            second,
+            microsecond,
+            offset,
+        };
+        v.validate()?;
+        Ok(v)
+    }
+
+    /// Create a UTC datetime, written with the `Z` suffix.
+    pub fn utc(
+        year: u16,
+        month: u8,
+        day: u8,
+        hour: u8,
+        minute: u8,
+        second: u8,
+        microsecond: u32,
+    ) -> Result<Self, Error> {
+        Self::new(year, month, day, hour, minute, second, microsecond, Some(0))
+    }
+
+    pub fn from_parts(date: Date, time: Time) -> Self {
+        DateTime {
+            year: date.year,
+            month: date.month,
+            day: date.day,
+            hour: time.hour,
+            minute: time.minute,
+            second: time.second,
+            microsecond: time.microsecond,
+            offset: time.offset,
+        }
+    }
+
+    pub fn date(&self) -> Date {
+        Date {
+            year: self.year,
+            month: self.month,
+            day: self.day,
+        }
+    }
+
+    pub fn time(&self) -> Time {
+        Time {
+            hour: self.hour,
+            minute: self.minute,
+            second: self.second,
+            microsecond: self.microsecond,
+            offset: self.offset,
+        }
+    }
+
+    fn validate(&self) -> Result<(), Error> {
+        validate_date(self.year, self.month, self.day)?;
+        validate_time(self.hour, self.minute, self.second, self.microsecond)?;
+        validate_offset(self.offset)
+    }
+}
+
+impl FromStr for DateTime {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || Error::DecodeError(format!("Invalid datetime value '{}'", s));
+
+        let mut parser = Parser::new(s.as_bytes());
+        let (year, month, day) = parser.date().ok_or_else(err)?;
+        parser.expect(b'T').ok_or_else(err)?;
+        let (hour, minute, second, microsecond) = parser.time().ok_or_else(err)?;
+        let offset = parser.offset().ok_or_else(err)?;
+        if !parser.is_end() {
+            return Err(err());
+        }
+
+        Self::new(year, month, day, hour, minute, second, microsecond, offset).map_err(|_| err())
+    }
+}
+
+impl fmt::Display for DateTime {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:06}",
+            self.year, self.month, self.day, self.hour, self.minute, self.second, self.microsecond
+        )?;
+        write_offset(f, self.offset)
+    }
+}
+
+/// A cMDC date value such as `2021-10-31`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Date {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+}
+
+impl Date {
+    pub fn new(year: u16, month: u8, day: u8) -> Result<Self, Error> {
+        validate_date(year, month, day)?;
+        Ok(Date { year, month, day })
+    }
+}
+
+impl FromStr for Date {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || Error::DecodeError(format!("Invalid date value '{}'", s));
+
+        let mut parser = Parser::new(s.as_bytes());
+        let (year, month, day) = parser.date().ok_or_else(err)?;
+        if !parser.is_end() {
+            return Err(err());
+        }
+
+        Self::new(year, month, day).map_err(|_| err())
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+/// A cMDC time of day such as `09:13:02.667997Z`.
+///
+/// Like [`DateTime`], only the forms values are written in are accepted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Time {
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+    pub microsecond: u32,
+    /// Offset from UTC in minutes, `Some(0)` is written as `Z`.
+    pub offset: Option<i16>,
+}
+
+impl Time {
+    pub fn new(
+        hour: u8,
+        minute: u8,
+        second: u8,
+        microsecond: u32,
+        offset: Option<i16>,
+    ) -> Result<Self, Error> {
+        validate_time(hour, minute, second, microsecond)?;
+        validate_offset(offset)?;
+        Ok(Time {
+            hour,
+            minute,
+            second,
+            microsecond,
+            offset,
+        })
+    }
+}
+
+impl FromStr for Time {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || Error::DecodeError(format!("Invalid time value '{}'", s));
+
+        let mut parser = Parser::new(s.as_bytes());
+        let (hour, minute, second, microsecond) = parser.time().ok_or_else(err)?;
+        let offset = parser.offset().ok_or_else(err)?;
+        if !parser.is_end() {
+            return Err(err());
+        }
+
+        Self::new(hour, minute, second, microsecond, offset).map_err(|_| err())
+    }
+}
+
+impl fmt::Display for Time {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:02}:{:02}:{:02}.{:06}",
+            self.hour, self.minute, self.second, self.microsecond
+        )?;
+        write_offset(f, self.offset)
+    }
+}
+
+pub(crate) fn validate_date(year: u16, month: u8, day: u8) -> Result<(), Error> {
+    if year > 9999 || !(1..=12).contains(&month) || day == 0 || day > days_in_month(year, month) {
+        return Err(Error::DecodeError(format!(
+            "Invalid date {:04}-{:02}-{:02}",
+            year, month, day
+        )));
+    }
+    Ok(())
+}
+
+pub(crate) fn validate_time(
+    hour: u8,
+    minute: u8,
+    second: u8,
+    microsecond: u32,
+) -> Result<(), Error> {
+    if hour > 23 || minute > 59 || second > 59 || microsecond > 999_999 {
+        return Err(Error::DecodeError(format!(
+            "Invalid time {:02}:{:02}:{:02}.{:06}",
+            hour, minute, second, microsecond
+        )));
+    }
+    Ok(())
+}
+
+pub(crate) fn validate_offset(offset: Option<i16>) -> Result<(), Error> {
+    match offset {
+        Some(v) if v.abs() >= 24 * 60 => Err(Error::DecodeError(format!(
+            "Invalid UTC offset, {} minutes",
+            v
+        ))),
+        _ => Ok(()),
+    }
+}
+
+pub(crate) fn write_offset(f: &mut fmt::Formatter, offset: Option<i16>) -> fmt::Result {
+    match offset {
+        None => Ok(()),
+        Some(0) => write!(f, "Z"),
+        Some(v) => {
+            let sign = if v < 0 { '-' } else { '+' };
+            write!(f, "{}{:02}:{:02}", sign, v.abs() / 60, v.abs() % 60)
+        }
+    }
+}
+
+fn days_in_month(year: u16, month: u8) -> u8 {
+    match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if (year.is_multiple_of(4) && !year.is_multiple_of(100)) || year.is_multiple_of(400) => {
+            29
+        }
+        _ => 28,
+    }
+}
+
+/// Cursor over the textual date and time forms used by cMDC.
+pub(crate) struct Parser<'a> {
+    data: &'a [u8],
+    idx: usize,
+}
+
+impl<'a> Parser<'a> {
+    pub(crate) fn new(data: &'a [u8]) -> Self {
+        Parser { data, idx: 0 }
+    }
+
+    pub(crate) fn is_end(&self) -> bool {
+        self.idx == self.data.len()
+    }
+
+    pub(crate) fn expect(&mut self, c: u8) -> Option<()> {
+        if self.data.get(self.idx) == Some(&c) {
+            self.idx += 1;
+            Some(())
+        } else {
+            None
+        }
+    }
+
+    fn digits(&mut self, n: usize) -> Option<u32> {
+        let end = self.idx + n;
+        if end > self.data.len() {
+            return None;
+        }
+        let mut v = 0;
+        for &c in &self.data[self.idx..end] {
+            if !c.is_ascii_digit() {
+                return None;
+            }
+            v = v * 10 + (c - b'0') as u32;
+        }
+        self.idx = end;
+        Some(v)
+    }
+
+    /// `YYYY-MM-DD`
+    pub(crate) fn date(&mut self) -> Option<(u16, u8, u8)> {
+        let year = self.digits(4)? as u16;
+        self.expect(b'-')?;
+        let month = self.digits(2)? as u8;
+        self.expect(b'-')?;
+        let day = self.digits(2)? as u8;
+        Some((year, month, day))
+    }
+
+    /// `hh:mm:ss.ffffff`
+    pub(crate) fn time(&mut self) -> Option<(u8, u8, u8, u32)> {
+        let hour = self.digits(2)? as u8;
+        self.expect(b':')?;
+        let minute = self.digits(2)? as u8;
+        self.expect(b':')?;
+        let second = self.digits(2)? as u8;
+        self.expect(b'.')?;
+        let microsecond = self.digits(6)?;
+        Some((hour, minute, second, microsecond))
+    }
+
+    /// `Z`, `+hh:mm`, `-hh:mm` or nothing, a zero offset must be `Z`.
+    pub(crate) fn offset(&mut self) -> Option<Option<i16>> {
+        match self.data.get(self.idx) {
+            None => Some(None),
+            Some(b'Z') => {
+                self.idx += 1;
+                Some(Some(0))
+            }
+            Some(&c) if c == b'+' || c == b'-' => {
+                self.idx += 1;
+                let hour = self.digits(2)? as i16;
+                self.expect(b':')?;
+                let minute = self.digits(2)? as i16;
+                if minute > 59 {
+                    return None;
+                }
+                let v = hour * 60 + minute;
+                if v == 0 {
+                    return None;
+                }
+                Some(Some(if c == b'-' { -v } else { v }))
+            }
+            Some(_) => None,
+        }
+    }
+}
+
+#[cfg(feature = "chrono")]
+mod chrono_impl {
+    use super::DateTime;
+    use crate::error::Error;
+    use chrono::{Datelike, Timelike};
+
+    impl TryFrom<DateTime> for chrono::NaiveDateTime {
+        type Error = Error;
+
+        fn try_from(v: DateTime) -> Result<Self, Self::Error> {
+            chrono::NaiveDate::from_ymd_opt(v.year as i32, v.month as u32, v.day as u32)
+                .and_then(|d| {
+                    d.and_hms_micro_opt(
+                        v.hour as u32,
+                        v.minute as u32,
+                        v.second as u32,
+                        v.microsecond,
+                    )
+                })
+                .ok_or_else(|| Error::DecodeError(format!("Invalid datetime value '{}'", v)))
+        }
+    }
+
+    impl TryFrom<DateTime> for chrono::DateTime<chrono::FixedOffset> {
+        type Error = Error;
+
+        fn try_from(v: DateTime) -> Result<Self, Self::Error> {
+            let offset = v
+                .offset
+                .and_then(|o| chrono::FixedOffset::east_opt(o as i32 * 60))
+                .ok_or_else(|| Error::DecodeError(format!("Datetime '{}' has no UTC offset", v)))?;
+            let naive = chrono::NaiveDateTime::try_from(v)?;
+            naive
+                .and_local_timezone(offset)
+                .single()
+                .ok_or_else(|| Error::DecodeError(format!("Invalid datetime value '{}'", v)))
+        }
+    }
+
+    impl TryFrom<DateTime> for chrono::DateTime<chrono::Utc> {
+        type Error = Error;
+
+        fn try_from(v: DateTime) -> Result<Self, Self::Error> {
+            let dt = chrono::DateTime::<chrono::FixedOffset>::try_from(v)?;
+            Ok(dt.with_timezone(&chrono::Utc))
+        }
+    }
+
+    impl TryFrom<chrono::NaiveDateTime> for DateTime {
+        type Error = Error;
+
+        fn try_from(v: chrono::NaiveDateTime) -> Result<Self, Self::Error> {
+            from_naive(&v, None)
+        }
+    }
+
+    impl TryFrom<chrono::DateTime<chrono::FixedOffset>> for DateTime {
+        type Error = Error;
+
+        fn try_from(v: chrono::DateTime<chrono::FixedOffset>) -> Result<Self, Self::Error> {
+            let offset = (v.offset().local_minus_utc() / 60) as i16;
+            from_naive(&v.naive_local(), Some(offset))
+        }
+    }
+
+    impl TryFrom<chrono::DateTime<chrono::Utc>> for DateTime {
+        type Error = Error;
+
+        fn try_from(v: chrono::DateTime<chrono::Utc>) -> Result<Self, Self::Error> {
+            from_naive(&v.naive_utc(), Some(0))
+        }
+    }
+
+    fn from_naive(v: &chrono::NaiveDateTime, offset: Option<i16>) -> Result<DateTime, Error> {
+        let year = u16::try_from(v.year())
+            .map_err(|_| Error::EncodeError(format!("Year {} is out of range", v.year())))?;
+        // chrono represents a leap second as nanosecond >= 1_000_000_000
+        let microsecond = (v.nanosecond() / 1000).min(999_999);
+        DateTime::new(
+            year,
+            v.month() as u8,
+            v.day() as u8,
+            v.hour() as u8,
+            v.minute() as u8,
+            v.second() as u8,
+            microsecond,
+            offset,
+        )
+    }
+}
+
+#[cfg(feature = "time")]
+mod time_impl {
+    use super::DateTime;
+    use crate::error::Error;
+
+    impl TryFrom<DateTime> for time::PrimitiveDateTime {
+        type Error = Error;
+
+        fn try_from(v: DateTime) -> Result<Self, Self::Error> {
+            let err = |_| Error::DecodeError(format!("Invalid datetime value '{}'", v));
+            let month = time::Month::try_from(v.month).map_err(err)?;
+            let date = time::Date::from_calendar_date(v.year as i32, month, v.day).map_err(err)?;
+            let time = time::Time::from_hms_micro(v.hour, v.minute, v.second, v.microsecond)
+                .map_err(err)?;
+            Ok(time::PrimitiveDateTime::new(date, time))
+        }
+    }
+
+    impl TryFrom<DateTime> for time::OffsetDateTime {
+        type Error = Error;
+
+        fn try_from(v: DateTime) -> Result<Self, Self::Error> {
+            let offset = v
+                .offset
+                .and_then(|o| time::UtcOffset::from_whole_seconds(o as i32 * 60).ok())
+                .ok_or_else(|| Error::DecodeError(format!("Datetime '{}' has no UTC offset", v)))?;
+            Ok(time::PrimitiveDateTime::try_from(v)?.assume_offset(offset))
+        }
+    }
+
+    impl TryFrom<time::PrimitiveDateTime> for DateTime {
+        type Error = Error;
+
+        fn try_from(v: time::PrimitiveDateTime) -> Result<Self, Self::Error> {
+            from_primitive(&v, None)
+        }
+    }
+
+    impl TryFrom<time::OffsetDateTime> for DateTime {
+        type Error = Error;
+
+        fn try_from(v: time::OffsetDateTime) -> Result<Self, Self::Error> {
+            let offset = (v.offset().whole_seconds() / 60) as i16;
+            from_primitive(
+                &time::PrimitiveDateTime::new(v.date(), v.time()),
+                Some(offset),
+            )
+        }
+    }
+
+    fn from_primitive(v: &time::PrimitiveDateTime, offset: Option<i16>) -> Result<DateTime, Error> {
+        let year = u16::try_from(v.year())
+            .map_err(|_| Error::EncodeError(format!("Year {} is out of range", v.year())))?;
+        DateTime::new(
+            year,
+            v.month() as u8,
+            v.day(),
+            v.hour(),
+            v.minute(),
+            v.second(),
+            v.microsecond(),
+            offset,
+        )
+    }
+}
+
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use super::Date;
+    use super::DateTime;
+    use super::Time;
+    use crate::cmdc::de::deserialize_str_newtype;
+    use crate::cmdc::ser::serialize_str_newtype;
+    use crate::cmdc::ser::DATE;
+    use crate::cmdc::ser::DATETIME;
+    use crate::cmdc::ser::TIME;
+    use serde::Deserialize;
+    use serde::Deserializer;
+    use serde::Serialize;
+    use serde::Serializer;
+
+    macro_rules! impl_serde {
+        ($ty:ty, $name:expr) => {
+            /// Written as a string in other formats.
+            impl Serialize for $ty {
+                fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    serialize_str_newtype(serializer, $name, self)
+                }
+            }
+
+            impl<'de> Deserialize<'de> for $ty {
+                fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    deserialize_str_newtype(deserializer, $name)
+                }
+            }
+        };
+    }
+
+    impl_serde!(DateTime, DATETIME);
+    impl_serde!(Date, DATE);
+    impl_serde!(Time, TIME);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_datetime() {
+        let v: DateTime = "2021-09-07T08:00:25.000001Z".parse().unwrap();
+        assert_eq!(v, DateTime::utc(2021, 9, 7, 8, 0, 25, 1).unwrap());
+        assert_eq!(v.to_string(), "2021-09-07T08:00:25.000001Z");
+    }
+
+    #[test]
+    fn test_parse_datetime_offset() {
+        let v: DateTime = "2021-09-07T08:00:25.500000+08:00".parse().unwrap();
+        assert_eq!(v.microsecond, 500000);
+        assert_eq!(v.offset, Some(480));
+        assert_eq!(v.to_string(), "2021-09-07T08:00:25.500000+08:00");
+
+        let v: DateTime = "2021-09-07T08:00:25.000000-05:30".parse().unwrap();
+        assert_eq!(v.offset, Some(-330));
+        assert_eq!(v.to_string(), "2021-09-07T08:00:25.000000-05:30");
+
+        let v: DateTime = "2021-09-07T08:00:25.000000".parse().unwrap();
+        assert_eq!(v.offset, None);
+        assert_eq!(v.to_string(), "2021-09-07T08:00:25.000000");
+    }
+
+    #[test]
+    fn test_parse_datetime_other_forms() {
+        // Forms that would not encode back to the same bytes are rejected
+        for s in [
+            "2021-09-07T08:00:25.5Z",
+            "2021-09-07T08:00:25Z",
+            "2021-09-07T08:00:25.000000+00:00",
+            "2021-09-07T08:00:25.000000-00:00",
+        ] {
+            let err = s.parse::<DateTime>().unwrap_err();
+            assert_eq!(err.to_string(), format!("Invalid datetime value '{}'", s));
+        }
+        let err = "09:13:02Z".parse::<Time>().unwrap_err();
+        assert_eq!(err.to_string(), "Invalid time value '09:13:02Z'");
+    }
+
+    #[test]
+    fn test_parse_invalid_datetime() {
+        for s in [
+            "",
+            "2021-09-07",
+            "2021-09-07 08:00:25.000000Z",
+            "2021-13-07T08:00:25.000000Z",
+            "2021-02-29T08:00:25.000000Z",
+            "2021-09-07T24:00:25.000000Z",
+            "2021-09-07T08:00:25.0000001Z",
+            "2021-09-07T08:00:25.Z",
+            "2021-09-07T08:00:25.000000X",
+            "2021-09-07T08:00:25.000000+8:00",
+        ] {
+            let err = s.parse::<DateTime>().unwrap_err();
+            assert_eq!(err.to_string(), format!("Invalid datetime value '{}'", s));
+        }
+    }
+
+    #[test]
+    fn test_parse_leap_day() {
+        assert!("2024-02-29T00:00:00.000000Z".parse::<DateTime>().is_ok());
+        assert!("2000-02-29T00:00:00.000000Z".parse::<DateTime>().is_ok());
+        assert!("1900-02-29T00:00:00.000000Z".parse::<DateTime>().is_err());
+    }
+
+    #[test]
+    fn test_parse_date() {
+        let v: Date = "2021-10-31".parse().unwrap();
+        assert_eq!(v, Date::new(2021, 10, 31).unwrap());
+        assert_eq!(v.to_string(), "2021-10-31");
+    }
+
+    #[test]
+    fn test_parse_invalid_date() {
+        for s in [
+            "",
+            "2021-10",
+            "2021-10-32",
+            "2021-00-01",
+            "21-10-31",
+            "2021-10-31Z",
+        ] {
+            let err = s.parse::<Date>().unwrap_err();
+            assert_eq!(err.to_string(), format!("Invalid date value '{}'", s));
+        }
+    }
+
+    #[test]
+    fn test_parse_time() {
+        let v: Time = "09:13:02.667997Z".parse().unwrap();
+        assert_eq!(v, Time::new(9, 13, 2, 667997, Some(0)).unwrap());
+        assert_eq!(v.to_string(), "09:13:02.667997Z");
+
+        let v: Time = "23:59:59.000000".parse().unwrap();
+        assert_eq!(v.offset, None);
+        assert_eq!(v.to_string(), "23:59:59.000000");
+    }
+
+    #[test]
+    fn test_parse_invalid_time() {
+        for s in [
+            "",
+            "09:13",
+            "09:60:02.667997Z",
+            "9:13:02Z",
+            "09:13:02.667997ZZ",
+        ] {
+            let err = s.parse::<Time>().unwrap_err();
+            assert_eq!(err.to_string(), format!("Invalid time value '{}'", s));
+        }
+    }
+
+    #[test]
+    fn test_datetime_parts() {
+        let v: DateTime = "2021-09-07T08:00:25.000001Z".parse().unwrap();
+        assert_eq!(v.date().to_string(), "2021-09-07");
+        assert_eq!(v.time().to_string(), "08:00:25.000001Z");
+        assert_eq!(DateTime::from_parts(v.date(), v.time()), v);
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn test_chrono_conversion() {
+        let v: DateTime = "2021-09-07T08:00:25.000001Z".parse().unwrap();
+        let dt = chrono::DateTime::<chrono::Utc>::try_from(v).unwrap();
+        assert_eq!(dt.to_rfc3339(), "2021-09-07T08:00:25.000001+00:00");
+        assert_eq!(DateTime::try_from(dt).unwrap(), v);
+
+        let v: DateTime = "2021-09-07T08:00:25.000001+08:00".parse().unwrap();
+        let dt = chrono::DateTime::<chrono::FixedOffset>::try_from(v).unwrap();
+        assert_eq!(DateTime::try_from(dt).unwrap(), v);
+
+        let v: DateTime = "2021-09-07T08:00:25.000001".parse().unwrap();
+        assert!(chrono::DateTime::<chrono::Utc>::try_from(v).is_err());
+        let naive = chrono::NaiveDateTime::try_from(v).unwrap();
+        assert_eq!(DateTime::try_from(naive).unwrap(), v);
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn test_time_conversion() {
+        let v: DateTime = "2021-09-07T08:00:25.000001Z".parse().unwrap();
+        let dt = time::OffsetDateTime::try_from(v).unwrap();
+        assert_eq!(dt.unix_timestamp(), 1631001625);
+        assert_eq!(dt.microsecond(), 1);
+        assert_eq!(DateTime::try_from(dt).unwrap(), v);
+
+        let v: DateTime = "2021-09-07T08:00:25.000001".parse().unwrap();
+        assert!(time::OffsetDateTime::try_from(v).is_err());
+        let primitive = time::PrimitiveDateTime::try_from(v).unwrap();
+        assert_eq!(DateTime::try_from(primitive).unwrap(), v);
+    }
+}
@@ -3,34 +3,64 @@ use std::fmt;
 #[derive(Debug)]
 pub enum Error {
     ParseIntError(std::num::ParseIntError),
+    ParseDecimalError(bigdecimal::ParseBigDecimalError),
     Utf8Error(std::str::Utf8Error),
     IoError(std::io::Error),
     InvalidHeader(String),
     DecodeError(String),
     EncodeError(String),
+    UnsupportedFieldType(String),
+    TypeMismatch(String),
+    SchemaError(String),
+    UnknownField(String),
 }
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             Error::ParseIntError(e) => write!(f, "{}", e),
+            Error::ParseDecimalError(e) => write!(f, "{}", e),
             Error::Utf8Error(e) => write!(f, "{}", e),
             Error::IoError(e) => write!(f, "{}", e),
             Error::InvalidHeader(msg) => write!(f, "{}", msg),
             Error::DecodeError(msg) => write!(f, "{}", msg),
             Error::EncodeError(msg) => write!(f, "{}", msg),
+            Error::UnsupportedFieldType(msg) => write!(f, "{}", msg),
+            Error::TypeMismatch(msg) => write!(f, "{}", msg),
+            Error::SchemaError(msg) => write!(f, "{}", msg),
+            Error::UnknownField(msg) => write!(f, "{}", msg),
         }
     }
 }
 
 impl std::error::Error for Error {}
 
+#[cfg(feature = "serde")]
+impl serde::ser::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Error::EncodeError(msg.to_string())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::de::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Error::DecodeError(msg.to_string())
+    }
+}
+
 impl From<std::num::ParseIntError> for Error {
     fn from(err: std::num::ParseIntError) -> Self {
         Error::ParseIntError(err)
     }
 }
 
+impl From<bigdecimal::ParseBigDecimalError> for Error {
+    fn from(err: bigdecimal::ParseBigDecimalError) -> Self {
+        Error::ParseDecimalError(err)
+    }
+}
+
 impl From<std::str::Utf8Error> for Error {
     fn from(err: std::str::Utf8Error) -> Self {
         Error::Utf8Error(err)
@@ -0,0 +1,522 @@
+use super::value::JSON_VALUE_CODEC;
+use super::JsonCodec;
+use crate::error::Error;
+use crate::mdd::Container;
+use crate::mdd::Containers;
+use crate::mdd::Value;
+use crate::named::NamedCodec;
+use crate::named::NamedFields;
+
+impl JsonCodec<'_> {
+    /// Decode a JSON object, or an array of objects, into containers.
+    pub fn decode_containers<'a>(&self, data: &'a [u8]) -> Result<Containers<'a>, Error> {
+        let mut scanner = Scanner::new(data);
+        let containers = self.decode_objects(&mut scanner, None, 0)?;
+        scanner.end()?;
+        Ok(containers)
+    }
+
+    /// An object or an array of objects. Objects without a `$` member are
+    /// taken as the `declared` container.
+    pub(crate) fn decode_objects<'a>(
+        &self,
+        scanner: &mut Scanner<'a>,
+        declared: Option<&str>,
+        depth: i8,
+    ) -> Result<Containers<'a>, Error> {
+        let mut containers = vec![];
+        match scanner.peek() {
+            Some(b'[') => scanner.array(|scanner| {
+                containers.push(self.decode_object(scanner, declared, depth)?);
+                Ok(())
+            })?,
+            _ => containers.push(self.decode_object(scanner, declared, depth)?),
+        }
+        Ok(Containers { containers })
+    }
+
+    /// An array of objects for a list of structs, `null` elements are empty
+    /// and elements of several containers are arrays of objects.
+    pub(crate) fn decode_object_list<'a>(
+        &self,
+        scanner: &mut Scanner<'a>,
+        declared: Option<&str>,
+        depth: i8,
+    ) -> Result<Value<'a>, Error> {
+        let mut values = vec![];
+        scanner.array(|scanner| {
+            if scanner.null() {
+                values.push(None);
+                return Ok(());
+            }
+            let containers = self.decode_objects(scanner, declared, depth)?;
+            values.push(Some(Value::Struct(containers)));
+            Ok(())
+        })?;
+        Ok(Value::List(values))
+    }
+
+    fn decode_object<'a>(
+        &self,
+        scanner: &mut Scanner<'a>,
+        declared: Option<&str>,
+        depth: i8,
+    ) -> Result<Container<'a>, Error> {
+        let mut name = None;
+        let mut header_depth = None;
+        let mut members = vec![];
+        scanner.object(|scanner, key| {
+            match key.as_str() {
+                "$" => name = Some(scanner.string()?),
+                "$depth" => {
+                    let token = scanner.value()?;
+                    let depth = std::str::from_utf8(token).ok().and_then(|d| d.parse().ok());
+                    header_depth = Some(depth.ok_or_else(|| {
+                        Error::DecodeError(format!(
+                            "Invalid container depth '{}'",
+                            String::from_utf8_lossy(token)
+                        ))
+                    })?);
+                }
+                _ => members.push((key, scanner.pos(), scanner.value()?)),
+            }
+            Ok(())
+        })?;
+        let name = name.as_deref().or(declared).ok_or_else(|| {
+            Error::DecodeError("Missing container name '$' in JSON object".into())
+        })?;
+        let mut fields = NamedFields::new(self.lookup(name, declared)?, &JSON_VALUE_CODEC);
+        for (key, pos, token) in members {
+            let field = fields.field(&key)?;
+            if token == b"null" {
+                continue;
+            }
+            let def = field.def;
+            if def.is_struct() {
+                let mut scanner = Scanner::at(scanner.data, pos);
+                let depth = depth.checked_add(1).ok_or_else(|| {
+                    scanner.error(&format!("containers nested deeper than {}", i8::MAX))
+                })?;
+                let declared = def.struct_name.as_deref();
+                field.set_value(match def.is_list {
+                    true => self.decode_object_list(&mut scanner, declared, depth),
+                    false => self
+                        .decode_objects(&mut scanner, declared, depth)
+                        .map(Value::Struct),
+                })?;
+            } else {
+                field.set_data(token);
+            }
+        }
+        fields.finish(header_depth.unwrap_or(depth))
+    }
+}
+
+/// Most objects and arrays a value skipped over may be nested in.
+const MAX_NESTING: usize = 256;
+
+/// Cursor over JSON text. Values are skipped over to keep their text, and
+/// parsed when decoded.
+pub(crate) struct Scanner<'a> {
+    data: &'a [u8],
+    idx: usize,
+    nesting: usize,
+}
+
+impl<'a> Scanner<'a> {
+    pub(crate) fn new(data: &'a [u8]) -> Self {
+        Scanner::at(data, 0)
+    }
+
+    fn at(data: &'a [u8], idx: usize) -> Self {
+        Scanner {
+            data,
+            idx,
+            nesting: 0,
+        }
+    }
+
+    fn pos(&self) -> usize {
+        self.idx
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self
+            .data
+            .get(self.idx)
+            .is_some_and(|c| matches!(c, b' ' | b'\t' | b'\n' | b'\r'))
+        {
+            self.idx += 1;
+        }
+    }
+
+    /// The next character after any whitespace.
+    pub(crate) fn peek(&mut self) -> Option<u8> {
+        self.skip_whitespace();
+        self.data.get(self.idx).copied()
+    }
+
+    fn eat(&mut self, c: u8) -> bool {
+        if self.peek() == Some(c) {
+            self.idx += 1;
+            return true;
+        }
+        false
+    }
+
+    fn expect(&mut self, c: u8) -> Result<(), Error> {
+        match self.eat(c) {
+            true => Ok(()),
+            false => Err(self.error(&format!("'{}' expected", c as char))),
+        }
+    }
+
+    /// Fail unless only whitespace is left.
+    pub(crate) fn end(&mut self) -> Result<(), Error> {
+        match self.peek() {
+            None => Ok(()),
+            Some(_) => Err(self.error("end of data expected")),
+        }
+    }
+
+    fn error(&self, msg: &str) -> Error {
+        Error::DecodeError(format!("Invalid JSON at position {}, {}", self.idx, msg))
+    }
+
+    /// Skip a `null` literal.
+    pub(crate) fn null(&mut self) -> bool {
+        self.skip_whitespace();
+        if self.data[self.idx..].starts_with(b"null") {
+            self.idx += 4;
+            return true;
+        }
+        false
+    }
+
+    /// Call `f` with the key of each member of an object, with the scanner
+    /// at the value, which `f` must consume.
+    pub(crate) fn object<F>(&mut self, mut f: F) -> Result<(), Error>
+    where
+        F: FnMut(&mut Self, String) -> Result<(), Error>,
+    {
+        self.expect(b'{')?;
+        if self.eat(b'}') {
+            return Ok(());
+        }
+        loop {
+            let key = self.string()?;
+            self.expect(b':')?;
+            f(self, key)?;
+            if !self.eat(b',') {
+                return self.expect(b'}');
+            }
+        }
+    }
+
+    /// Call `f` with the scanner at each element of an array.
+    pub(crate) fn array<F>(&mut self, mut f: F) -> Result<(), Error>
+    where
+        F: FnMut(&mut Self) -> Result<(), Error>,
+    {
+        self.expect(b'[')?;
+        if self.eat(b']') {
+            return Ok(());
+        }
+        loop {
+            f(self)?;
+            if !self.eat(b',') {
+                return self.expect(b']');
+            }
+        }
+    }
+
+    /// The text of the next value.
+    pub(crate) fn value(&mut self) -> Result<&'a [u8], Error> {
+        self.skip_whitespace();
+        let start = self.idx;
+        match self.data.get(self.idx) {
+            None => return Err(self.error("value expected")),
+            Some(b'"') => {
+                self.string()?;
+            }
+            Some(b'{' | b'[') if self.nesting == MAX_NESTING => {
+                return Err(self.error(&format!("values nested deeper than {}", MAX_NESTING)))
+            }
+            Some(b'{') => {
+                self.nesting += 1;
+                self.object(|scanner, _| scanner.value().map(|_| ()))?;
+                self.nesting -= 1;
+            }
+            Some(b'[') => {
+                self.nesting += 1;
+                self.array(|scanner| scanner.value().map(|_| ()))?;
+                self.nesting -= 1;
+            }
+            Some(_) => {
+                while self
+                    .data
+                    .get(self.idx)
+                    .is_some_and(|c| c.is_ascii_alphanumeric() || matches!(c, b'-' | b'+' | b'.'))
+                {
+                    self.idx += 1;
+                }
+                if self.idx == start {
+                    return Err(self.error("value expected"));
+                }
+            }
+        }
+        Ok(&self.data[start..self.idx])
+    }
+
+    pub(crate) fn string(&mut self) -> Result<String, Error> {
+        self.expect(b'"')?;
+        let mut bytes = vec![];
+        loop {
+            let c = match self.data.get(self.idx) {
+                Some(&c) => c,
+                None => return Err(self.error("unterminated string")),
+            };
+            self.idx += 1;
+            match c {
+                b'"' => break,
+                b'\\' => self.escape(&mut bytes)?,
+                c if c < 0x20 => return Err(self.error("control character in string")),
+                c => bytes.push(c),
+            }
+        }
+        Ok(std::str::from_utf8(&bytes)?.to_string())
+    }
+
+    fn escape(&mut self, bytes: &mut Vec<u8>) -> Result<(), Error> {
+        let c = self.data.get(self.idx).copied();
+        self.idx += 1;
+        let c = match c {
+            Some(b'"') => '"',
+            Some(b'\\') => '\\',
+            Some(b'/') => '/',
+            Some(b'b') => '\u{8}',
+            Some(b'f') => '\u{c}',
+            Some(b'n') => '\n',
+            Some(b'r') => '\r',
+            Some(b't') => '\t',
+            Some(b'u') => {
+                let mut code = self.hex()?;
+                // A high surrogate must be followed by a low one
+                if (0xd800..0xdc00).contains(&code) && self.data[self.idx..].starts_with(b"\\u") {
+                    self.idx += 2;
+                    let low = self.hex()?;
+                    if !(0xdc00..0xe000).contains(&low) {
+                        return Err(self.error("invalid unicode escape"));
+                    }
+                    code = 0x10000 + ((code - 0xd800) << 10) + (low - 0xdc00);
+                }
+                char::from_u32(code).ok_or_else(|| self.error("invalid unicode escape"))?
+            }
+            _ => return Err(self.error("invalid escape in string")),
+        };
+        let mut buf = [0; 4];
+        bytes.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+        Ok(())
+    }
+
+    fn hex(&mut self) -> Result<u32, Error> {
+        let digits = self
+            .data
+            .get(self.idx..self.idx + 4)
+            .and_then(|d| std::str::from_utf8(d).ok())
+            .and_then(|d| u32::from_str_radix(d, 16).ok())
+            .ok_or_else(|| self.error("invalid unicode escape"))?;
+        self.idx += 4;
+        Ok(digits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codec::Codec;
+    use crate::mdd::FieldType;
+    use crate::schema::ContainerDef;
+    use crate::schema::FieldDef;
+    use crate::schema::SchemaRegistry;
+
+    fn registry() -> SchemaRegistry {
+        let mut registry = SchemaRegistry::new();
+        registry
+            .add(
+                ContainerDef::new("MtxBalanceInfo", 330)
+                    .field(FieldDef::new("TemplateId", FieldType::UInt32))
+                    .field(FieldDef::new("Name", FieldType::String))
+                    .field(FieldDef::new("Amount", FieldType::Decimal)),
+            )
+            .unwrap();
+        registry
+            .add(
+                ContainerDef::new("CustomBalanceInfo", 5001)
+                    .base("MtxBalanceInfo")
+                    .field(FieldDef::new("Region", FieldType::String)),
+            )
+            .unwrap();
+        registry
+            .add(
+                ContainerDef::new("MtxResponseWallet", 263)
+                    .field(FieldDef::new("Result", FieldType::UInt32))
+                    .field(FieldDef::new_struct("BalanceArray", "MtxBalanceInfo").list())
+                    .field(FieldDef::new("ExternalId", FieldType::String))
+                    .field(FieldDef::new("Flags", FieldType::Int16).list()),
+            )
+            .unwrap();
+        registry
+    }
+
+    #[test]
+    fn test_decode_object() {
+        let registry = registry();
+        let codec = JsonCodec::new(&registry, 5222, 2);
+        let data = r#"{
+            "BalanceArray": [
+                {"$": "MtxBalanceInfo", "TemplateId": 200, "Amount": 17485760.0},
+                null,
+                {"Name": "Data \"Plan\" é😀", "$": "CustomBalanceInfo", "Region": "UK"}
+            ],
+            "$": "MtxResponseWallet",
+            "Result": 2,
+            "Flags": [1, null, -3]
+        }"#
+        .as_bytes();
+        let containers = codec.decode(data).unwrap();
+        assert_eq!(containers.containers.len(), 1);
+
+        let wallet = &containers.containers[0];
+        assert_eq!(wallet.name(), Some("MtxResponseWallet"));
+        assert_eq!(wallet.header.key, 263);
+        assert_eq!(wallet.header.schema_version, 5222);
+        assert_eq!(wallet.header.total_field, 4);
+        assert_eq!(wallet.fields[0].data, b"2");
+        assert_eq!(wallet.get::<u32>("Result").unwrap(), 2);
+        assert!(wallet.fields[2].is_null);
+        assert_eq!(
+            wallet.get::<Vec<Option<i16>>>("Flags").unwrap(),
+            [Some(1), None, Some(-3)]
+        );
+
+        let balances = match wallet.field("BalanceArray").unwrap().value().unwrap() {
+            Value::List(values) => values,
+            _ => panic!("Not a list"),
+        };
+        assert_eq!(balances.len(), 3);
+        assert!(balances[1].is_none());
+        let balance = &balances[0]
+            .as_ref()
+            .unwrap()
+            .as_struct()
+            .unwrap()
+            .containers[0];
+        assert_eq!(balance.header.depth, 1);
+        assert_eq!(balance.fields[2].data, b"17485760.0");
+        assert_eq!(
+            balance
+                .get::<bigdecimal::BigDecimal>("Amount")
+                .unwrap()
+                .to_string(),
+            "17485760.0"
+        );
+        let custom = &balances[2]
+            .as_ref()
+            .unwrap()
+            .as_struct()
+            .unwrap()
+            .containers[0];
+        assert_eq!(custom.header.key, 5001);
+        assert_eq!(custom.get::<String>("Name").unwrap(), "Data \"Plan\" é😀");
+        assert_eq!(custom.get::<String>("Region").unwrap(), "UK");
+    }
+
+    #[test]
+    fn test_decode_array() {
+        let registry = registry();
+        let codec = JsonCodec::new(&registry, 5222, 2);
+        let data = br#"[{"$":"MtxBalanceInfo"},{"$":"MtxResponseWallet","ExternalId":null}]"#;
+        let containers = codec.decode(data).unwrap();
+        assert_eq!(containers.containers.len(), 2);
+        assert_eq!(containers.containers[0].field_count(), 0);
+        assert_eq!(containers.containers[0].header.total_field, 3);
+        let wallet = &containers.containers[1];
+        assert_eq!(wallet.fields.len(), 3);
+        assert!(wallet.fields[2].is_null);
+        assert_eq!(wallet.fields[2].data, b"");
+        assert_eq!(wallet.get::<Option<String>>("ExternalId").unwrap(), None);
+    }
+
+    #[test]
+    fn test_decode_invalid() {
+        let registry = registry();
+        let codec = JsonCodec::new(&registry, 5222, 2);
+        let cases: [(&[u8], &str); 10] = [
+            (b"", "Invalid JSON at position 0, '{' expected"),
+            (br#"{"Result": 1}"#, "Missing container name '$' in JSON object"),
+            (br#"{"$": "MtxUnknown"}"#, "Unknown container 'MtxUnknown'"),
+            (
+                br#"{"$": "MtxBalanceInfo", "Result": 1}"#,
+                "Unknown field 'Result' in container 'MtxBalanceInfo'",
+            ),
+            (
+                br#"{"$": "MtxBalanceInfo", "Name": null, "Name": "a"}"#,
+                "Duplicate field 'Name' in container 'MtxBalanceInfo'",
+            ),
+            (
+                br#"{"$": "MtxResponseWallet", "BalanceArray": [{"$": "MtxResponseWallet"}]}"#,
+                "Invalid field 'BalanceArray' in container 'MtxResponseWallet', Container 'MtxResponseWallet' is not a 'MtxBalanceInfo'",
+            ),
+            (
+                br#"{"$": "MtxBalanceInfo", "Name": "a}"#,
+                "Invalid JSON at position 35, unterminated string",
+            ),
+            (
+                br#"{"$": "MtxBalanceInfo", "Name": "\x"}"#,
+                "Invalid JSON at position 35, invalid escape in string",
+            ),
+            (
+                br#"{"$": "MtxBalanceInfo", "$depth": "1"}"#,
+                r#"Invalid container depth '"1"'"#,
+            ),
+            (
+                br#"{"$": "MtxBalanceInfo"} {}"#,
+                "Invalid JSON at position 24, end of data expected",
+            ),
+        ];
+        for (data, expected) in cases {
+            let err = codec.decode(data).unwrap_err();
+            assert_eq!(err.to_string(), expected);
+        }
+    }
+
+    #[test]
+    fn test_decode_nesting_limits() {
+        let mut registry = SchemaRegistry::new();
+        registry
+            .add(ContainerDef::new("Node", 500).field(FieldDef::new_struct("Child", "Node")))
+            .unwrap();
+        let codec = JsonCodec::new(&registry, 5222, 2);
+
+        // Containers nested deeper than the i8 header depth
+        let data = format!(
+            "{}{{\"$\":\"Node\"}}{}",
+            r#"{"$":"Node","Child":"#.repeat(130),
+            "}".repeat(130)
+        );
+        let err = codec.decode(data.as_bytes()).unwrap_err();
+        assert!(matches!(err, Error::DecodeError(_)));
+        assert!(err
+            .to_string()
+            .ends_with("Invalid JSON at position 2560, containers nested deeper than 127"));
+
+        // Values skipped over are not followed without bound
+        let data = format!(r#"{{"$":"Node","Child":{}"#, "[".repeat(100_000));
+        let err = codec.decode(data.as_bytes()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid JSON at position 276, values nested deeper than 256"
+        );
+    }
+}
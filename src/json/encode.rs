@@ -0,0 +1,131 @@
+use super::value::write_string;
+use super::value::JsonValueCodec;
+use super::value::JSON_VALUE_CODEC;
+use super::JsonCodec;
+use crate::error::Error;
+use crate::mdd::Containers;
+use crate::named::NamedCodec;
+use crate::schema::SchemaRegistry;
+
+impl JsonCodec<'_> {
+    /// Encode a single container as an object, other numbers of containers
+    /// as an array of objects.
+    pub fn encode_containers(
+        &self,
+        buffer: &mut Vec<u8>,
+        containers: &Containers,
+    ) -> Result<(), Error> {
+        self.encode_containers_at(buffer, containers, 0)
+    }
+}
+
+impl NamedCodec for JsonCodec<'_> {
+    type Values = JsonValueCodec;
+
+    const LIST: [&'static [u8]; 4] = [b"[", b",", b"null", b"]"];
+
+    fn values(&self) -> &'static JsonValueCodec {
+        &JSON_VALUE_CODEC
+    }
+
+    fn registry(&self) -> &SchemaRegistry {
+        self.registry
+    }
+
+    fn versions(&self) -> (u16, u16) {
+        (self.schema_version, self.ext_version)
+    }
+
+    fn start_container(&self, buffer: &mut Vec<u8>, name: &str, depth: Option<i8>) {
+        buffer.extend_from_slice(b"{\"$\":");
+        write_string(buffer, name);
+        if let Some(depth) = depth {
+            buffer.extend_from_slice(format!(",\"$depth\":{}", depth).as_bytes());
+        }
+    }
+
+    fn end_container(&self, buffer: &mut Vec<u8>, _name: &str) {
+        buffer.push(b'}');
+    }
+
+    fn start_field(&self, buffer: &mut Vec<u8>, name: &str) {
+        buffer.push(b',');
+        write_string(buffer, name);
+        buffer.push(b':');
+    }
+
+    fn end_field(&self, _buffer: &mut Vec<u8>, _name: &str) {}
+
+    fn encode_containers_at(
+        &self,
+        buffer: &mut Vec<u8>,
+        containers: &Containers,
+        depth: i8,
+    ) -> Result<(), Error> {
+        match containers.containers.as_slice() {
+            [container] => self.encode_container(buffer, container, depth),
+            containers => {
+                buffer.push(b'[');
+                for (i, container) in containers.iter().enumerate() {
+                    if i > 0 {
+                        buffer.push(b',');
+                    }
+                    self.encode_container(buffer, container, depth)?;
+                }
+                buffer.push(b']');
+                Ok(())
+            }
+        }
+    }
+
+    fn encode_struct(
+        &self,
+        buffer: &mut Vec<u8>,
+        containers: &Containers,
+        depth: i8,
+    ) -> Result<(), Error> {
+        self.encode_containers_at(buffer, containers, depth)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmdc::CMDC_CODEC;
+    use crate::codec::Codec;
+    use crate::named::tests::registry;
+
+    #[test]
+    fn test_json_round_trip() {
+        let registry = registry();
+        let codec = JsonCodec::new(&registry, 5222, 2);
+        let data = r#"{"$":"MtxResponseWallet","Result":2,"BalanceArray":[{"$":"MtxBalanceInfo","TemplateId":200,"Amount":-1258291032.242187000,"Updated":"2021-09-07T08:00:25.000000Z"},null,{"$":"MtxBalanceInfo","Name":"Tab\t\"quoted\" ü"}],"Flags":[true,null,false],"Balance":{"$":"MtxBalanceInfo","Amount":1E+3}}"#;
+        let containers = codec.decode(data.as_bytes()).unwrap();
+        let encoded = codec.encode(&containers).unwrap();
+        assert_eq!(String::from_utf8(encoded).unwrap(), data);
+    }
+
+    #[test]
+    fn test_cmdc_datetime() {
+        let registry = registry();
+        let codec = JsonCodec::new(&registry, 5222, 2);
+        let data = b"<1,4,0,330,5222,2>[,,,2021-09-07T08:00:25.500000Z]";
+        let json = codec.encode(&CMDC_CODEC.decode(data).unwrap()).unwrap();
+        assert_eq!(
+            json,
+            br#"{"$":"MtxBalanceInfo","Updated":"2021-09-07T08:00:25.500000Z"}"#
+        );
+        let decoded = codec.decode(&json).unwrap();
+        assert_eq!(CMDC_CODEC.encode(&decoded).unwrap(), data);
+
+        // Other forms would not be written back as they were
+        let decoded = codec
+            .decode(br#"{"$":"MtxBalanceInfo","Updated":"2021-09-07T08:00:25.5Z"}"#)
+            .unwrap();
+        let err = CMDC_CODEC.encode(&decoded).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid datetime value '2021-09-07T08:00:25.5Z'"
+        );
+    }
+}
@@ -0,0 +1,84 @@
+//! MDD containers as JSON objects in the form used by the MATRIXX REST API.
+//!
+//! ```json
+//! {"$":"MtxResponseWallet","Result":2,"BalanceArray":[{"$":"MtxBalanceInfo","Amount":17485760.0}]}
+//! ```
+//!
+//! The `$` member names the container definition and the other members are
+//! the fields by name, null fields are left out. A struct field holds an
+//! object, or an array of objects for a list, and the `$` of a nested object
+//! may be left out for the declared struct type. A list element holding
+//! several containers is an array of objects. Other lists are arrays.
+//! Containers whose header depth is not their nesting depth keep it in a
+//! `$depth` member.
+//!
+//! Integers and decimals are numbers, decimals keep all their digits and
+//! may also be given as strings. Datetimes, dates, times and object ids are
+//! strings in their cMDC form.
+
+pub mod decode;
+pub mod encode;
+pub mod value;
+
+use crate::codec::Codec;
+use crate::error::Error;
+use crate::mdd::Containers;
+use crate::mdd::Field;
+use crate::mdd::FieldType;
+use crate::mdd::Value;
+use crate::named::NamedCodec;
+use crate::schema::SchemaRegistry;
+use decode::Scanner;
+use value::JSON_VALUE_CODEC;
+
+/// Codec for JSON containers, which are matched to their definitions by
+/// name. Decoded containers get the layout of the given versions.
+#[derive(Debug, Clone)]
+pub struct JsonCodec<'r> {
+    registry: &'r SchemaRegistry,
+    schema_version: u16,
+    ext_version: u16,
+}
+
+impl<'r> JsonCodec<'r> {
+    pub fn new(registry: &'r SchemaRegistry, schema_version: u16, ext_version: u16) -> Self {
+        JsonCodec {
+            registry,
+            schema_version,
+            ext_version,
+        }
+    }
+}
+
+impl Codec for JsonCodec<'_> {
+    fn name(&self) -> &'static str {
+        "json"
+    }
+
+    fn decode<'a>(&self, data: &'a [u8]) -> Result<Containers<'a>, Error> {
+        self.decode_containers(data)
+    }
+
+    fn encode(&self, containers: &Containers) -> Result<Vec<u8>, Error> {
+        let mut buffer = Vec::new();
+        self.encode_containers(&mut buffer, containers)?;
+        Ok(buffer)
+    }
+
+    fn decode_field<'a>(&self, field: &Field<'a>) -> Result<Value<'a>, Error> {
+        if field.field_type != FieldType::Struct {
+            return JSON_VALUE_CODEC.decode_field(field);
+        }
+        let mut scanner = Scanner::new(field.data);
+        let value = match field.is_multi {
+            true => self.decode_object_list(&mut scanner, None, 1)?,
+            false => Value::Struct(self.decode_objects(&mut scanner, None, 1)?),
+        };
+        scanner.end()?;
+        Ok(value)
+    }
+
+    fn encode_field(&self, field: &Field) -> Result<Vec<u8>, Error> {
+        self.encode_named_field(field)
+    }
+}
@@ -0,0 +1,328 @@
+use super::decode::Scanner;
+use crate::codec::Codec;
+use crate::error::Error;
+use crate::mdd::Containers;
+use crate::mdd::Field;
+use crate::mdd::FieldType;
+use crate::mdd::Value;
+use crate::named::decode_value_field;
+use crate::named::encode_value_field;
+use crate::named::ValueCodec;
+use bigdecimal::BigDecimal;
+use std::str::FromStr;
+
+/// Decodes the JSON text that `JsonCodec` keeps for fields of any type but
+/// struct.
+pub(crate) static JSON_VALUE_CODEC: JsonValueCodec = JsonValueCodec {};
+
+#[derive(Debug)]
+pub(crate) struct JsonValueCodec {}
+
+impl Codec for JsonValueCodec {
+    fn name(&self) -> &'static str {
+        "json"
+    }
+
+    fn decode<'a>(&self, _data: &'a [u8]) -> Result<Containers<'a>, Error> {
+        Err(Error::DecodeError(
+            "JSON containers are decoded with a schema by JsonCodec".into(),
+        ))
+    }
+
+    fn encode(&self, _containers: &Containers) -> Result<Vec<u8>, Error> {
+        Err(Error::EncodeError(
+            "JSON containers are encoded with a schema by JsonCodec".into(),
+        ))
+    }
+
+    fn decode_field<'a>(&self, field: &Field<'a>) -> Result<Value<'a>, Error> {
+        decode_value_field(self, field)
+    }
+
+    fn encode_field(&self, field: &Field) -> Result<Vec<u8>, Error> {
+        encode_value_field(self, field)
+    }
+}
+
+impl ValueCodec for JsonValueCodec {
+    fn decode_value<'a>(&self, field_type: &FieldType, data: &[u8]) -> Result<Value<'a>, Error> {
+        let text = || String::from_utf8_lossy(data);
+        let invalid = || {
+            Error::DecodeError(format!(
+                "Invalid JSON {} value '{}'",
+                field_type.name(),
+                text()
+            ))
+        };
+        match field_type {
+            FieldType::String => Ok(Value::String(string(data)?)),
+            FieldType::Int8 => Ok(Value::Int8(number(data).ok_or_else(invalid)?)),
+            FieldType::Int16 => Ok(Value::Int16(number(data).ok_or_else(invalid)?)),
+            FieldType::Int32 => Ok(Value::Int32(number(data).ok_or_else(invalid)?)),
+            FieldType::Int64 => Ok(Value::Int64(number(data).ok_or_else(invalid)?)),
+            FieldType::UInt8 => Ok(Value::UInt8(number(data).ok_or_else(invalid)?)),
+            FieldType::UInt16 => Ok(Value::UInt16(number(data).ok_or_else(invalid)?)),
+            FieldType::UInt32 => Ok(Value::UInt32(number(data).ok_or_else(invalid)?)),
+            FieldType::UInt64 => Ok(Value::UInt64(number(data).ok_or_else(invalid)?)),
+            FieldType::Bool => match data {
+                b"true" => Ok(Value::Bool(true)),
+                b"false" => Ok(Value::Bool(false)),
+                _ => Err(invalid()),
+            },
+            FieldType::Decimal => {
+                let v = match data.first() {
+                    Some(b'"') => BigDecimal::from_str(&string(data)?).ok(),
+                    _ => number(data),
+                };
+                Ok(Value::Decimal(v.ok_or_else(invalid)?))
+            }
+            FieldType::DateTime => Ok(Value::DateTime(string(data)?.parse()?)),
+            FieldType::Date => Ok(Value::Date(string(data)?.parse()?)),
+            FieldType::Time => Ok(Value::Time(string(data)?.parse()?)),
+            FieldType::ObjectId => Ok(Value::ObjectId(string(data)?.parse()?)),
+            FieldType::Struct => Err(Error::UnsupportedFieldType(
+                "JSON struct fields are decoded with a schema by JsonCodec".into(),
+            )),
+            FieldType::Unknown => Err(Error::UnsupportedFieldType(
+                "Unsupported field type Unknown, cannot decode field".into(),
+            )),
+        }
+    }
+
+    fn decode_list<'a>(
+        &self,
+        field_type: &FieldType,
+        data: &[u8],
+    ) -> Result<Vec<Option<Value<'a>>>, Error> {
+        let mut values = vec![];
+        let mut scanner = Scanner::new(data);
+        scanner.array(|scanner| {
+            let element = scanner.value()?;
+            match element {
+                b"null" => values.push(None),
+                _ => values.push(Some(self.decode_value(field_type, element)?)),
+            }
+            Ok(())
+        })?;
+        scanner.end()?;
+        Ok(values)
+    }
+
+    fn encode_value(&self, field_type: &FieldType, value: &Value) -> Result<Vec<u8>, Error> {
+        if let Value::List(values) = value {
+            return self.encode_list(field_type, values);
+        }
+        let mismatch = || {
+            Error::TypeMismatch(format!(
+                "Value type mismatch, expected {:?} but found {}",
+                field_type,
+                value.type_name()
+            ))
+        };
+        let number = |v: Option<String>| v.map(String::into_bytes).ok_or_else(mismatch);
+        let string = |v: Option<String>| v.map(|v| quoted(&v)).ok_or_else(mismatch);
+        let data = match field_type {
+            FieldType::String => quoted(value.as_string().ok_or_else(mismatch)?),
+            FieldType::Int8 => number(value.as_int8().map(|v| v.to_string()))?,
+            FieldType::Int16 => number(value.as_int16().map(|v| v.to_string()))?,
+            FieldType::Int32 => number(value.as_int32().map(|v| v.to_string()))?,
+            FieldType::Int64 => number(value.as_int64().map(|v| v.to_string()))?,
+            FieldType::UInt8 => number(value.as_uint8().map(|v| v.to_string()))?,
+            FieldType::UInt16 => number(value.as_uint16().map(|v| v.to_string()))?,
+            FieldType::UInt32 => number(value.as_uint32().map(|v| v.to_string()))?,
+            FieldType::UInt64 => number(value.as_uint64().map(|v| v.to_string()))?,
+            FieldType::Bool => number(value.as_bool().map(|v| v.to_string()))?,
+            FieldType::Decimal => number(value.as_decimal().map(|v| v.to_string()))?,
+            FieldType::DateTime => string(value.as_datetime().map(|v| v.to_string()))?,
+            FieldType::Date => string(value.as_date().map(|v| v.to_string()))?,
+            FieldType::Time => string(value.as_time().map(|v| v.to_string()))?,
+            FieldType::ObjectId => string(value.as_object_id().map(|v| v.to_string()))?,
+            FieldType::Struct => {
+                return Err(Error::UnsupportedFieldType(
+                    "JSON struct fields are encoded with a schema by JsonCodec".into(),
+                ))
+            }
+            FieldType::Unknown => {
+                return Err(Error::UnsupportedFieldType(
+                    "Unsupported field type Unknown, cannot encode field".into(),
+                ))
+            }
+        };
+        Ok(data)
+    }
+}
+
+impl JsonValueCodec {
+    pub(crate) fn encode_list(
+        &self,
+        field_type: &FieldType,
+        values: &[Option<Value>],
+    ) -> Result<Vec<u8>, Error> {
+        let mut data = Vec::new();
+        data.push(b'[');
+        for (i, value) in values.iter().enumerate() {
+            if i > 0 {
+                data.push(b',');
+            }
+            match value {
+                Some(value) => data.extend_from_slice(&self.encode_value(field_type, value)?),
+                None => data.extend_from_slice(b"null"),
+            }
+        }
+        data.push(b']');
+        Ok(data)
+    }
+}
+
+/// A JSON string value.
+fn string(data: &[u8]) -> Result<String, Error> {
+    let mut scanner = Scanner::new(data);
+    let s = scanner.string()?;
+    scanner.end()?;
+    Ok(s)
+}
+
+/// A JSON number as `T`, only plain numbers are accepted so strings such as
+/// `"12"` or `+12` are not taken as numbers.
+fn number<T: FromStr>(data: &[u8]) -> Option<T> {
+    match data.first() {
+        Some(c) if c.is_ascii_digit() || *c == b'-' => std::str::from_utf8(data).ok()?.parse().ok(),
+        _ => None,
+    }
+}
+
+fn quoted(s: &str) -> Vec<u8> {
+    let mut data = Vec::with_capacity(s.len() + 2);
+    write_string(&mut data, s);
+    data
+}
+
+/// Write a string as a quoted JSON string.
+pub(crate) fn write_string(data: &mut Vec<u8>, s: &str) {
+    data.push(b'"');
+    for c in s.chars() {
+        match c {
+            '"' => data.extend_from_slice(b"\\\""),
+            '\\' => data.extend_from_slice(b"\\\\"),
+            '\n' => data.extend_from_slice(b"\\n"),
+            '\r' => data.extend_from_slice(b"\\r"),
+            '\t' => data.extend_from_slice(b"\\t"),
+            c if (c as u32) < 0x20 => {
+                data.extend_from_slice(format!("\\u{:04x}", c as u32).as_bytes())
+            }
+            c => {
+                let mut buf = [0; 4];
+                data.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+            }
+        }
+    }
+    data.push(b'"');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(field_type: FieldType, data: &[u8]) -> Value<'static> {
+        let value = JSON_VALUE_CODEC.decode_value(&field_type, data).unwrap();
+        let encoded = JSON_VALUE_CODEC.encode_value(&field_type, &value).unwrap();
+        assert_eq!(
+            String::from_utf8_lossy(&encoded),
+            String::from_utf8_lossy(data)
+        );
+        value
+    }
+
+    #[test]
+    fn test_values() {
+        assert_eq!(round_trip(FieldType::Int8, b"-12").as_int8(), Some(-12));
+        let v = round_trip(FieldType::UInt64, b"18446744073709551615");
+        assert_eq!(v.as_uint64(), Some(u64::MAX));
+        assert_eq!(round_trip(FieldType::Bool, b"false").as_bool(), Some(false));
+        let v = round_trip(FieldType::String, r#""a\"b\\c\nd\u0001é""#.as_bytes());
+        assert_eq!(v.as_string(), Some("a\"b\\c\nd\u{1}é"));
+        round_trip(FieldType::ObjectId, br#""0:1:5:277""#);
+        round_trip(FieldType::Date, br#""2021-10-31""#);
+        round_trip(FieldType::Time, br#""09:13:02.667997+01:00""#);
+    }
+
+    #[test]
+    fn test_decimal_and_datetime_values() {
+        for data in [
+            &b"17485760.0"[..],
+            b"-1258291032.242187",
+            b"0.000000000000000000000000000001",
+            b"123456789012345678901234567890.123456789",
+        ] {
+            let v = round_trip(FieldType::Decimal, data);
+            assert_eq!(v.as_decimal().unwrap().to_string().as_bytes(), data);
+        }
+        let v = JSON_VALUE_CODEC
+            .decode_value(&FieldType::Decimal, br#""1.50""#)
+            .unwrap();
+        assert_eq!(v.as_decimal().unwrap().to_string(), "1.50");
+
+        for data in [
+            &br#""2021-09-07T08:00:25.000000Z""#[..],
+            br#""2021-09-07T08:00:25.123456-05:30""#,
+        ] {
+            round_trip(FieldType::DateTime, data);
+        }
+    }
+
+    #[test]
+    fn test_list_value() {
+        let field = Field {
+            data: b"[ \"a\", null,\"b\" ]",
+            field_type: FieldType::String,
+            value: None,
+            codec: Some(&JSON_VALUE_CODEC),
+            is_multi: true,
+            is_container: false,
+            is_null: false,
+        };
+        let value = JSON_VALUE_CODEC.decode_field(&field).unwrap();
+        let encoded = JSON_VALUE_CODEC
+            .encode_value(&FieldType::String, &value)
+            .unwrap();
+        assert_eq!(encoded, br#"["a",null,"b"]"#);
+    }
+
+    #[test]
+    fn test_invalid_values() {
+        let cases: [(FieldType, &[u8], &str); 6] = [
+            (FieldType::UInt8, b"256", "Invalid JSON uint8 value '256'"),
+            (FieldType::Int32, b"1.0", "Invalid JSON int32 value '1.0'"),
+            (
+                FieldType::Int32,
+                br#""1""#,
+                "Invalid JSON int32 value '\"1\"'",
+            ),
+            (FieldType::Bool, b"1", "Invalid JSON bool value '1'"),
+            (
+                FieldType::Decimal,
+                b"1.2.3",
+                "Invalid JSON decimal value '1.2.3'",
+            ),
+            (
+                FieldType::String,
+                b"12",
+                "Invalid JSON at position 0, '\"' expected",
+            ),
+        ];
+        for (field_type, data, expected) in cases {
+            let err = JSON_VALUE_CODEC
+                .decode_value(&field_type, data)
+                .unwrap_err();
+            assert_eq!(err.to_string(), expected);
+        }
+
+        let err = JSON_VALUE_CODEC
+            .encode_value(&FieldType::UInt32, &Value::String("1".into()))
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Value type mismatch, expected UInt32 but found String"
+        );
+    }
+}
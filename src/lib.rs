@@ -1,4 +1,18 @@
+// Lets the derive macros refer to this crate as `rust_mdd` from within it
+extern crate self as rust_mdd;
+
 pub mod cmdc;
 pub mod codec;
+pub mod codegen;
+pub mod container;
+pub mod datetime;
 pub mod error;
+pub mod json;
 pub mod mdd;
+mod named;
+pub mod object_id;
+pub mod schema;
+pub mod xml;
+
+// Decimal values are `bigdecimal::BigDecimal`, re-exported for generated code
+pub use bigdecimal;
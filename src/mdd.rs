@@ -1,6 +1,12 @@
 use crate::codec::Codec;
+use crate::datetime::Date;
+use crate::datetime::DateTime;
+use crate::datetime::Time;
 use crate::error::Error;
+use crate::object_id::ObjectId;
+use crate::schema::ContainerSchema;
 use core::clone::Clone;
+use std::sync::Arc;
 
 #[derive(Debug, Clone)]
 pub struct Containers<'a> {
@@ -11,6 +17,8 @@ pub struct Containers<'a> {
 pub struct Container<'a> {
     pub header: Header,
     pub fields: Vec<Field<'a>>,
+    /// Layout of the container, set by `SchemaRegistry::apply()`.
+    pub(crate) schema: Option<Arc<ContainerSchema>>,
 }
 
 #[derive(Debug, Clone)]
@@ -48,9 +56,15 @@ pub enum Value<'a> {
     UInt64(u64),
     Bool(bool),
     Decimal(bigdecimal::BigDecimal),
+    DateTime(DateTime),
+    Date(Date),
+    Time(Time),
+    ObjectId(ObjectId),
+    /// Elements of a multi-value field, `None` for an empty element.
+    List(Vec<Option<Value<'a>>>),
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum FieldType {
     Unknown,
     Struct,
@@ -65,6 +79,70 @@ pub enum FieldType {
     UInt64,
     Bool,
     Decimal,
+    DateTime,
+    Date,
+    Time,
+    ObjectId,
+}
+
+impl FieldType {
+    pub fn name(&self) -> &'static str {
+        match self {
+            FieldType::Unknown => "unknown",
+            FieldType::Struct => "struct",
+            FieldType::String => "string",
+            FieldType::Int8 => "int8",
+            FieldType::Int16 => "int16",
+            FieldType::Int32 => "int32",
+            FieldType::Int64 => "int64",
+            FieldType::UInt8 => "uint8",
+            FieldType::UInt16 => "uint16",
+            FieldType::UInt32 => "uint32",
+            FieldType::UInt64 => "uint64",
+            FieldType::Bool => "bool",
+            FieldType::Decimal => "decimal",
+            FieldType::DateTime => "datetime",
+            FieldType::Date => "date",
+            FieldType::Time => "time",
+            FieldType::ObjectId => "object_id",
+        }
+    }
+}
+
+impl std::str::FromStr for FieldType {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "unknown" => Ok(FieldType::Unknown),
+            "struct" => Ok(FieldType::Struct),
+            "string" => Ok(FieldType::String),
+            "int8" => Ok(FieldType::Int8),
+            "int16" => Ok(FieldType::Int16),
+            "int32" => Ok(FieldType::Int32),
+            "int64" => Ok(FieldType::Int64),
+            "uint8" => Ok(FieldType::UInt8),
+            "uint16" => Ok(FieldType::UInt16),
+            "uint32" => Ok(FieldType::UInt32),
+            "uint64" => Ok(FieldType::UInt64),
+            "bool" => Ok(FieldType::Bool),
+            "decimal" => Ok(FieldType::Decimal),
+            "datetime" => Ok(FieldType::DateTime),
+            "date" => Ok(FieldType::Date),
+            "time" => Ok(FieldType::Time),
+            "object_id" => Ok(FieldType::ObjectId),
+            _ => Err(Error::UnsupportedFieldType(format!(
+                "Unknown field type '{}'",
+                s
+            ))),
+        }
+    }
+}
+
+impl std::fmt::Display for FieldType {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
 }
 
 impl<'a> Field<'a> {
@@ -114,7 +192,157 @@ impl<'a> Field<'a> {
     }
 }
 
+impl<'a> Container<'a> {
+    /// A container without an attached schema.
+    pub fn new(header: Header, fields: Vec<Field<'a>>) -> Self {
+        Container {
+            header,
+            fields,
+            schema: None,
+        }
+    }
+
+    /// Layout of the container, set by `SchemaRegistry::apply()`.
+    pub fn schema(&self) -> Option<&Arc<ContainerSchema>> {
+        self.schema.as_ref()
+    }
+
+    /// Number of fields in the body, an empty body `[]` has none. A single
+    /// field built from a value counts even though it has no data.
+    pub fn field_count(&self) -> usize {
+        match self.fields.as_slice() {
+            [field] if field.is_null && field.value.is_none() && field.data.is_empty() => 0,
+            fields => fields.len(),
+        }
+    }
+
+    /// Name of the container definition from the attached schema.
+    pub fn name(&self) -> Option<&str> {
+        self.schema.as_ref().map(|s| s.name.as_str())
+    }
+
+    /// Whether the container is the named container or derived from it.
+    pub fn is_a(&self, name: &str) -> bool {
+        self.schema.as_ref().is_some_and(|s| s.is_a(name))
+    }
+
+    /// Position of the named field, looked up in the attached schema.
+    pub fn field_index(&self, name: &str) -> Result<usize, Error> {
+        let schema = self.schema.as_ref().ok_or_else(|| {
+            Error::SchemaError(format!(
+                "No schema attached to container with key {}",
+                self.header.key
+            ))
+        })?;
+        schema.field_index(name).ok_or_else(|| {
+            Error::UnknownField(format!(
+                "Unknown field '{}' in container '{}'",
+                name, schema.name
+            ))
+        })
+    }
+
+    pub fn field(&self, name: &str) -> Result<&Field<'a>, Error> {
+        let idx = self.field_index(name)?;
+        self.fields.get(idx).ok_or_else(|| self.missing(name))
+    }
+
+    pub fn field_mut(&mut self, name: &str) -> Result<&mut Field<'a>, Error> {
+        let idx = self.field_index(name)?;
+        if idx >= self.fields.len() {
+            return Err(self.missing(name));
+        }
+        Ok(&mut self.fields[idx])
+    }
+
+    /// Value of the named field converted to `T`. Fields left out at the end
+    /// of the container read as null, fields not decoded yet are decoded
+    /// without caching the value.
+    pub fn get<T: FromValue<'a>>(&self, name: &str) -> Result<T, Error> {
+        let idx = self.field_index(name)?;
+        let error = |e: Error| {
+            Error::TypeMismatch(format!(
+                "Invalid field '{}' in container '{}', {}",
+                name,
+                self.name().unwrap_or_default(),
+                e
+            ))
+        };
+
+        let field = match self.fields.get(idx) {
+            Some(field) if !field.is_null => field,
+            _ => {
+                return T::from_null()
+                    .ok_or_else(|| error(Error::TypeMismatch("value is null".into())))
+            }
+        };
+        match &field.value {
+            Some(value) => T::from_value(value).map_err(error),
+            None => {
+                let codec = field
+                    .codec
+                    .ok_or_else(|| Error::DecodeError("No codec".into()))?;
+                let value = codec.decode_field(field)?;
+                T::from_value(&value).map_err(error)
+            }
+        }
+    }
+
+    /// Value of the field at `idx` converted to `T`. Fields not decoded yet
+    /// are decoded as `T` without caching the value, so this works on
+    /// containers decoded without a schema.
+    pub fn get_at<T: FromValue<'a> + ToValue>(&self, idx: usize) -> Result<T, Error> {
+        let field = match self.fields.get(idx) {
+            Some(field) if !field.is_null => field,
+            _ => return T::from_null().ok_or_else(|| Error::TypeMismatch("value is null".into())),
+        };
+        if let Some(value) = &field.value {
+            return T::from_value(value);
+        }
+
+        let codec = field
+            .codec
+            .ok_or_else(|| Error::DecodeError("No codec".into()))?;
+        let field = Field {
+            field_type: T::field_type(),
+            is_multi: T::is_list(),
+            ..field.clone()
+        };
+        T::from_value(&codec.decode_field(&field)?)
+    }
+
+    fn missing(&self, name: &str) -> Error {
+        Error::UnknownField(format!(
+            "Field '{}' is not present in container '{}'",
+            name,
+            self.name().unwrap_or_default()
+        ))
+    }
+}
+
 impl<'a> Value<'a> {
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Struct(_) => "Struct",
+            Value::String(_) => "String",
+            Value::Int8(_) => "Int8",
+            Value::Int16(_) => "Int16",
+            Value::Int32(_) => "Int32",
+            Value::Int64(_) => "Int64",
+            Value::UInt8(_) => "UInt8",
+            Value::UInt16(_) => "UInt16",
+            Value::UInt32(_) => "UInt32",
+            Value::UInt64(_) => "UInt64",
+            Value::Bool(_) => "Bool",
+            Value::Decimal(_) => "Decimal",
+            Value::DateTime(_) => "DateTime",
+            Value::Date(_) => "Date",
+            Value::Time(_) => "Time",
+            Value::ObjectId(_) => "ObjectId",
+            Value::List(_) => "List",
+        }
+    }
+
     pub fn as_struct(&self) -> Option<&Containers<'a>> {
         match self {
             Value::Struct(v) => Some(v),
@@ -187,11 +415,274 @@ impl<'a> Value<'a> {
             _ => None,
         }
     }
+    pub fn as_datetime(&self) -> Option<&DateTime> {
+        match self {
+            Value::DateTime(v) => Some(v),
+            _ => None,
+        }
+    }
+    pub fn as_date(&self) -> Option<&Date> {
+        match self {
+            Value::Date(v) => Some(v),
+            _ => None,
+        }
+    }
+    pub fn as_time(&self) -> Option<&Time> {
+        match self {
+            Value::Time(v) => Some(v),
+            _ => None,
+        }
+    }
+    pub fn as_object_id(&self) -> Option<&ObjectId> {
+        match self {
+            Value::ObjectId(v) => Some(v),
+            _ => None,
+        }
+    }
+    pub fn as_list(&self) -> Option<&[Option<Value<'a>>]> {
+        match self {
+            Value::List(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Named field of a struct value holding a single container.
+    pub fn field(&self, name: &str) -> Result<&Field<'a>, Error> {
+        self.container()?.field(name)
+    }
+
+    /// Value of the named field of a struct value holding a single container.
+    pub fn get<T: FromValue<'a>>(&self, name: &str) -> Result<T, Error> {
+        self.container()?.get(name)
+    }
+
+    fn container(&self) -> Result<&Container<'a>, Error> {
+        match self {
+            Value::Struct(v) if v.containers.len() == 1 => Ok(&v.containers[0]),
+            Value::Struct(v) => Err(Error::TypeMismatch(format!(
+                "Struct holds {} containers, expected 1",
+                v.containers.len()
+            ))),
+            _ => Err(mismatch("Struct", self)),
+        }
+    }
+}
+
+/// Conversion from a decoded field value, used by `Container::get()`.
+pub trait FromValue<'a>: Sized {
+    fn from_value(value: &Value<'a>) -> Result<Self, Error>;
+
+    /// Value of a null field, `None` when null is not allowed.
+    fn from_null() -> Option<Self> {
+        None
+    }
+}
+
+fn mismatch(expected: &str, value: &Value) -> Error {
+    Error::TypeMismatch(format!(
+        "Value type mismatch, expected {} but found {}",
+        expected,
+        value.type_name()
+    ))
+}
+
+macro_rules! from_int_value {
+    ($($t:ty),*) => {
+        $(
+            impl<'a> FromValue<'a> for $t {
+                fn from_value(value: &Value<'a>) -> Result<Self, Error> {
+                    let v = match value {
+                        Value::Int8(v) => <$t>::try_from(*v).ok(),
+                        Value::Int16(v) => <$t>::try_from(*v).ok(),
+                        Value::Int32(v) => <$t>::try_from(*v).ok(),
+                        Value::Int64(v) => <$t>::try_from(*v).ok(),
+                        Value::UInt8(v) => <$t>::try_from(*v).ok(),
+                        Value::UInt16(v) => <$t>::try_from(*v).ok(),
+                        Value::UInt32(v) => <$t>::try_from(*v).ok(),
+                        Value::UInt64(v) => <$t>::try_from(*v).ok(),
+                        _ => return Err(mismatch(stringify!($t), value)),
+                    };
+                    v.ok_or_else(|| {
+                        Error::TypeMismatch(format!(
+                            "Value {:?} out of range for {}",
+                            value,
+                            stringify!($t)
+                        ))
+                    })
+                }
+            }
+        )*
+    };
+}
+
+from_int_value!(i8, i16, i32, i64, u8, u16, u32, u64);
+
+macro_rules! from_value {
+    ($t:ty, $variant:ident) => {
+        impl<'a> FromValue<'a> for $t {
+            fn from_value(value: &Value<'a>) -> Result<Self, Error> {
+                match value {
+                    Value::$variant(v) => Ok(v.clone()),
+                    _ => Err(mismatch(stringify!($variant), value)),
+                }
+            }
+        }
+    };
+}
+
+from_value!(bool, Bool);
+from_value!(String, String);
+from_value!(bigdecimal::BigDecimal, Decimal);
+from_value!(DateTime, DateTime);
+from_value!(Date, Date);
+from_value!(Time, Time);
+from_value!(ObjectId, ObjectId);
+from_value!(Containers<'a>, Struct);
+
+impl<'a> FromValue<'a> for Value<'a> {
+    fn from_value(value: &Value<'a>) -> Result<Self, Error> {
+        Ok(value.clone())
+    }
+}
+
+impl<'a, T: FromValue<'a>> FromValue<'a> for Option<T> {
+    fn from_value(value: &Value<'a>) -> Result<Self, Error> {
+        T::from_value(value).map(Some)
+    }
+
+    fn from_null() -> Option<Self> {
+        Some(None)
+    }
+}
+
+/// Null lists read as empty.
+impl<'a, T: FromValue<'a>> FromValue<'a> for Vec<T> {
+    fn from_value(value: &Value<'a>) -> Result<Self, Error> {
+        let values = value.as_list().ok_or_else(|| mismatch("List", value))?;
+        values
+            .iter()
+            .map(|v| match v {
+                Some(v) => T::from_value(v),
+                None => T::from_null()
+                    .ok_or_else(|| Error::TypeMismatch("List element is null".to_string())),
+            })
+            .collect()
+    }
+
+    fn from_null() -> Option<Self> {
+        Some(vec![])
+    }
+}
+
+/// Conversion into a field value, the counterpart of `FromValue`.
+pub trait ToValue {
+    /// Type of the field holding the value, the element type for lists.
+    fn field_type() -> FieldType;
+
+    fn is_list() -> bool {
+        false
+    }
+
+    /// Value of the field, `None` for a null field.
+    fn to_value(&self) -> Result<Option<Value<'static>>, Error>;
+}
+
+macro_rules! to_value {
+    ($t:ty, $variant:ident, $field_type:ident) => {
+        impl ToValue for $t {
+            fn field_type() -> FieldType {
+                FieldType::$field_type
+            }
+
+            fn to_value(&self) -> Result<Option<Value<'static>>, Error> {
+                Ok(Some(Value::$variant(self.clone())))
+            }
+        }
+    };
+}
+
+to_value!(i8, Int8, Int8);
+to_value!(i16, Int16, Int16);
+to_value!(i32, Int32, Int32);
+to_value!(i64, Int64, Int64);
+to_value!(u8, UInt8, UInt8);
+to_value!(u16, UInt16, UInt16);
+to_value!(u32, UInt32, UInt32);
+to_value!(u64, UInt64, UInt64);
+to_value!(bool, Bool, Bool);
+to_value!(String, String, String);
+to_value!(bigdecimal::BigDecimal, Decimal, Decimal);
+to_value!(DateTime, DateTime, DateTime);
+to_value!(Date, Date, Date);
+to_value!(Time, Time, Time);
+to_value!(ObjectId, ObjectId, ObjectId);
+to_value!(Containers<'static>, Struct, Struct);
+
+impl<T: ToValue> ToValue for Option<T> {
+    fn field_type() -> FieldType {
+        T::field_type()
+    }
+
+    fn is_list() -> bool {
+        T::is_list()
+    }
+
+    fn to_value(&self) -> Result<Option<Value<'static>>, Error> {
+        match self {
+            Some(v) => v.to_value(),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Empty lists are written as null.
+impl<T: ToValue> ToValue for Vec<T> {
+    fn field_type() -> FieldType {
+        T::field_type()
+    }
+
+    fn is_list() -> bool {
+        true
+    }
+
+    fn to_value(&self) -> Result<Option<Value<'static>>, Error> {
+        if self.is_empty() {
+            return Ok(None);
+        }
+        let values = self
+            .iter()
+            .map(|v| v.to_value())
+            .collect::<Result<Vec<_>, Error>>()?;
+        Ok(Some(Value::List(values)))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::cmdc::CMDC_CODEC;
+    use crate::container::ContainerBuilder;
+    use crate::schema::ContainerDef;
+    use crate::schema::FieldDef;
+    use crate::schema::SchemaRegistry;
+
+    #[test]
+    fn test_field_type_name() {
+        for field_type in [
+            FieldType::Struct,
+            FieldType::String,
+            FieldType::Int8,
+            FieldType::UInt64,
+            FieldType::Decimal,
+            FieldType::DateTime,
+            FieldType::ObjectId,
+        ] {
+            assert_eq!(field_type.name().parse::<FieldType>().unwrap(), field_type);
+        }
+        assert_eq!(FieldType::ObjectId.to_string(), "object_id");
+        let err = "float".parse::<FieldType>().unwrap_err();
+        assert_eq!(err.to_string(), "Unknown field type 'float'");
+    }
 
     #[test]
     fn test_get_string_field() {
@@ -251,6 +742,7 @@ mod tests {
                         Field::raw("(5:three)".as_bytes()),
                         Field::raw("400000".as_bytes()),
                     ],
+                    schema: None,
                 }],
             })),
             codec: None,
@@ -270,4 +762,147 @@ mod tests {
             _ => panic!("Not a struct"),
         }
     }
+
+    fn wallet_registry() -> SchemaRegistry {
+        let mut registry = SchemaRegistry::new();
+        registry
+            .add(
+                ContainerDef::new("MtxBalanceInfo", 330)
+                    .field(FieldDef::new("TemplateId", FieldType::UInt32))
+                    .field(FieldDef::new("ResourceId", FieldType::UInt32))
+                    .field(FieldDef::new("Amount", FieldType::Decimal)),
+            )
+            .unwrap();
+        registry
+            .add(
+                ContainerDef::new("MtxResponseWallet", 263)
+                    .field(FieldDef::new("Result", FieldType::UInt32))
+                    .field(FieldDef::new_struct("BalanceArray", "MtxBalanceInfo").list())
+                    .field(FieldDef::new("ExternalId", FieldType::String))
+                    .field(FieldDef::new_struct("Primary", "MtxBalanceInfo"))
+                    .field(FieldDef::new("Tags", FieldType::String).list())
+                    .field(FieldDef::new("Comment", FieldType::String)),
+            )
+            .unwrap();
+        registry
+    }
+
+    #[test]
+    fn test_container_field_by_name() {
+        let data = b"<1,6,0,263,5222,2>[2,{<1,3,1,330,5222,2>[200,4,1.5]},(6:555555),<1,3,1,330,5222,2>[202,6,]]";
+        let mut containers = CMDC_CODEC
+            .decode_with_schema(data, &wallet_registry())
+            .unwrap();
+        let container = &mut containers.containers[0];
+
+        assert_eq!(container.field_index("ExternalId").unwrap(), 2);
+        assert_eq!(container.field("ExternalId").unwrap().data, b"(6:555555)");
+
+        let field = container.field_mut("Result").unwrap();
+        assert_eq!(field.decode_value().unwrap().unwrap().as_uint32(), Some(2));
+
+        let err = container.field("Tags").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Field 'Tags' is not present in container 'MtxResponseWallet'"
+        );
+        let err = container.field("Balance").unwrap_err();
+        assert!(matches!(err, Error::UnknownField(_)));
+        assert_eq!(
+            err.to_string(),
+            "Unknown field 'Balance' in container 'MtxResponseWallet'"
+        );
+    }
+
+    #[test]
+    fn test_container_get() {
+        let data = b"<1,6,0,263,5222,2>[2,{<1,3,1,330,5222,2>[200,4,1.5]},(6:555555),<1,3,1,330,5222,2>[202,6,]]";
+        let containers = CMDC_CODEC
+            .decode_with_schema(data, &wallet_registry())
+            .unwrap();
+        let container = &containers.containers[0];
+
+        assert_eq!(container.get::<u32>("Result").unwrap(), 2);
+        assert_eq!(container.get::<i64>("Result").unwrap(), 2);
+        assert_eq!(container.get::<String>("ExternalId").unwrap(), "555555");
+        assert_eq!(container.get::<Option<String>>("Comment").unwrap(), None);
+        assert!(container.get::<Vec<String>>("Tags").unwrap().is_empty());
+
+        let balances = container.get::<Vec<Containers>>("BalanceArray").unwrap();
+        assert_eq!(balances.len(), 1);
+        let balance = &balances[0].containers[0];
+        assert_eq!(balance.get::<u32>("TemplateId").unwrap(), 200);
+        assert_eq!(
+            balance
+                .get::<bigdecimal::BigDecimal>("Amount")
+                .unwrap()
+                .to_string(),
+            "1.5"
+        );
+
+        // Nested struct fields are reachable through the value
+        let primary = container.field("Primary").unwrap().value().unwrap();
+        assert_eq!(primary.get::<u32>("ResourceId").unwrap(), 6);
+        assert_eq!(
+            primary
+                .get::<Option<bigdecimal::BigDecimal>>("Amount")
+                .unwrap(),
+            None
+        );
+        assert_eq!(primary.field("TemplateId").unwrap().data, b"202");
+    }
+
+    #[test]
+    fn test_container_get_errors() {
+        let data = b"<1,6,0,263,5222,2>[2,,(6:555555),<1,3,1,330,5222,2>[202,6,]]";
+        let containers = CMDC_CODEC
+            .decode_with_schema(data, &wallet_registry())
+            .unwrap();
+        let container = &containers.containers[0];
+
+        let err = container.get::<String>("Result").unwrap_err();
+        assert!(matches!(err, Error::TypeMismatch(_)));
+        assert_eq!(
+            err.to_string(),
+            "Invalid field 'Result' in container 'MtxResponseWallet', Value type mismatch, expected String but found UInt32"
+        );
+        let err = container.get::<u8>("Comment").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid field 'Comment' in container 'MtxResponseWallet', value is null"
+        );
+        let err = container.get::<u32>("Amount").unwrap_err();
+        assert!(matches!(err, Error::UnknownField(_)));
+
+        let err = Value::UInt32(300).get::<u8>("Result").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Value type mismatch, expected Struct but found UInt32"
+        );
+        let err = u8::from_value(&Value::UInt32(300)).unwrap_err();
+        assert_eq!(err.to_string(), "Value UInt32(300) out of range for u8");
+
+        // Containers decoded without a schema only allow positional access
+        let containers = CMDC_CODEC.decode(data).unwrap();
+        let err = containers.containers[0].get::<u32>("Result").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "No schema attached to container with key 263"
+        );
+    }
+
+    #[test]
+    fn test_field_count() {
+        let containers = CMDC_CODEC.decode(b"<1,2,0,263,5222,2>[]").unwrap();
+        assert_eq!(containers.containers[0].field_count(), 0);
+        let containers = CMDC_CODEC.decode(b"<1,2,0,263,5222,2>[,]").unwrap();
+        assert_eq!(containers.containers[0].field_count(), 2);
+
+        let container = ContainerBuilder::new(263, 5222, 2)
+            .field(0, &7u32)
+            .unwrap()
+            .build()
+            .unwrap();
+        assert_eq!(container.field_count(), 1);
+    }
 }
@@ -0,0 +1,513 @@
+//! Shared parts of the codecs whose containers and fields are named from
+//! their definitions, JSON and XML. The containers are walked with the
+//! schema here, the codecs only read and write the tokens of their format.
+//!
+//! Fields keep the text of their value in the format and decode on demand,
+//! so values are encoded again by the same codec exactly as they were
+//! written.
+
+use crate::codec::field_value;
+use crate::codec::is_native;
+use crate::codec::Codec;
+use crate::error::Error;
+use crate::mdd::Container;
+use crate::mdd::Containers;
+use crate::mdd::Field;
+use crate::mdd::FieldType;
+use crate::mdd::Header;
+use crate::mdd::Value;
+use crate::schema::ContainerSchema;
+use crate::schema::FieldDef;
+use crate::schema::SchemaRegistry;
+use std::sync::Arc;
+
+/// Codec of the field values of a named format. Fields of any type but
+/// struct are decoded and encoded by it.
+pub(crate) trait ValueCodec: Codec + Sized + 'static {
+    fn decode_value<'a>(&self, field_type: &FieldType, data: &[u8]) -> Result<Value<'a>, Error>;
+
+    fn decode_list<'a>(
+        &self,
+        field_type: &FieldType,
+        data: &[u8],
+    ) -> Result<Vec<Option<Value<'a>>>, Error>;
+
+    fn encode_value(&self, field_type: &FieldType, value: &Value) -> Result<Vec<u8>, Error>;
+}
+
+/// `Codec::decode_field` of value codecs.
+pub(crate) fn decode_value_field<'a, C: ValueCodec>(
+    codec: &C,
+    field: &Field<'a>,
+) -> Result<Value<'a>, Error> {
+    if field.is_multi {
+        return Ok(Value::List(
+            codec.decode_list(&field.field_type, field.data)?,
+        ));
+    }
+    codec.decode_value(&field.field_type, field.data)
+}
+
+/// `Codec::encode_field` of value codecs, data in the format is kept.
+pub(crate) fn encode_value_field<C: ValueCodec>(
+    codec: &C,
+    field: &Field,
+) -> Result<Vec<u8>, Error> {
+    if field.is_null {
+        return Ok(vec![]);
+    }
+    if !field.data.is_empty() && is_native(codec, field) {
+        return Ok(field.data.to_vec());
+    }
+    let value = field_value(codec, field)?;
+    codec.encode_value(&field.field_type, &value)
+}
+
+/// Codec of containers named from their definitions. Decoded containers
+/// get the layout of the versions of the codec.
+pub(crate) trait NamedCodec: Codec + Sized {
+    type Values: ValueCodec;
+
+    /// Tokens of a list of structs: start, element separator, empty element
+    /// and end.
+    const LIST: [&'static [u8]; 4];
+
+    fn values(&self) -> &'static Self::Values;
+    fn registry(&self) -> &SchemaRegistry;
+    fn versions(&self) -> (u16, u16);
+
+    /// Start a container, with the depth of its header when it is not the
+    /// nesting depth of the container.
+    fn start_container(&self, buffer: &mut Vec<u8>, name: &str, depth: Option<i8>);
+    fn end_container(&self, buffer: &mut Vec<u8>, name: &str);
+    fn start_field(&self, buffer: &mut Vec<u8>, name: &str);
+    fn end_field(&self, buffer: &mut Vec<u8>, name: &str);
+
+    /// Encode containers nested `depth` deep as at the top level, a single
+    /// container on its own and others wrapped. List elements holding
+    /// several containers, concatenated in cMDC, are written this way.
+    fn encode_containers_at(
+        &self,
+        buffer: &mut Vec<u8>,
+        containers: &Containers,
+        depth: i8,
+    ) -> Result<(), Error>;
+
+    /// Encode the containers of a struct field that is not a list.
+    fn encode_struct(
+        &self,
+        buffer: &mut Vec<u8>,
+        containers: &Containers,
+        depth: i8,
+    ) -> Result<(), Error>;
+
+    /// Definition of the container `name` at the versions of the codec,
+    /// which must be the `declared` container or derived from it.
+    fn lookup(&self, name: &str, declared: Option<&str>) -> Result<Arc<ContainerSchema>, Error> {
+        let registry = self.registry();
+        let def = registry
+            .get_by_name(name)
+            .ok_or_else(|| Error::SchemaError(format!("Unknown container '{}'", name)))?;
+        let (schema_version, ext_version) = self.versions();
+        let schema = registry.lookup(def.key, schema_version, ext_version)?;
+        if let Some(declared) = declared {
+            if !schema.is_a(declared) {
+                return Err(Error::SchemaError(format!(
+                    "Container '{}' is not a '{}'",
+                    schema.name, declared
+                )));
+            }
+        }
+        Ok(schema)
+    }
+
+    /// Fields are named from the attached schema, or the definition matching
+    /// the header if there is none. The container is nested `depth` deep.
+    fn encode_container(
+        &self,
+        buffer: &mut Vec<u8>,
+        container: &Container,
+        depth: i8,
+    ) -> Result<(), Error> {
+        let schema = match &container.schema {
+            Some(schema) => schema.clone(),
+            None => self.registry().lookup_header(&container.header)?,
+        };
+        let header_depth = container.header.depth;
+        self.start_container(
+            buffer,
+            &schema.name,
+            (header_depth != depth).then_some(header_depth),
+        );
+        for (idx, field) in container.fields.iter().enumerate() {
+            if field.is_null || (field.data.is_empty() && field.value.is_none()) {
+                continue;
+            }
+            let def = schema.fields.get(idx).ok_or_else(|| {
+                Error::EncodeError(format!(
+                    "Field {} is not defined in container '{}'",
+                    idx, schema.name
+                ))
+            })?;
+            self.start_field(buffer, &def.name);
+            encode_field_value(self, buffer, field, def, depth).map_err(|e| {
+                Error::EncodeError(format!(
+                    "Invalid field '{}' in container '{}', {}",
+                    def.name, schema.name, e
+                ))
+            })?;
+            self.end_field(buffer, &def.name);
+        }
+        self.end_container(buffer, &schema.name);
+        Ok(())
+    }
+
+    /// Encode the containers of a struct field, or a list of them, nested
+    /// `depth` deep.
+    fn encode_struct_value(
+        &self,
+        buffer: &mut Vec<u8>,
+        value: &Value,
+        depth: i8,
+    ) -> Result<(), Error> {
+        let [start, separator, null, end] = Self::LIST;
+        match value {
+            Value::Struct(containers) => self.encode_struct(buffer, containers, depth),
+            Value::List(values) => {
+                buffer.extend_from_slice(start);
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        buffer.extend_from_slice(separator);
+                    }
+                    match value {
+                        Some(Value::Struct(containers)) => {
+                            self.encode_containers_at(buffer, containers, depth)?
+                        }
+                        Some(value) => return Err(struct_mismatch(value)),
+                        None => buffer.extend_from_slice(null),
+                    }
+                }
+                buffer.extend_from_slice(end);
+                Ok(())
+            }
+            value => Err(struct_mismatch(value)),
+        }
+    }
+
+    /// `Codec::encode_field` of the codec, struct fields are encoded with
+    /// their containers, nested in the container of the field.
+    fn encode_named_field(&self, field: &Field) -> Result<Vec<u8>, Error> {
+        if field.is_null || field.field_type != FieldType::Struct {
+            return self.values().encode_field(field);
+        }
+        if !field.data.is_empty() && is_native(self, field) {
+            return Ok(field.data.to_vec());
+        }
+        let value = field_value(self, field)?;
+        let mut buffer = Vec::new();
+        self.encode_struct_value(&mut buffer, &value, 1)?;
+        Ok(buffer)
+    }
+}
+
+/// A field of a container nested `depth` deep.
+fn encode_field_value<C: NamedCodec>(
+    codec: &C,
+    buffer: &mut Vec<u8>,
+    field: &Field,
+    def: &FieldDef,
+    depth: i8,
+) -> Result<(), Error> {
+    if !field.data.is_empty() && is_native(codec, field) {
+        buffer.extend_from_slice(field.data);
+        return Ok(());
+    }
+
+    // Fields decoded by another codec without a schema are typed here
+    let typed;
+    let field = match field.field_type {
+        FieldType::Unknown => {
+            typed = Field {
+                field_type: def.field_type.clone(),
+                is_multi: def.is_list,
+                ..field.clone()
+            };
+            &typed
+        }
+        _ => field,
+    };
+    let value = field_value(codec, field)?;
+    match def.is_struct() {
+        true => {
+            let depth = depth.checked_add(1).ok_or_else(|| {
+                Error::EncodeError(format!("Containers nested deeper than {}", i8::MAX))
+            })?;
+            codec.encode_struct_value(buffer, &value, depth)
+        }
+        false => {
+            buffer.extend_from_slice(&codec.values().encode_value(&def.field_type, &value)?);
+            Ok(())
+        }
+    }
+}
+
+fn struct_mismatch(value: &Value) -> Error {
+    Error::TypeMismatch(format!(
+        "Value type mismatch, expected Struct but found {}",
+        value.type_name()
+    ))
+}
+
+/// Fields of a container being decoded, given by name in any order.
+pub(crate) struct NamedFields<'a> {
+    schema: Arc<ContainerSchema>,
+    codec: &'static dyn Codec,
+    fields: Vec<Field<'a>>,
+    seen: Vec<bool>,
+}
+
+/// A field of `NamedFields` to set.
+pub(crate) struct NamedField<'f, 'a> {
+    pub(crate) def: &'f FieldDef,
+    schema: &'f str,
+    field: &'f mut Field<'a>,
+}
+
+impl<'a> NamedFields<'a> {
+    /// Null fields are left to `codec` to decode.
+    pub(crate) fn new(schema: Arc<ContainerSchema>, codec: &'static dyn Codec) -> Self {
+        NamedFields {
+            schema,
+            codec,
+            fields: vec![],
+            seen: vec![],
+        }
+    }
+
+    /// The field `name`, which is null until set and may only be given once.
+    pub(crate) fn field(&mut self, name: &str) -> Result<NamedField<'_, 'a>, Error> {
+        let schema = &self.schema;
+        let idx = schema.field_index(name).ok_or_else(|| {
+            Error::UnknownField(format!(
+                "Unknown field '{}' in container '{}'",
+                name, schema.name
+            ))
+        })?;
+        if self.fields.len() <= idx {
+            let codec = self.codec;
+            self.fields.resize_with(idx + 1, || Field {
+                codec: Some(codec),
+                ..crate::container::null_field(FieldType::Unknown)
+            });
+            self.seen.resize(idx + 1, false);
+        }
+        if std::mem::replace(&mut self.seen[idx], true) {
+            return Err(Error::DecodeError(format!(
+                "Duplicate field '{}' in container '{}'",
+                name, schema.name
+            )));
+        }
+        Ok(NamedField {
+            def: &schema.fields[idx],
+            schema: &schema.name,
+            field: &mut self.fields[idx],
+        })
+    }
+
+    /// The container of the fields. Trailing null fields are left out as in
+    /// cMDC, the header keeps the field count of the definition.
+    pub(crate) fn finish(self, depth: i8) -> Result<Container<'a>, Error> {
+        let NamedFields {
+            schema, mut fields, ..
+        } = self;
+        for (field, def) in fields.iter_mut().zip(schema.fields.iter()) {
+            field.field_type = def.field_type.clone();
+        }
+        let total_field = u8::try_from(schema.fields.len()).map_err(|_| {
+            Error::DecodeError(format!(
+                "Too many fields {} in container '{}'",
+                schema.fields.len(),
+                schema.name
+            ))
+        })?;
+
+        Ok(Container {
+            header: Header {
+                version: 1,
+                total_field,
+                depth,
+                key: schema.key,
+                schema_version: schema.schema_version,
+                ext_version: schema.ext_version,
+            },
+            fields,
+            schema: Some(schema),
+        })
+    }
+}
+
+impl<'a> NamedField<'_, 'a> {
+    /// Keep the text of the value in the format, to decode on demand.
+    pub(crate) fn set_data(self, data: &'a [u8]) {
+        self.field.data = data;
+        self.field.is_null = false;
+        self.field.is_multi = self.def.is_list;
+    }
+
+    /// Set the decoded value, such as the containers of a struct field.
+    pub(crate) fn set_value(self, value: Result<Value<'a>, Error>) -> Result<(), Error> {
+        let value = value.map_err(|e| {
+            Error::DecodeError(format!(
+                "Invalid field '{}' in container '{}', {}",
+                self.def.name, self.schema, e
+            ))
+        })?;
+        self.field.value = Some(value);
+        self.field.is_null = false;
+        self.field.is_multi = self.def.is_list;
+        self.field.is_container = self.def.is_struct();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::cmdc::CMDC_CODEC;
+    use crate::container::ContainerBuilder;
+    use crate::json::JsonCodec;
+    use crate::schema::ContainerDef;
+    use crate::xml::XmlCodec;
+
+    pub(crate) fn registry() -> SchemaRegistry {
+        let mut registry = SchemaRegistry::new();
+        registry
+            .add(
+                ContainerDef::new("MtxBalanceInfo", 330)
+                    .field(FieldDef::new("TemplateId", FieldType::UInt32))
+                    .field(FieldDef::new("Name", FieldType::String))
+                    .field(FieldDef::new("Amount", FieldType::Decimal))
+                    .field(FieldDef::new("Updated", FieldType::DateTime)),
+            )
+            .unwrap();
+        registry
+            .add(
+                ContainerDef::new("MtxResponseWallet", 263)
+                    .field(FieldDef::new("Result", FieldType::UInt32))
+                    .field(FieldDef::new_struct("BalanceArray", "MtxBalanceInfo").list())
+                    .field(FieldDef::new("ExternalId", FieldType::String))
+                    .field(FieldDef::new("Flags", FieldType::Bool).list())
+                    .field(FieldDef::new_struct("Balance", "MtxBalanceInfo")),
+            )
+            .unwrap();
+        registry
+    }
+
+    #[test]
+    fn test_cmdc_round_trip() {
+        let registry = registry();
+        let json = JsonCodec::new(&registry, 5222, 2);
+        let xml = XmlCodec::new(&registry, 5222, 2);
+        let data = b"<1,5,0,263,5222,2>[2,{<1,4,1,330,5222,2>[200,,17485760.0,2021-09-07T08:00:25.000000Z],<1,4,1,330,5222,2>[,(9:a <b> & c)]},(0:),{1,,0},<1,4,1,330,5222,2>[1,,0.000001]]<1,4,0,330,5222,2>[,(7:a \"b\" c)]";
+        let cases: [(&dyn Codec, &str); 2] = [
+            (
+                &json,
+                r#"[{"$":"MtxResponseWallet","Result":2,"BalanceArray":[{"$":"MtxBalanceInfo","TemplateId":200,"Amount":17485760.0,"Updated":"2021-09-07T08:00:25.000000Z"},{"$":"MtxBalanceInfo","Name":"a <b> & c"}],"ExternalId":"","Flags":[true,null,false],"Balance":{"$":"MtxBalanceInfo","TemplateId":1,"Amount":0.000001}},{"$":"MtxBalanceInfo","Name":"a \"b\" c"}]"#,
+            ),
+            (
+                &xml,
+                "<Containers><MtxResponseWallet><Result>2</Result><BalanceArray><MtxBalanceInfo><TemplateId>200</TemplateId><Amount>17485760.0</Amount><Updated>2021-09-07T08:00:25.000000Z</Updated></MtxBalanceInfo><MtxBalanceInfo><Name>a &lt;b&gt; &amp; c</Name></MtxBalanceInfo></BalanceArray><ExternalId></ExternalId><Flags><value>true</value><null/><value>false</value></Flags><Balance><MtxBalanceInfo><TemplateId>1</TemplateId><Amount>0.000001</Amount></MtxBalanceInfo></Balance></MtxResponseWallet><MtxBalanceInfo><Name>a \"b\" c</Name></MtxBalanceInfo></Containers>",
+            ),
+        ];
+        for (codec, expected) in cases {
+            // Untyped cMDC fields are typed from the definitions
+            let containers = CMDC_CODEC.decode(data).unwrap();
+            let encoded = codec.encode(&containers).unwrap();
+            assert_eq!(String::from_utf8_lossy(&encoded), expected);
+
+            let decoded = codec.decode(&encoded).unwrap();
+            assert_eq!(CMDC_CODEC.encode(&decoded).unwrap(), data);
+        }
+    }
+
+    #[test]
+    fn test_cmdc_round_trip_concatenated() {
+        let registry = registry();
+        let json = JsonCodec::new(&registry, 5222, 2);
+        let xml = XmlCodec::new(&registry, 5222, 2);
+        // List elements of several containers, and a header depth that is
+        // not the nesting depth
+        let data = b"<1,5,0,263,5222,2>[,{<1,4,1,330,5222,2>[1]<1,4,1,330,5222,2>[2],,<1,4,1,330,5222,2>[3]<1,4,0,330,5222,2>[4]}]";
+        let cases: [(&dyn Codec, &str); 2] = [
+            (
+                &json,
+                r#"{"$":"MtxResponseWallet","BalanceArray":[[{"$":"MtxBalanceInfo","TemplateId":1},{"$":"MtxBalanceInfo","TemplateId":2}],null,[{"$":"MtxBalanceInfo","TemplateId":3},{"$":"MtxBalanceInfo","$depth":0,"TemplateId":4}]]}"#,
+            ),
+            (
+                &xml,
+                r#"<MtxResponseWallet><BalanceArray><Containers><MtxBalanceInfo><TemplateId>1</TemplateId></MtxBalanceInfo><MtxBalanceInfo><TemplateId>2</TemplateId></MtxBalanceInfo></Containers><null/><Containers><MtxBalanceInfo><TemplateId>3</TemplateId></MtxBalanceInfo><MtxBalanceInfo depth="0"><TemplateId>4</TemplateId></MtxBalanceInfo></Containers></BalanceArray></MtxResponseWallet>"#,
+            ),
+        ];
+        for (codec, expected) in cases {
+            let containers = CMDC_CODEC.decode(data).unwrap();
+            let encoded = codec.encode(&containers).unwrap();
+            assert_eq!(String::from_utf8_lossy(&encoded), expected);
+
+            let decoded = codec.decode(&encoded).unwrap();
+            assert_eq!(CMDC_CODEC.encode(&decoded).unwrap(), data);
+        }
+    }
+
+    #[test]
+    fn test_encode_built_container() {
+        let registry = registry();
+        let json = JsonCodec::new(&registry, 5222, 2);
+        let xml = XmlCodec::new(&registry, 5222, 2);
+        let container = ContainerBuilder::new(330, 5222, 2)
+            .field(1, &"Main & Co".to_string())
+            .unwrap()
+            .field(2, &"12.50".parse::<bigdecimal::BigDecimal>().unwrap())
+            .unwrap()
+            .build()
+            .unwrap();
+        let containers = Containers {
+            containers: vec![container],
+        };
+        let cases: [(&dyn Codec, &str); 2] = [
+            (
+                &json,
+                r#"{"$":"MtxBalanceInfo","Name":"Main & Co","Amount":12.50}"#,
+            ),
+            (
+                &xml,
+                "<MtxBalanceInfo><Name>Main &amp; Co</Name><Amount>12.50</Amount></MtxBalanceInfo>",
+            ),
+        ];
+        for (codec, expected) in cases {
+            let encoded = codec.encode(&containers).unwrap();
+            assert_eq!(String::from_utf8_lossy(&encoded), expected);
+        }
+    }
+
+    #[test]
+    fn test_encode_invalid() {
+        let registry = registry();
+        let json = JsonCodec::new(&registry, 5222, 2);
+        let xml = XmlCodec::new(&registry, 5222, 2);
+        for codec in [&json as &dyn Codec, &xml] {
+            let containers = CMDC_CODEC.decode(b"<1,5,0,330,5222,2>[1,,,,5]").unwrap();
+            let err = codec.encode(&containers).unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                "Field 4 is not defined in container 'MtxBalanceInfo'"
+            );
+
+            let containers = CMDC_CODEC.decode(b"<1,1,0,330,5222,2>[abc]").unwrap();
+            let err = codec.encode(&containers).unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                "Invalid field 'TemplateId' in container 'MtxBalanceInfo', invalid digit found in string"
+            );
+        }
+    }
+}
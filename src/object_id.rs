@@ -0,0 +1,142 @@
+use crate::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// A MATRIXX object identifier such as `0:1:5:277`.
+///
+/// Object IDs compare component by component, so they can be sorted and
+/// used as map keys. Components are plain decimal numbers without leading
+/// zeros, so an object id encodes back to the text it was parsed from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ObjectId {
+    components: [u64; 4],
+}
+
+impl ObjectId {
+    pub const fn new(a: u64, b: u64, c: u64, d: u64) -> Self {
+        ObjectId {
+            components: [a, b, c, d],
+        }
+    }
+
+    pub fn components(&self) -> [u64; 4] {
+        self.components
+    }
+}
+
+impl From<[u64; 4]> for ObjectId {
+    fn from(components: [u64; 4]) -> Self {
+        ObjectId { components }
+    }
+}
+
+impl FromStr for ObjectId {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || Error::DecodeError(format!("Invalid object id value '{}'", s));
+
+        let mut components = [0u64; 4];
+        let mut parts = s.split(':');
+        for component in components.iter_mut() {
+            let part = parts.next().ok_or_else(err)?;
+            if part.is_empty()
+                || !part.bytes().all(|c| c.is_ascii_digit())
+                || (part.len() > 1 && part.starts_with('0'))
+            {
+                return Err(err());
+            }
+            *component = part.parse::<u64>().map_err(|_| err())?;
+        }
+        if parts.next().is_some() {
+            return Err(err());
+        }
+
+        Ok(ObjectId { components })
+    }
+}
+
+impl fmt::Display for ObjectId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let [a, b, c, d] = self.components;
+        write!(f, "{}:{}:{}:{}", a, b, c, d)
+    }
+}
+
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use super::ObjectId;
+    use crate::cmdc::de::deserialize_str_newtype;
+    use crate::cmdc::ser::serialize_str_newtype;
+    use crate::cmdc::ser::OBJECT_ID;
+    use serde::Deserialize;
+    use serde::Deserializer;
+    use serde::Serialize;
+    use serde::Serializer;
+
+    /// Written as a string in other formats.
+    impl Serialize for ObjectId {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serialize_str_newtype(serializer, OBJECT_ID, self)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for ObjectId {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            deserialize_str_newtype(deserializer, OBJECT_ID)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_parse_object_id() {
+        let v: ObjectId = "0:1:5:277".parse().unwrap();
+        assert_eq!(v, ObjectId::new(0, 1, 5, 277));
+        assert_eq!(v.components(), [0, 1, 5, 277]);
+        assert_eq!(v.to_string(), "0:1:5:277");
+    }
+
+    #[test]
+    fn test_parse_invalid_object_id() {
+        for s in [
+            "",
+            "0:1:5",
+            "0:1:5:277:1",
+            "0:1::277",
+            "0:1:5:a",
+            "0:1:5:-1",
+            "0:1:5:+1",
+            "01:2:3:4",
+            "0:1:5:0277",
+            "00:1:5:277",
+        ] {
+            let err = s.parse::<ObjectId>().unwrap_err();
+            assert_eq!(err.to_string(), format!("Invalid object id value '{}'", s));
+        }
+    }
+
+    #[test]
+    fn test_object_id_ordering() {
+        let mut ids: Vec<ObjectId> = ["0:1:5:283", "0:1:5:277", "0:1:52:1", "0:1:5:279"]
+            .iter()
+            .map(|s| s.parse().unwrap())
+            .collect();
+        ids.sort();
+        let sorted: Vec<String> = ids.iter().map(|v| v.to_string()).collect();
+        assert_eq!(sorted, ["0:1:5:277", "0:1:5:279", "0:1:5:283", "0:1:52:1"]);
+    }
+
+    #[test]
+    fn test_object_id_map_key() {
+        let mut map = HashMap::new();
+        map.insert("0:1:5:277".parse::<ObjectId>().unwrap(), "Asset");
+        map.insert("0:1:5:279".parse::<ObjectId>().unwrap(), "Subscriber");
+        assert_eq!(map[&ObjectId::new(0, 1, 5, 277)], "Asset");
+        assert_eq!(map[&ObjectId::new(0, 1, 5, 279)], "Subscriber");
+    }
+}
@@ -0,0 +1,303 @@
+use super::ContainerSchema;
+use super::SchemaRegistry;
+use crate::error::Error;
+use crate::mdd::Container;
+use crate::mdd::Containers;
+use crate::mdd::Value;
+use std::sync::Arc;
+
+impl SchemaRegistry {
+    /// Type every field of the containers from their definitions.
+    ///
+    /// Each container is looked up by the key and versions in its header.
+    /// Struct fields are decoded and typed recursively, other fields keep
+    /// their raw data and decode on demand with `Field::decode_value()`.
+    pub fn apply(&self, containers: &mut Containers) -> Result<(), Error> {
+        for container in containers.containers.iter_mut() {
+            self.apply_container(container)?;
+        }
+        Ok(())
+    }
+
+    pub fn apply_container(&self, container: &mut Container) -> Result<(), Error> {
+        let schema = self.lookup_header(&container.header)?;
+        self.apply_schema(container, schema)
+    }
+
+    /// Type a container held by a struct field declared as `declared`.
+    ///
+    /// The nested header key picks the concrete definition, which must be
+    /// the declared container or derived from it. Containers with a key
+    /// missing from the registry are typed as the declared container.
+    fn apply_nested(&self, container: &mut Container, declared: &str) -> Result<(), Error> {
+        let header = &container.header;
+        let schema = match self.get(header.key) {
+            Some(_) => self.lookup_header(header)?,
+            None => {
+                let def = self.get_by_name(declared).ok_or_else(|| {
+                    Error::SchemaError(format!("Unknown struct type '{}'", declared))
+                })?;
+                self.lookup(def.key, header.schema_version, header.ext_version)?
+            }
+        };
+        if !schema.is_a(declared) {
+            return Err(Error::SchemaError(format!(
+                "Container '{}' is not a '{}'",
+                schema.name, declared
+            )));
+        }
+        self.apply_schema(container, schema)
+    }
+
+    fn apply_schema(
+        &self,
+        container: &mut Container,
+        schema: Arc<ContainerSchema>,
+    ) -> Result<(), Error> {
+        // Fields beyond the definition are left untyped
+        for (field, def) in container.fields.iter_mut().zip(schema.fields.iter()) {
+            field.field_type = def.field_type.clone();
+            if field.is_null {
+                continue;
+            }
+            field.is_multi = def.is_list;
+
+            if def.is_struct() {
+                // Fields built from values have no data to decode again
+                if !field.data.is_empty() {
+                    field.value = None;
+                }
+                let error = |e: Error| {
+                    Error::DecodeError(format!(
+                        "Invalid field '{}' in container '{}', {}",
+                        def.name, schema.name, e
+                    ))
+                };
+                field.decode_value().map_err(error)?;
+                if let Some(value) = field.value.as_mut() {
+                    self.apply_value(value, def.struct_name.as_deref())
+                        .map_err(error)?;
+                }
+            }
+        }
+        container.schema = Some(schema);
+        Ok(())
+    }
+
+    fn apply_value(&self, value: &mut Value, declared: Option<&str>) -> Result<(), Error> {
+        match value {
+            Value::Struct(containers) => {
+                for container in containers.containers.iter_mut() {
+                    match declared {
+                        Some(declared) => self.apply_nested(container, declared)?,
+                        None => self.apply_container(container)?,
+                    }
+                }
+                Ok(())
+            }
+            Value::List(values) => {
+                for value in values.iter_mut().flatten() {
+                    self.apply_value(value, declared)?;
+                }
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cmdc::CMDC_CODEC;
+    use crate::mdd::Container;
+    use crate::mdd::Containers;
+    use crate::mdd::FieldType;
+    use crate::schema::ContainerDef;
+    use crate::schema::FieldDef;
+    use crate::schema::SchemaRegistry;
+
+    fn registry() -> SchemaRegistry {
+        let mut registry = SchemaRegistry::new();
+        registry
+            .add(
+                ContainerDef::new("MtxBalanceInfo", 330)
+                    .field(FieldDef::new("TemplateId", FieldType::UInt32))
+                    .field(FieldDef::new("ResourceId", FieldType::UInt32))
+                    .field(FieldDef::new("ClassId", FieldType::UInt32)),
+            )
+            .unwrap();
+        registry
+            .add(
+                ContainerDef::new("MtxResponseWallet", 263)
+                    .field(FieldDef::new("Result", FieldType::UInt32))
+                    .field(FieldDef::new_struct("BalanceArray", "MtxBalanceInfo").list())
+                    .field(FieldDef::new("ExternalId", FieldType::String)),
+            )
+            .unwrap();
+        registry
+    }
+
+    #[test]
+    fn test_apply_schema() {
+        let data = b"<1,7,0,263,5222,2>[2,{<1,5,1,330,5222,2>[200,4,1],<1,5,1,330,5222,2>[202,6,2]},(6:555555)]";
+        let mut containers = CMDC_CODEC.decode_containers(data).unwrap();
+        registry().apply(&mut containers).unwrap();
+
+        let container = &mut containers.containers[0];
+        assert_eq!(container.fields[0].field_type, FieldType::UInt32);
+        assert_eq!(container.fields[1].field_type, FieldType::Struct);
+        assert!(container.fields[1].is_multi);
+        assert_eq!(container.fields[2].field_type, FieldType::String);
+
+        let result = container.fields[0].decode_value().unwrap().unwrap();
+        assert_eq!(result.as_uint32(), Some(2));
+        let external_id = container.fields[2].decode_value().unwrap().unwrap();
+        assert_eq!(external_id.as_string(), Some("555555"));
+
+        // Nested containers are typed as well
+        let balances = container.fields[1].value().unwrap().as_list().unwrap();
+        assert_eq!(balances.len(), 2);
+        let mut balance = balances[1].as_ref().unwrap().as_struct().unwrap().clone();
+        let balance = &mut balance.containers[0];
+        assert_eq!(balance.fields[0].field_type, FieldType::UInt32);
+        let template_id = balance.fields[0].decode_value().unwrap().unwrap();
+        assert_eq!(template_id.as_uint32(), Some(202));
+    }
+
+    #[test]
+    fn test_apply_schema_null_and_extra_fields() {
+        let data = b"<1,3,0,263,5222,2>[,,(6:555555),7]";
+        let mut containers = CMDC_CODEC.decode_containers(data).unwrap();
+        registry().apply(&mut containers).unwrap();
+
+        let container = &mut containers.containers[0];
+        assert_eq!(container.fields[1].field_type, FieldType::Struct);
+        assert!(container.fields[1].decode_value().unwrap().is_none());
+        assert_eq!(container.fields[3].field_type, FieldType::Unknown);
+    }
+
+    #[test]
+    fn test_apply_schema_unknown_key() {
+        let data = b"<1,29,0,208,5222,2>[]";
+        let mut containers = CMDC_CODEC.decode_containers(data).unwrap();
+        let err = registry().apply(&mut containers).unwrap_err();
+        assert_eq!(err.to_string(), "Unknown container key 208");
+    }
+
+    #[test]
+    fn test_apply_schema_invalid_struct() {
+        let data = b"<1,7,0,263,5222,2>[2,{<1,5,1,330,5222>[200,4,1]},(6:555555)]";
+        let mut containers = CMDC_CODEC.decode_containers(data).unwrap();
+        let err = registry().apply(&mut containers).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid field 'BalanceArray' in container 'MtxResponseWallet', Invalid cMDC header, 6 fields expected"
+        );
+    }
+
+    #[test]
+    fn test_apply_schema_inherited_fields() {
+        let mut registry = registry();
+        registry
+            .add(
+                ContainerDef::new("CustomBalanceInfo", 5001)
+                    .base("MtxBalanceInfo")
+                    .field(FieldDef::new("Region", FieldType::String)),
+            )
+            .unwrap();
+
+        let data = b"<1,7,0,263,5222,2>[2,{<1,4,1,5001,5222,2>[200,4,1,(2:EU)]},(6:555555)]";
+        let mut containers = CMDC_CODEC.decode_containers(data).unwrap();
+        registry.apply(&mut containers).unwrap();
+
+        let balances = containers.containers[0].fields[1].value().unwrap();
+        let balance = &balances.as_list().unwrap()[0];
+        let balance = &balance.as_ref().unwrap().as_struct().unwrap().containers[0];
+        let types: Vec<FieldType> = balance
+            .fields
+            .iter()
+            .map(|f| f.field_type.clone())
+            .collect();
+        assert_eq!(
+            types,
+            [
+                FieldType::UInt32,
+                FieldType::UInt32,
+                FieldType::UInt32,
+                FieldType::String
+            ]
+        );
+        assert!(balance.schema.as_ref().unwrap().is_a("MtxBalanceInfo"));
+        assert_eq!(balance.get::<String>("Region").unwrap(), "EU");
+        assert_eq!(balance.get::<u32>("TemplateId").unwrap(), 200);
+    }
+
+    fn polymorphic_registry() -> SchemaRegistry {
+        let mut registry = registry();
+        registry
+            .add(
+                ContainerDef::new("CustomBalanceInfo", 5001)
+                    .base("MtxBalanceInfo")
+                    .field(FieldDef::new("Region", FieldType::String)),
+            )
+            .unwrap();
+        registry
+            .add(
+                ContainerDef::new("MtxResultInfo", 452)
+                    .field(FieldDef::new("ResultCode", FieldType::UInt32)),
+            )
+            .unwrap();
+        registry
+    }
+
+    #[test]
+    fn test_apply_schema_polymorphic_struct() {
+        let data = b"<1,7,0,263,5222,2>[2,{<1,5,1,330,5222,2>[200,4,1],<1,4,1,5001,5222,2>[202,6,2,(2:EU)],<1,4,1,5002,5222,2>[204,8,3,(2:US)]},(6:555555)]";
+        let mut containers = CMDC_CODEC.decode_containers(data).unwrap();
+        polymorphic_registry().apply(&mut containers).unwrap();
+
+        let balances = containers.containers[0]
+            .get::<Vec<Containers>>("BalanceArray")
+            .unwrap();
+        let balances: Vec<&Container> = balances.iter().map(|b| &b.containers[0]).collect();
+
+        let names: Vec<Option<&str>> = balances.iter().map(|b| b.name()).collect();
+        // Unknown keys are typed as the declared container
+        assert_eq!(
+            names,
+            [
+                Some("MtxBalanceInfo"),
+                Some("CustomBalanceInfo"),
+                Some("MtxBalanceInfo")
+            ]
+        );
+        assert!(balances.iter().all(|b| b.is_a("MtxBalanceInfo")));
+        assert!(!balances[0].is_a("CustomBalanceInfo"));
+
+        for balance in balances {
+            match balance.name() {
+                Some("CustomBalanceInfo") => {
+                    assert_eq!(balance.get::<String>("Region").unwrap(), "EU");
+                }
+                _ => {
+                    assert!(balance.get::<u32>("TemplateId").unwrap() > 0);
+                    if let Some(region) = balance.fields.get(3) {
+                        assert_eq!(region.field_type, FieldType::Unknown);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_apply_schema_struct_type_mismatch() {
+        let data =
+            b"<1,7,0,263,5222,2>[2,{<1,5,1,330,5222,2>[200,4,1],<1,1,1,452,5222,2>[1]},(6:555555)]";
+        let mut containers = CMDC_CODEC.decode_containers(data).unwrap();
+        let err = polymorphic_registry().apply(&mut containers).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid field 'BalanceArray' in container 'MtxResponseWallet', Container 'MtxResultInfo' is not a 'MtxBalanceInfo'"
+        );
+    }
+}
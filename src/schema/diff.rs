@@ -0,0 +1,431 @@
+use super::ContainerSchema;
+use super::SchemaRegistry;
+use crate::error::Error;
+use crate::mdd::FieldType;
+use std::fmt;
+use std::sync::Arc;
+
+/// A difference between two sets of container definitions.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Change {
+    /// Container name, followed by the field name for field changes, such
+    /// as `MtxBalanceInfo.TemplateId`.
+    pub path: String,
+    pub kind: ChangeKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChangeKind {
+    AddedContainer,
+    RemovedContainer,
+    KeyChanged {
+        from: i32,
+        to: i32,
+    },
+    BaseChanged {
+        from: Option<String>,
+        to: Option<String>,
+    },
+    ExtensionChanged {
+        from: bool,
+        to: bool,
+    },
+    /// A field added at the given position.
+    AddedField(usize),
+    RemovedField,
+    /// A field at another position, fields of derived containers move when
+    /// fields are added to or removed from their base containers.
+    MovedField {
+        from: usize,
+        to: usize,
+    },
+    TypeChanged {
+        from: FieldType,
+        to: FieldType,
+    },
+    ListChanged {
+        from: bool,
+        to: bool,
+    },
+    StructChanged {
+        from: Option<String>,
+        to: Option<String>,
+    },
+    IntroducedInChanged {
+        from: u16,
+        to: u16,
+    },
+    RequiredChanged {
+        from: bool,
+        to: bool,
+    },
+}
+
+impl Change {
+    /// Whether messages written with the old definitions may fail to decode,
+    /// or decode differently, with the new ones.
+    pub fn is_breaking(&self) -> bool {
+        match &self.kind {
+            ChangeKind::AddedContainer => false,
+            ChangeKind::AddedField(_) => false,
+            ChangeKind::TypeChanged { from, to } => !widens(from, to),
+            ChangeKind::RequiredChanged { to, .. } => *to,
+            _ => true,
+        }
+    }
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let class = match self.is_breaking() {
+            true => "breaking",
+            false => "compatible",
+        };
+        write!(f, "{}: {}: {}", class, self.path, self.kind)
+    }
+}
+
+impl fmt::Display for ChangeKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fn name(name: &Option<String>) -> &str {
+            name.as_deref().unwrap_or("none")
+        }
+        match self {
+            ChangeKind::AddedContainer => write!(f, "container added"),
+            ChangeKind::RemovedContainer => write!(f, "container removed"),
+            ChangeKind::KeyChanged { from, to } => {
+                write!(f, "key changed from {} to {}", from, to)
+            }
+            ChangeKind::BaseChanged { from, to } => {
+                write!(f, "base changed from {} to {}", name(from), name(to))
+            }
+            ChangeKind::ExtensionChanged { from, to } => {
+                write!(f, "extension changed from {} to {}", from, to)
+            }
+            ChangeKind::AddedField(idx) => write!(f, "field added at position {}", idx),
+            ChangeKind::RemovedField => write!(f, "field removed"),
+            ChangeKind::MovedField { from, to } => {
+                write!(f, "field moved from position {} to {}", from, to)
+            }
+            ChangeKind::TypeChanged { from, to } => {
+                write!(f, "type changed from {} to {}", from.name(), to.name())
+            }
+            ChangeKind::ListChanged { from, to } => {
+                write!(f, "list changed from {} to {}", from, to)
+            }
+            ChangeKind::StructChanged { from, to } => {
+                write!(f, "struct changed from {} to {}", name(from), name(to))
+            }
+            ChangeKind::IntroducedInChanged { from, to } => {
+                write!(f, "version introduced changed from {} to {}", from, to)
+            }
+            ChangeKind::RequiredChanged { from, to } => {
+                write!(f, "required changed from {} to {}", from, to)
+            }
+        }
+    }
+}
+
+/// Whether every value of `from` reads as the same value of `to`.
+fn widens(from: &FieldType, to: &FieldType) -> bool {
+    use FieldType::*;
+    let signed = |t: &FieldType| match t {
+        Int8 => Some(8),
+        Int16 => Some(16),
+        Int32 => Some(32),
+        Int64 => Some(64),
+        _ => None,
+    };
+    let unsigned = |t: &FieldType| match t {
+        UInt8 => Some(8),
+        UInt16 => Some(16),
+        UInt32 => Some(32),
+        UInt64 => Some(64),
+        _ => None,
+    };
+    match (signed(from), unsigned(from)) {
+        (Some(bits), _) => signed(to).is_some_and(|b| b > bits) || *to == Decimal,
+        (_, Some(bits)) => {
+            unsigned(to).is_some_and(|b| b > bits)
+                || signed(to).is_some_and(|b| b > bits)
+                || *to == Decimal
+        }
+        _ => false,
+    }
+}
+
+impl SchemaRegistry {
+    /// Compare these definitions with a newer set, matching containers and
+    /// fields by name.
+    ///
+    /// Fields are compared in the layout of the latest version, with the
+    /// fields of base containers first, so a change to a base container is
+    /// reported for every container derived from it.
+    pub fn diff(&self, new: &SchemaRegistry) -> Result<Vec<Change>, Error> {
+        let mut changes = vec![];
+        let mut change = |path: &str, kind| {
+            changes.push(Change {
+                path: path.to_string(),
+                kind,
+            })
+        };
+
+        for old_def in self.containers() {
+            let path = old_def.name.as_str();
+            let new_def = match new.get_by_name(&old_def.name) {
+                Some(def) => def,
+                None => {
+                    change(path, ChangeKind::RemovedContainer);
+                    continue;
+                }
+            };
+            if old_def.key != new_def.key {
+                change(
+                    path,
+                    ChangeKind::KeyChanged {
+                        from: old_def.key,
+                        to: new_def.key,
+                    },
+                );
+            }
+            if old_def.base != new_def.base {
+                change(
+                    path,
+                    ChangeKind::BaseChanged {
+                        from: old_def.base.clone(),
+                        to: new_def.base.clone(),
+                    },
+                );
+            }
+            if old_def.is_extension != new_def.is_extension {
+                change(
+                    path,
+                    ChangeKind::ExtensionChanged {
+                        from: old_def.is_extension,
+                        to: new_def.is_extension,
+                    },
+                );
+            }
+
+            let old = latest(self, old_def.key)?;
+            let new = latest(new, new_def.key)?;
+            for (old_idx, old_field) in old.fields.iter().enumerate() {
+                let path = format!("{}.{}", path, old_field.name);
+                let new_idx = match new.field_index(&old_field.name) {
+                    Some(idx) => idx,
+                    None => {
+                        change(&path, ChangeKind::RemovedField);
+                        continue;
+                    }
+                };
+                let new_field = &new.fields[new_idx];
+                if old_idx != new_idx {
+                    change(
+                        &path,
+                        ChangeKind::MovedField {
+                            from: old_idx,
+                            to: new_idx,
+                        },
+                    );
+                }
+                if old_field.field_type != new_field.field_type {
+                    change(
+                        &path,
+                        ChangeKind::TypeChanged {
+                            from: old_field.field_type.clone(),
+                            to: new_field.field_type.clone(),
+                        },
+                    );
+                }
+                if old_field.is_list != new_field.is_list {
+                    change(
+                        &path,
+                        ChangeKind::ListChanged {
+                            from: old_field.is_list,
+                            to: new_field.is_list,
+                        },
+                    );
+                }
+                if old_field.struct_name != new_field.struct_name && new_field.is_struct() {
+                    change(
+                        &path,
+                        ChangeKind::StructChanged {
+                            from: old_field.struct_name.clone(),
+                            to: new_field.struct_name.clone(),
+                        },
+                    );
+                }
+                if old_field.introduced_in != new_field.introduced_in {
+                    change(
+                        &path,
+                        ChangeKind::IntroducedInChanged {
+                            from: old_field.introduced_in,
+                            to: new_field.introduced_in,
+                        },
+                    );
+                }
+                if old_field.is_required != new_field.is_required {
+                    change(
+                        &path,
+                        ChangeKind::RequiredChanged {
+                            from: old_field.is_required,
+                            to: new_field.is_required,
+                        },
+                    );
+                }
+            }
+            for (idx, new_field) in new.fields.iter().enumerate() {
+                if old.field_index(&new_field.name).is_none() {
+                    let path = format!("{}.{}", path, new_field.name);
+                    change(&path, ChangeKind::AddedField(idx));
+                }
+            }
+        }
+
+        for new_def in new.containers() {
+            if self.get_by_name(&new_def.name).is_none() {
+                change(&new_def.name, ChangeKind::AddedContainer);
+            }
+        }
+        Ok(changes)
+    }
+}
+
+/// Layout of a container with the fields of every version.
+fn latest(registry: &SchemaRegistry, key: i32) -> Result<Arc<ContainerSchema>, Error> {
+    registry.lookup(key, u16::MAX, u16::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::ContainerDef;
+    use crate::schema::FieldDef;
+
+    fn registry(containers: Vec<ContainerDef>) -> SchemaRegistry {
+        let mut registry = SchemaRegistry::new();
+        for container in containers {
+            registry.add(container).unwrap();
+        }
+        registry.check().unwrap();
+        registry
+    }
+
+    fn balance_info() -> ContainerDef {
+        ContainerDef::new("MtxBalanceInfo", 330)
+            .field(FieldDef::new("TemplateId", FieldType::UInt32))
+            .field(FieldDef::new("ResourceId", FieldType::UInt16))
+            .field(FieldDef::new("Name", FieldType::String))
+    }
+
+    #[test]
+    fn test_diff_compatible() {
+        let old = registry(vec![balance_info()]);
+        let new = registry(vec![
+            balance_info().field(FieldDef::new("ClassId", FieldType::UInt32).introduced_in(5300)),
+            ContainerDef::new("MtxThreshold", 331),
+        ]);
+        assert!(old.diff(&old).unwrap().is_empty());
+
+        let changes = old.diff(&new).unwrap();
+        let changes: Vec<String> = changes.iter().map(|c| c.to_string()).collect();
+        assert_eq!(
+            changes,
+            [
+                "compatible: MtxBalanceInfo.ClassId: field added at position 3",
+                "compatible: MtxThreshold: container added",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_fields() {
+        let old = registry(vec![
+            balance_info(),
+            ContainerDef::new("MtxThreshold", 331)
+                .field(FieldDef::new("Amount", FieldType::Decimal))
+                .field(FieldDef::new_struct("Balance", "MtxBalanceInfo"))
+                .field(FieldDef::new("Flags", FieldType::UInt8).list()),
+        ]);
+        let new = registry(vec![
+            ContainerDef::new("MtxBalanceInfo", 330)
+                .field(FieldDef::new("ResourceId", FieldType::UInt32).required())
+                .field(FieldDef::new("TemplateId", FieldType::String))
+                .field(FieldDef::new("Name", FieldType::String).introduced_in(2)),
+            ContainerDef::new("MtxThreshold", 1331)
+                .field(FieldDef::new("Amount", FieldType::Int64))
+                .field(FieldDef::new_struct("Balance", "MtxThreshold"))
+                .field(FieldDef::new("Flags", FieldType::UInt8)),
+        ]);
+
+        let changes = old.diff(&new).unwrap();
+        let changes: Vec<String> = changes.iter().map(|c| c.to_string()).collect();
+        assert_eq!(
+            changes,
+            [
+                "breaking: MtxBalanceInfo.TemplateId: field moved from position 0 to 1",
+                "breaking: MtxBalanceInfo.TemplateId: type changed from uint32 to string",
+                "breaking: MtxBalanceInfo.ResourceId: field moved from position 1 to 0",
+                "compatible: MtxBalanceInfo.ResourceId: type changed from uint16 to uint32",
+                "breaking: MtxBalanceInfo.ResourceId: required changed from false to true",
+                "breaking: MtxBalanceInfo.Name: version introduced changed from 0 to 2",
+                "breaking: MtxThreshold: key changed from 331 to 1331",
+                "breaking: MtxThreshold.Amount: type changed from decimal to int64",
+                "breaking: MtxThreshold.Balance: struct changed from MtxBalanceInfo to MtxThreshold",
+                "breaking: MtxThreshold.Flags: list changed from true to false",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_base_containers() {
+        let base = ContainerDef::new("MtxBase", 1).field(FieldDef::new("Id", FieldType::UInt32));
+        let old = registry(vec![
+            base.clone(),
+            ContainerDef::new("MtxDerived", 2)
+                .base("MtxBase")
+                .field(FieldDef::new("Name", FieldType::String)),
+            ContainerDef::new("MtxOther", 3),
+        ]);
+        let new = registry(vec![
+            base.field(FieldDef::new("Flags", FieldType::UInt32)),
+            ContainerDef::new("MtxDerived", 2)
+                .base("MtxBase")
+                .extension()
+                .field(FieldDef::new("Name", FieldType::String)),
+        ]);
+
+        let changes = old.diff(&new).unwrap();
+        let breaking: Vec<String> = changes
+            .iter()
+            .filter(|c| c.is_breaking())
+            .map(|c| c.to_string())
+            .collect();
+        assert_eq!(
+            breaking,
+            [
+                "breaking: MtxDerived: extension changed from false to true",
+                "breaking: MtxDerived.Name: field moved from position 1 to 2",
+                "breaking: MtxOther: container removed",
+            ]
+        );
+        assert_eq!(
+            changes[0],
+            Change {
+                path: "MtxBase.Flags".into(),
+                kind: ChangeKind::AddedField(1),
+            }
+        );
+    }
+
+    #[test]
+    fn test_widens() {
+        assert!(widens(&FieldType::UInt8, &FieldType::UInt16));
+        assert!(widens(&FieldType::UInt32, &FieldType::Int64));
+        assert!(widens(&FieldType::Int32, &FieldType::Decimal));
+        assert!(!widens(&FieldType::UInt32, &FieldType::Int32));
+        assert!(!widens(&FieldType::Int8, &FieldType::UInt64));
+        assert!(!widens(&FieldType::Decimal, &FieldType::Int64));
+        assert!(!widens(&FieldType::String, &FieldType::String));
+    }
+}
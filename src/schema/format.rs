@@ -0,0 +1,440 @@
+//! JSON and TOML container definition formats, handy for writing small
+//! schemas by hand.
+//!
+//! Containers are keyed by their `Header::key`, fields are listed in wire
+//! order.
+//!
+//! ```toml
+//! [containers.330]
+//! name = "MtxBalanceInfo"
+//! base = "MtxBase"          # optional
+//! extension = false         # optional
+//! fields = [
+//!     { name = "TemplateId", type = "uint32", required = true },
+//!     { name = "ThresholdArray", struct = "MtxThreshold", list = true },
+//!     { name = "ClassId", type = "uint32", since = 5200 },
+//! ]
+//! ```
+//!
+//! ```json
+//! {
+//!   "containers": {
+//!     "330": {
+//!       "name": "MtxBalanceInfo",
+//!       "fields": [
+//!         { "name": "TemplateId", "type": "uint32", "required": true },
+//!         { "name": "ThresholdArray", "struct": "MtxThreshold", "list": true },
+//!         { "name": "ClassId", "type": "uint32", "since": 5200 }
+//!       ]
+//!     }
+//!   }
+//! }
+//! ```
+//!
+//! `type` takes the `FieldType` names and may be left out when `struct`
+//! names the container a field holds. `since` is the version the field was
+//! introduced in, `required` fields must not be null.
+
+use super::ContainerDef;
+use super::FieldDef;
+use super::SchemaRegistry;
+use crate::error::Error;
+use crate::mdd::FieldType;
+use serde::Deserialize;
+use serde::Deserializer;
+use serde::Serialize;
+use serde::Serializer;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SchemaFile {
+    #[serde(default)]
+    containers: BTreeMap<Key, ContainerEntry>,
+}
+
+/// Container key, written as a string since JSON and TOML keys are strings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct Key(i32);
+
+impl Serialize for Key {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for Key {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let key = String::deserialize(deserializer)?;
+        key.parse::<i32>()
+            .map(Key)
+            .map_err(|_| serde::de::Error::custom(format!("invalid container key '{}'", key)))
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ContainerEntry {
+    name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    base: Option<String>,
+    #[serde(default, skip_serializing_if = "is_false")]
+    extension: bool,
+    #[serde(default)]
+    fields: Vec<FieldEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FieldEntry {
+    name: String,
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    field_type: Option<String>,
+    #[serde(rename = "struct", default, skip_serializing_if = "Option::is_none")]
+    struct_name: Option<String>,
+    #[serde(default, skip_serializing_if = "is_false")]
+    list: bool,
+    #[serde(default, skip_serializing_if = "is_zero")]
+    since: u16,
+    #[serde(default, skip_serializing_if = "is_false")]
+    required: bool,
+}
+
+fn is_false(v: &bool) -> bool {
+    !v
+}
+
+fn is_zero(v: &u16) -> bool {
+    *v == 0
+}
+
+impl SchemaRegistry {
+    fn load_schema_file(&mut self, file: SchemaFile) -> Result<(), Error> {
+        for (Key(key), entry) in file.containers {
+            let container = entry.into_def(key)?;
+            self.add(container)
+                .map_err(|e| Error::SchemaError(format!("Invalid schema, {}", e)))?;
+        }
+        Ok(())
+    }
+
+    fn to_file(&self) -> SchemaFile {
+        let containers = self
+            .containers()
+            .into_iter()
+            .map(|c| (Key(c.key), ContainerEntry::from_def(c)))
+            .collect();
+        SchemaFile { containers }
+    }
+}
+
+#[cfg(feature = "json")]
+impl SchemaRegistry {
+    /// Load container definitions from JSON files. Struct types and base
+    /// containers may refer to containers defined in any of the files.
+    pub fn from_json_files<P: AsRef<Path>>(paths: &[P]) -> Result<SchemaRegistry, Error> {
+        let mut registry = SchemaRegistry::new();
+        for path in paths {
+            registry.load_json_file(path)?;
+        }
+        registry.check()?;
+        Ok(registry)
+    }
+
+    pub fn load_json_file<P: AsRef<Path>>(&mut self, path: P) -> Result<(), Error> {
+        let path = path.as_ref();
+        let json = std::fs::read_to_string(path)?;
+        self.load_json(&json)
+            .map_err(|e| Error::SchemaError(format!("{}: {}", path.display(), e)))
+    }
+
+    /// Add the containers defined in a JSON document.
+    pub fn load_json(&mut self, json: &str) -> Result<(), Error> {
+        let file = serde_json::from_str(json)
+            .map_err(|e| Error::SchemaError(format!("Invalid JSON, {}", e)))?;
+        self.load_schema_file(file)
+    }
+
+    /// Write every container definition as a JSON document.
+    pub fn to_json(&self) -> Result<String, Error> {
+        serde_json::to_string_pretty(&self.to_file())
+            .map_err(|e| Error::SchemaError(format!("Invalid JSON, {}", e)))
+    }
+}
+
+#[cfg(feature = "toml")]
+impl SchemaRegistry {
+    /// Load container definitions from TOML files. Struct types and base
+    /// containers may refer to containers defined in any of the files.
+    pub fn from_toml_files<P: AsRef<Path>>(paths: &[P]) -> Result<SchemaRegistry, Error> {
+        let mut registry = SchemaRegistry::new();
+        for path in paths {
+            registry.load_toml_file(path)?;
+        }
+        registry.check()?;
+        Ok(registry)
+    }
+
+    pub fn load_toml_file<P: AsRef<Path>>(&mut self, path: P) -> Result<(), Error> {
+        let path = path.as_ref();
+        let toml = std::fs::read_to_string(path)?;
+        self.load_toml(&toml)
+            .map_err(|e| Error::SchemaError(format!("{}: {}", path.display(), e)))
+    }
+
+    /// Add the containers defined in a TOML document.
+    pub fn load_toml(&mut self, toml: &str) -> Result<(), Error> {
+        let file = toml::from_str(toml)
+            .map_err(|e| Error::SchemaError(format!("Invalid TOML, {}", e.message())))?;
+        self.load_schema_file(file)
+    }
+
+    /// Write every container definition as a TOML document.
+    pub fn to_toml(&self) -> Result<String, Error> {
+        toml::to_string_pretty(&self.to_file())
+            .map_err(|e| Error::SchemaError(format!("Invalid TOML, {}", e)))
+    }
+}
+
+impl ContainerEntry {
+    fn into_def(self, key: i32) -> Result<ContainerDef, Error> {
+        let mut container = ContainerDef::new(&self.name, key);
+        container.base = self.base;
+        container.is_extension = self.extension;
+        for field in self.fields {
+            let field = field.into_def(&container.name)?;
+            container.fields.push(field);
+        }
+        Ok(container)
+    }
+
+    fn from_def(container: &ContainerDef) -> Self {
+        ContainerEntry {
+            name: container.name.clone(),
+            base: container.base.clone(),
+            extension: container.is_extension,
+            fields: container.fields.iter().map(FieldEntry::from_def).collect(),
+        }
+    }
+}
+
+impl FieldEntry {
+    fn into_def(self, container: &str) -> Result<FieldDef, Error> {
+        let error = |msg: String| Error::SchemaError(format!("Invalid schema, {}", msg));
+
+        let field_type = match (&self.field_type, &self.struct_name) {
+            (None, Some(_)) => FieldType::Struct,
+            (None, None) => {
+                return Err(error(format!(
+                    "missing type for field '{}' in container '{}'",
+                    self.name, container
+                )))
+            }
+            (Some(field_type), _) => match field_type.parse::<FieldType>() {
+                Ok(FieldType::Unknown) | Err(_) => {
+                    return Err(error(format!(
+                        "unknown type '{}' for field '{}' in container '{}'",
+                        field_type, self.name, container
+                    )))
+                }
+                Ok(field_type) => field_type,
+            },
+        };
+
+        let mut field = FieldDef::new(&self.name, field_type);
+        field.is_list = self.list;
+        field.struct_name = self.struct_name;
+        field.introduced_in = self.since;
+        field.is_required = self.required;
+
+        match (field.is_struct(), &field.struct_name) {
+            (true, None) => Err(error(format!(
+                "missing struct for struct field '{}' in container '{}'",
+                field.name, container
+            ))),
+            (false, Some(_)) => Err(error(format!(
+                "struct is only allowed on struct fields, field '{}' in container '{}'",
+                field.name, container
+            ))),
+            _ => Ok(field),
+        }
+    }
+
+    fn from_def(field: &FieldDef) -> Self {
+        FieldEntry {
+            name: field.name.clone(),
+            field_type: match field.is_struct() {
+                true => None,
+                false => Some(field.field_type.name().to_string()),
+            },
+            struct_name: field.struct_name.clone(),
+            list: field.is_list,
+            since: field.introduced_in,
+            required: field.is_required,
+        }
+    }
+}
+
+#[cfg(all(test, feature = "json", feature = "toml"))]
+mod tests {
+    use super::*;
+    use crate::cmdc::CMDC_CODEC;
+    use crate::mdd::Containers;
+    use crate::mdd::Value;
+
+    const BENCH_TOML: &str = include_str!("../../tests/data/bench_schema.toml");
+    const BENCH_JSON: &str = include_str!("../../tests/data/bench_schema.json");
+
+    fn bench_registry() -> SchemaRegistry {
+        let mut registry = SchemaRegistry::new();
+        registry.load_toml(BENCH_TOML).unwrap();
+        registry.check().unwrap();
+        registry
+    }
+
+    fn decode_all(containers: &mut Containers) -> usize {
+        let mut count = 0;
+        for container in containers.containers.iter_mut() {
+            for field in container.fields.iter_mut() {
+                if field.is_null {
+                    continue;
+                }
+                assert_ne!(field.field_type, FieldType::Unknown);
+                field.decode_value().unwrap();
+                if let Some(value) = field.value.as_mut() {
+                    count += decode_nested(value);
+                }
+            }
+        }
+        count
+    }
+
+    fn decode_nested(value: &mut Value) -> usize {
+        match value {
+            Value::Struct(containers) => decode_all(containers),
+            Value::List(values) => values.iter_mut().flatten().map(decode_nested).sum(),
+            _ => 1,
+        }
+    }
+
+    #[test]
+    fn test_load_toml_and_json() {
+        let registry = bench_registry();
+        assert_eq!(registry.len(), 18);
+
+        let mut json = SchemaRegistry::new();
+        json.load_json(BENCH_JSON).unwrap();
+        json.check().unwrap();
+        assert_eq!(json.containers(), registry.containers());
+
+        let session = registry.get(-6).unwrap();
+        assert_eq!(session.name, "MtxSessionContext");
+        assert_eq!(session.fields[2].field_type, FieldType::UInt8);
+
+        let subscriber = registry.get_by_name("MtxResponseSubscriber").unwrap();
+        let statuses = &subscriber.fields[0];
+        assert_eq!(statuses.field_type, FieldType::UInt8);
+        assert!(statuses.is_list);
+        let offers = &subscriber.fields[2];
+        assert_eq!(offers.field_type, FieldType::Struct);
+        assert!(offers.is_list);
+        assert_eq!(offers.struct_name.as_deref(), Some("MtxPurchasedOfferInfo"));
+    }
+
+    #[test]
+    fn test_save_and_reload() {
+        let mut registry = bench_registry();
+        registry
+            .add(
+                ContainerDef::new("CustomBalanceInfo", 5001)
+                    .base("MtxBalanceInfo")
+                    .extension()
+                    .field(FieldDef::new("Region", FieldType::String).introduced_in(2))
+                    .field(FieldDef::new("Owner", FieldType::ObjectId).required()),
+            )
+            .unwrap();
+
+        let mut json = SchemaRegistry::new();
+        json.load_json(&registry.to_json().unwrap()).unwrap();
+        assert_eq!(json.containers(), registry.containers());
+
+        let mut toml = SchemaRegistry::new();
+        toml.load_toml(&registry.to_toml().unwrap()).unwrap();
+        assert_eq!(toml.containers(), registry.containers());
+    }
+
+    #[test]
+    fn test_decode_bench_samples() {
+        let registry = bench_registry();
+
+        let data = b"<1,8,0,-6,5222,2>[,,2,(5:AMF-1),(4:eMBB),(11:SouthWestUK),1]<1,1,0,-5,5222,2>[1000001]<1,7,0,263,5222,2>[2,{<1,5,1,330,5222,2>[4,200,1,17485760.0,17485824.0]},(6:555555),0,0.0,64.0,200]<1,11,0,626,5222,2>[{1,3,1},,{<1,17,1,624,5222,2>[17485824.0,200,1,(21:Data: Asset + Overage),0:1:5:277,(7:2000000),3,0,,,,,,,,0]},,{(13:HXS0:1:52:409)},{<1,8,1,1000,5222,2>[4,(18:Triple Play Bundle),1,0,,,,0]},{<1,5,1,1277,5222,2>[4,(17:999 - 1200TB Plan),1,<1,6,1,-11,5222,2>[800000.0,1200.0,300000.0,5000000000.0,100000.0,5000000000.0]<1,0,0,1257,5222,2>[]]},,{<1,3,1,1360,5222,2>[,(5:Usage),(5:Usage)]},{<1,2,1,627,5222,2>[(13:HXS0:1:52:408),1]<1,29,0,208,5222,2>[0:1:5:279,(7:1000001),0:1:5:283,,4,0:1:5:278,0:1:5:277,(7:2000000),,,,{<1,14,1,209,5222,2>[,1000,2,1,4,2021-09-07T08:00:25.000000Z,2021-10-07T08:00:25.000000Z,1,0.0,,,-1258291032.242187,,0]},{<1,12,1,567,5222,2>[17485824.0,200,0,0,1,0.0,,1,,1,0]},2021-09-09T16:37:19.000000Z,,(13:HXS0:1:52:409),,,,,1,,,0:1:5:281,,1,2]}]<1,29,0,208,5222,2>[0:1:5:279,(7:1000001),0:1:5:283,,0,0:1:5:280,0:1:5:279,(7:1000001),,,,,,2021-09-09T16:37:19.000000Z,0,(13:HXS0:1:52:408),,,,,1,,,0:1:5:281,,1,1,(26:00000000000000594134:00000)]";
+        let mut containers = CMDC_CODEC.decode_with_schema(data, &registry).unwrap();
+        assert_eq!(containers.containers.len(), 5);
+        assert_eq!(decode_all(&mut containers), 95);
+
+        let subscriber = &containers.containers[4];
+        assert_eq!(subscriber.header.key, 208);
+        let activation = subscriber.fields[13].value().unwrap();
+        assert_eq!(
+            activation.as_datetime().unwrap().to_string(),
+            "2021-09-09T16:37:19.000000Z"
+        );
+
+        let data = b"<1,8,0,-6,5222,2>[,,,(5:AMF-1),(4:eMBB),(11:SouthWestUK),1]<1,1,0,-5,5222,2>[1000001]<1,7,0,263,5222,2>[2,{<1,5,1,330,5222,2>[200,4,1],<1,5,1,330,5222,2>[202,6,2]},(6:555555)]<1,11,0,626,5222,2>[{1,3,1},,{<1,17,1,624,5222,2>[17485824.0,200,1,(21:Data: Asset + Overage),0:1:5:277,(7:2000000),3,0,,,,,,,,0]},0:1:5:144,{(13:HXS0:1:52:409)}]<1,29,0,208,5222,2>[]";
+        let mut containers = CMDC_CODEC.decode_with_schema(data, &registry).unwrap();
+        assert_eq!(containers.containers.len(), 5);
+        decode_all(&mut containers);
+
+        let subscriber = &containers.containers[3];
+        let session_ids = subscriber.fields[4].value().unwrap().as_list().unwrap();
+        assert_eq!(
+            session_ids[0].as_ref().unwrap().as_string(),
+            Some("HXS0:1:52:409")
+        );
+    }
+
+    #[test]
+    fn test_load_invalid_schema() {
+        let cases = [
+            (
+                r#"{"containers": {"x": {"name": "A"}}}"#,
+                "Invalid JSON, invalid container key 'x' at line 1 column 19",
+            ),
+            (
+                r#"{"containers": {"1": {"name": "A", "fields": [{"name": "B", "type": "float"}]}}}"#,
+                "Invalid schema, unknown type 'float' for field 'B' in container 'A'",
+            ),
+            (
+                r#"{"containers": {"1": {"name": "A", "fields": [{"name": "B"}]}}}"#,
+                "Invalid schema, missing type for field 'B' in container 'A'",
+            ),
+            (
+                r#"{"containers": {"1": {"name": "A", "fields": [{"name": "B", "type": "struct"}]}}}"#,
+                "Invalid schema, missing struct for struct field 'B' in container 'A'",
+            ),
+            (
+                r#"{"containers": {"1": {"name": "A", "fields": [{"name": "B", "type": "string", "struct": "A"}]}}}"#,
+                "Invalid schema, struct is only allowed on struct fields, field 'B' in container 'A'",
+            ),
+            (
+                r#"{"containers": {"1": {"name": "A"}, "2": {"name": "A"}}}"#,
+                "Invalid schema, Duplicate container name 'A'",
+            ),
+        ];
+
+        for (json, expected) in cases {
+            let mut registry = SchemaRegistry::new();
+            let err = registry.load_json(json).unwrap_err();
+            assert_eq!(err.to_string(), expected);
+        }
+
+        let mut registry = SchemaRegistry::new();
+        let err = registry
+            .load_toml("[containers.1]\nname = \"A\"\nkey = 1\n")
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("Invalid TOML, unknown field `key`"));
+    }
+}
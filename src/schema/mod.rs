@@ -0,0 +1,670 @@
+mod apply;
+mod diff;
+#[cfg(any(feature = "json", feature = "toml"))]
+mod format;
+mod transcode;
+mod validate;
+mod xml;
+
+pub use diff::Change;
+pub use diff::ChangeKind;
+pub use transcode::Loss;
+pub use transcode::LossKind;
+pub use validate::Violation;
+pub use validate::ViolationKind;
+
+use crate::error::Error;
+use crate::mdd::FieldType;
+use crate::mdd::Header;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::RwLock;
+
+/// Definition of a single field of a container.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldDef {
+    pub name: String,
+    /// Type of the field, or of each element when `is_list` is set.
+    pub field_type: FieldType,
+    pub is_list: bool,
+    /// Name of the container a `Struct` field holds.
+    pub struct_name: Option<String>,
+    /// Version the field was added in. Compared against the header
+    /// `ext_version` for extension containers and `schema_version` otherwise.
+    pub introduced_in: u16,
+    /// Required fields must not be null, see `SchemaRegistry::validate()`.
+    pub is_required: bool,
+}
+
+impl FieldDef {
+    pub fn new(name: &str, field_type: FieldType) -> Self {
+        FieldDef {
+            name: name.to_string(),
+            field_type,
+            is_list: false,
+            struct_name: None,
+            introduced_in: 0,
+            is_required: false,
+        }
+    }
+
+    /// A `Struct` field holding a container of the given name.
+    pub fn new_struct(name: &str, struct_name: &str) -> Self {
+        FieldDef {
+            struct_name: Some(struct_name.to_string()),
+            ..Self::new(name, FieldType::Struct)
+        }
+    }
+
+    pub fn list(mut self) -> Self {
+        self.is_list = true;
+        self
+    }
+
+    pub fn introduced_in(mut self, version: u16) -> Self {
+        self.introduced_in = version;
+        self
+    }
+
+    pub fn required(mut self) -> Self {
+        self.is_required = true;
+        self
+    }
+
+    pub fn is_struct(&self) -> bool {
+        self.field_type == FieldType::Struct
+    }
+}
+
+/// Definition of a container, with all its fields across every version.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContainerDef {
+    pub name: String,
+    pub key: i32,
+    /// Name of the container this one is derived from.
+    pub base: Option<String>,
+    /// Extension containers version their fields by `ext_version`.
+    pub is_extension: bool,
+    pub fields: Vec<FieldDef>,
+}
+
+impl ContainerDef {
+    pub fn new(name: &str, key: i32) -> Self {
+        ContainerDef {
+            name: name.to_string(),
+            key,
+            base: None,
+            is_extension: false,
+            fields: vec![],
+        }
+    }
+
+    pub fn base(mut self, base: &str) -> Self {
+        self.base = Some(base.to_string());
+        self
+    }
+
+    pub fn extension(mut self) -> Self {
+        self.is_extension = true;
+        self
+    }
+
+    pub fn field(mut self, field: FieldDef) -> Self {
+        self.fields.push(field);
+        self
+    }
+
+    fn version(&self, schema_version: u16, ext_version: u16) -> u16 {
+        if self.is_extension {
+            ext_version
+        } else {
+            schema_version
+        }
+    }
+}
+
+/// Layout of a container for one `(key, schema_version, ext_version)`,
+/// holding only the fields present in that version, in wire order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContainerSchema {
+    pub name: String,
+    pub key: i32,
+    pub schema_version: u16,
+    pub ext_version: u16,
+    /// Names of the base containers, nearest first.
+    pub bases: Vec<String>,
+    /// Fields of the base containers followed by the container's own.
+    pub fields: Vec<FieldDef>,
+    index: HashMap<String, usize>,
+}
+
+impl ContainerSchema {
+    /// Position of the named field in the container.
+    pub fn field_index(&self, name: &str) -> Option<usize> {
+        self.index.get(name).copied()
+    }
+
+    pub fn field(&self, name: &str) -> Option<&FieldDef> {
+        self.field_index(name).map(|idx| &self.fields[idx])
+    }
+
+    /// Whether this is the named container or derived from it.
+    pub fn is_a(&self, name: &str) -> bool {
+        self.name == name || self.bases.iter().any(|b| b == name)
+    }
+}
+
+/// Most layouts kept by [`SchemaRegistry::lookup`], the versions come from
+/// headers of untrusted data.
+const CACHE_CAPACITY: usize = 1024;
+
+/// Container definitions indexed by key and by name.
+#[derive(Debug, Default)]
+pub struct SchemaRegistry {
+    containers: HashMap<i32, ContainerDef>,
+    names: HashMap<String, i32>,
+    cache: RwLock<HashMap<(i32, u16, u16), Arc<ContainerSchema>>>,
+}
+
+impl SchemaRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, container: ContainerDef) -> Result<(), Error> {
+        if self.containers.contains_key(&container.key) {
+            return Err(Error::SchemaError(format!(
+                "Duplicate container key {}",
+                container.key
+            )));
+        }
+        if self.names.contains_key(&container.name) {
+            return Err(Error::SchemaError(format!(
+                "Duplicate container name '{}'",
+                container.name
+            )));
+        }
+
+        for (idx, field) in container.fields.iter().enumerate() {
+            if container.fields[..idx].iter().any(|f| f.name == field.name) {
+                return Err(Error::SchemaError(format!(
+                    "Duplicate field name '{}' in container '{}'",
+                    field.name, container.name
+                )));
+            }
+        }
+
+        self.names.insert(container.name.clone(), container.key);
+        self.containers.insert(container.key, container);
+        self.cache
+            .get_mut()
+            .unwrap_or_else(|e| e.into_inner())
+            .clear();
+        Ok(())
+    }
+
+    pub fn get(&self, key: i32) -> Option<&ContainerDef> {
+        self.containers.get(&key)
+    }
+
+    pub fn get_by_name(&self, name: &str) -> Option<&ContainerDef> {
+        self.names
+            .get(name)
+            .and_then(|key| self.containers.get(key))
+    }
+
+    /// All container definitions, ordered by key.
+    pub fn containers(&self) -> Vec<&ContainerDef> {
+        let mut containers: Vec<&ContainerDef> = self.containers.values().collect();
+        containers.sort_by_key(|c| c.key);
+        containers
+    }
+
+    /// Check that every base container and struct type refers to a
+    /// registered container.
+    pub fn check(&self) -> Result<(), Error> {
+        for container in self.containers() {
+            let bases = self.bases(container)?;
+            for field in &container.fields {
+                if let Some(base) = bases
+                    .iter()
+                    .find(|b| b.fields.iter().any(|f| f.name == field.name))
+                {
+                    return Err(Error::SchemaError(format!(
+                        "Duplicate field name '{}' in container '{}', already defined in base container '{}'",
+                        field.name, container.name, base.name
+                    )));
+                }
+            }
+            for field in &container.fields {
+                if let Some(struct_name) = &field.struct_name {
+                    if self.get_by_name(struct_name).is_none() {
+                        return Err(Error::SchemaError(format!(
+                            "Unknown struct type '{}' for field '{}' in container '{}'",
+                            struct_name, field.name, container.name
+                        )));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Base containers of a container, nearest first.
+    pub fn bases(&self, container: &ContainerDef) -> Result<Vec<&ContainerDef>, Error> {
+        let mut bases: Vec<&ContainerDef> = vec![];
+        let mut derived = container;
+        while let Some(name) = &derived.base {
+            let base = self.get_by_name(name).ok_or_else(|| {
+                Error::SchemaError(format!(
+                    "Unknown base container '{}' for container '{}'",
+                    name, derived.name
+                ))
+            })?;
+            if base.key == container.key || bases.iter().any(|b| b.key == base.key) {
+                return Err(Error::SchemaError(format!(
+                    "Circular base container '{}' for container '{}'",
+                    name, container.name
+                )));
+            }
+            bases.push(base);
+            derived = base;
+        }
+        Ok(bases)
+    }
+
+    /// Whether the container with the given key is the named container or
+    /// derived from it.
+    pub fn is_a(&self, key: i32, name: &str) -> bool {
+        match self.get(key) {
+            Some(container) if container.name == name => true,
+            Some(container) => self
+                .bases(container)
+                .map(|bases| bases.iter().any(|b| b.name == name))
+                .unwrap_or(false),
+            None => false,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.containers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.containers.is_empty()
+    }
+
+    /// Load container definitions from files in any of the supported
+    /// formats, chosen by file extension.
+    pub fn from_files<P: AsRef<Path>>(paths: &[P]) -> Result<SchemaRegistry, Error> {
+        let mut registry = SchemaRegistry::new();
+        for path in paths {
+            registry.load_file(path)?;
+        }
+        registry.check()?;
+        Ok(registry)
+    }
+
+    pub fn load_file<P: AsRef<Path>>(&mut self, path: P) -> Result<(), Error> {
+        let path = path.as_ref();
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("xml") => self.load_xml_file(path),
+            #[cfg(feature = "json")]
+            Some("json") => self.load_json_file(path),
+            #[cfg(feature = "toml")]
+            Some("toml") => self.load_toml_file(path),
+            _ => Err(Error::SchemaError(format!(
+                "Unknown schema file format '{}'",
+                path.display()
+            ))),
+        }
+    }
+
+    /// Resolve the layout of a container for the given versions.
+    ///
+    /// Only the first 1024 layouts looked up are cached, later versions are
+    /// resolved on each lookup.
+    pub fn lookup(
+        &self,
+        key: i32,
+        schema_version: u16,
+        ext_version: u16,
+    ) -> Result<Arc<ContainerSchema>, Error> {
+        let cache_key = (key, schema_version, ext_version);
+        if let Some(schema) = self
+            .cache
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&cache_key)
+        {
+            return Ok(schema.clone());
+        }
+
+        let schema = Arc::new(self.resolve(key, schema_version, ext_version)?);
+        let mut cache = self.cache.write().unwrap_or_else(|e| e.into_inner());
+        if cache.len() < CACHE_CAPACITY {
+            cache.insert(cache_key, schema.clone());
+        }
+        Ok(schema)
+    }
+
+    /// Resolve the layout of the container described by a header.
+    pub fn lookup_header(&self, header: &Header) -> Result<Arc<ContainerSchema>, Error> {
+        self.lookup(header.key, header.schema_version, header.ext_version)
+    }
+
+    fn resolve(
+        &self,
+        key: i32,
+        schema_version: u16,
+        ext_version: u16,
+    ) -> Result<ContainerSchema, Error> {
+        let container = self
+            .get(key)
+            .ok_or_else(|| Error::SchemaError(format!("Unknown container key {}", key)))?;
+
+        let bases = self.bases(container)?;
+        let fields: Vec<FieldDef> = bases
+            .iter()
+            .rev()
+            .chain(std::iter::once(&container))
+            .flat_map(|c| {
+                let version = c.version(schema_version, ext_version);
+                c.fields.iter().filter(move |f| f.introduced_in <= version)
+            })
+            .cloned()
+            .collect();
+        let index = fields
+            .iter()
+            .enumerate()
+            .map(|(idx, f)| (f.name.clone(), idx))
+            .collect();
+
+        Ok(ContainerSchema {
+            name: container.name.clone(),
+            key,
+            schema_version,
+            ext_version,
+            bases: bases.iter().map(|b| b.name.clone()).collect(),
+            fields,
+            index,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn registry() -> SchemaRegistry {
+        let mut registry = SchemaRegistry::new();
+        registry
+            .add(
+                ContainerDef::new("MtxBalanceInfo", 330)
+                    .field(FieldDef::new("ResourceId", FieldType::UInt32))
+                    .field(FieldDef::new("TemplateId", FieldType::UInt32))
+                    .field(FieldDef::new("ClassId", FieldType::UInt32).introduced_in(5200))
+                    .field(FieldDef::new("Amount", FieldType::Decimal).introduced_in(5300)),
+            )
+            .unwrap();
+        registry
+            .add(
+                ContainerDef::new("MtxResponseWallet", 263)
+                    .field(FieldDef::new("Result", FieldType::UInt32))
+                    .field(FieldDef::new_struct("BalanceArray", "MtxBalanceInfo").list())
+                    .field(FieldDef::new("ResultText", FieldType::String)),
+            )
+            .unwrap();
+        registry
+            .add(
+                ContainerDef::new("CustomWalletExtension", 1000)
+                    .extension()
+                    .field(FieldDef::new("Label", FieldType::String).introduced_in(1))
+                    .field(FieldDef::new("Region", FieldType::String).introduced_in(3)),
+            )
+            .unwrap();
+        registry
+    }
+
+    #[test]
+    fn test_lookup_by_key_and_version() {
+        let registry = registry();
+
+        let schema = registry.lookup(330, 5222, 2).unwrap();
+        assert_eq!(schema.name, "MtxBalanceInfo");
+        let names: Vec<&str> = schema.fields.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, ["ResourceId", "TemplateId", "ClassId"]);
+        assert_eq!(schema.field_index("ClassId"), Some(2));
+        assert_eq!(schema.field_index("Amount"), None);
+
+        let schema = registry.lookup(330, 5300, 2).unwrap();
+        assert_eq!(schema.fields.len(), 4);
+        assert_eq!(
+            schema.field("Amount").unwrap().field_type,
+            FieldType::Decimal
+        );
+    }
+
+    #[test]
+    fn test_lookup_extension_by_ext_version() {
+        let registry = registry();
+
+        let schema = registry.lookup(1000, 5222, 2).unwrap();
+        assert_eq!(schema.fields.len(), 1);
+        let schema = registry.lookup(1000, 5222, 3).unwrap();
+        assert_eq!(schema.fields.len(), 2);
+    }
+
+    #[test]
+    fn test_lookup_header() {
+        let registry = registry();
+        let header = Header {
+            version: 1,
+            total_field: 3,
+            depth: 0,
+            key: 263,
+            schema_version: 5222,
+            ext_version: 2,
+        };
+        let schema = registry.lookup_header(&header).unwrap();
+        assert_eq!(schema.name, "MtxResponseWallet");
+
+        let field = schema.field("BalanceArray").unwrap();
+        assert!(field.is_list);
+        assert!(field.is_struct());
+        assert_eq!(field.struct_name.as_deref(), Some("MtxBalanceInfo"));
+    }
+
+    #[test]
+    fn test_lookup_is_cached() {
+        let registry = registry();
+        let a = registry.lookup(330, 5222, 2).unwrap();
+        let b = registry.lookup(330, 5222, 2).unwrap();
+        assert!(Arc::ptr_eq(&a, &b));
+    }
+
+    #[test]
+    fn test_lookup_cache_is_bounded() {
+        let registry = registry();
+        let a = registry.lookup(330, 5222, 2).unwrap();
+        for version in 0..=u16::MAX {
+            registry.lookup(330, version, 2).unwrap();
+            registry.lookup(1000, 5222, version).unwrap();
+        }
+        assert_eq!(registry.cache.read().unwrap().len(), CACHE_CAPACITY);
+
+        // Layouts cached first are kept
+        let b = registry.lookup(330, 5222, 2).unwrap();
+        assert!(Arc::ptr_eq(&a, &b));
+        let schema = registry.lookup(330, u16::MAX, 2).unwrap();
+        assert_eq!(schema.schema_version, u16::MAX);
+        assert_eq!(schema.fields.len(), 4);
+    }
+
+    #[test]
+    fn test_lookup_unknown_key() {
+        let registry = registry();
+        let err = registry.lookup(208, 5222, 2).unwrap_err();
+        assert_eq!(err.to_string(), "Unknown container key 208");
+    }
+
+    #[test]
+    fn test_get_by_name() {
+        let registry = registry();
+        assert_eq!(registry.get_by_name("MtxResponseWallet").unwrap().key, 263);
+        assert!(registry.get_by_name("MtxRequest").is_none());
+        let keys: Vec<i32> = registry.containers().iter().map(|c| c.key).collect();
+        assert_eq!(keys, [263, 330, 1000]);
+    }
+
+    #[test]
+    fn test_check() {
+        let mut registry = registry();
+        registry.check().unwrap();
+
+        registry
+            .add(ContainerDef::new("MtxRequestFoo", 500).base("MtxRequest"))
+            .unwrap();
+        let err = registry.check().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Unknown base container 'MtxRequest' for container 'MtxRequestFoo'"
+        );
+
+        let mut registry = SchemaRegistry::new();
+        registry
+            .add(ContainerDef::new("Foo", 1).field(FieldDef::new_struct("Bar", "Bar")))
+            .unwrap();
+        let err = registry.check().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Unknown struct type 'Bar' for field 'Bar' in container 'Foo'"
+        );
+    }
+
+    #[test]
+    fn test_add_duplicate() {
+        let mut registry = registry();
+        let err = registry.add(ContainerDef::new("Other", 330)).unwrap_err();
+        assert_eq!(err.to_string(), "Duplicate container key 330");
+        let err = registry
+            .add(ContainerDef::new("MtxBalanceInfo", 331))
+            .unwrap_err();
+        assert_eq!(err.to_string(), "Duplicate container name 'MtxBalanceInfo'");
+        let err = registry
+            .add(
+                ContainerDef::new("Other", 331)
+                    .field(FieldDef::new("Id", FieldType::UInt32))
+                    .field(FieldDef::new("Id", FieldType::String)),
+            )
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Duplicate field name 'Id' in container 'Other'"
+        );
+    }
+
+    fn request_registry() -> SchemaRegistry {
+        let mut registry = SchemaRegistry::new();
+        registry
+            .add(
+                ContainerDef::new("MtxRequest", 91)
+                    .field(FieldDef::new("RequestId", FieldType::String))
+                    .field(FieldDef::new("TraceId", FieldType::String).introduced_in(5300)),
+            )
+            .unwrap();
+        registry
+            .add(
+                ContainerDef::new("MtxRequestSubscriber", 92)
+                    .base("MtxRequest")
+                    .field(FieldDef::new("ExternalId", FieldType::String)),
+            )
+            .unwrap();
+        registry
+            .add(
+                ContainerDef::new("MtxRequestSubscriberQuery", 93)
+                    .base("MtxRequestSubscriber")
+                    .field(FieldDef::new("ObjectId", FieldType::ObjectId)),
+            )
+            .unwrap();
+        registry
+    }
+
+    #[test]
+    fn test_lookup_inherited_fields() {
+        let registry = request_registry();
+        registry.check().unwrap();
+
+        let schema = registry.lookup(93, 5222, 2).unwrap();
+        let names: Vec<&str> = schema.fields.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, ["RequestId", "ExternalId", "ObjectId"]);
+        assert_eq!(schema.bases, ["MtxRequestSubscriber", "MtxRequest"]);
+        assert_eq!(schema.field_index("ObjectId"), Some(2));
+
+        let schema = registry.lookup(93, 5300, 2).unwrap();
+        let names: Vec<&str> = schema.fields.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, ["RequestId", "TraceId", "ExternalId", "ObjectId"]);
+    }
+
+    #[test]
+    fn test_is_a() {
+        let registry = request_registry();
+        assert!(registry.is_a(93, "MtxRequest"));
+        assert!(registry.is_a(93, "MtxRequestSubscriber"));
+        assert!(registry.is_a(93, "MtxRequestSubscriberQuery"));
+        assert!(!registry.is_a(91, "MtxRequestSubscriber"));
+        assert!(!registry.is_a(94, "MtxRequest"));
+
+        let schema = registry.lookup(92, 5222, 2).unwrap();
+        assert!(schema.is_a("MtxRequest"));
+        assert!(!schema.is_a("MtxRequestSubscriberQuery"));
+    }
+
+    #[test]
+    fn test_check_inheritance() {
+        let mut registry = request_registry();
+        registry
+            .add(
+                ContainerDef::new("MtxRequestWallet", 94)
+                    .base("MtxRequestSubscriber")
+                    .field(FieldDef::new("RequestId", FieldType::String)),
+            )
+            .unwrap();
+        let err = registry.check().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Duplicate field name 'RequestId' in container 'MtxRequestWallet', already defined in base container 'MtxRequest'"
+        );
+
+        let mut registry = SchemaRegistry::new();
+        registry.add(ContainerDef::new("A", 1).base("B")).unwrap();
+        registry.add(ContainerDef::new("B", 2).base("A")).unwrap();
+        let err = registry.check().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Circular base container 'A' for container 'A'"
+        );
+        let err = registry.lookup(2, 5222, 2).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Circular base container 'B' for container 'B'"
+        );
+    }
+
+    #[test]
+    fn test_load_file() {
+        #[cfg(all(feature = "json", feature = "toml"))]
+        {
+            let toml = SchemaRegistry::from_files(&["tests/data/bench_schema.toml"]).unwrap();
+            let json = SchemaRegistry::from_files(&["tests/data/bench_schema.json"]).unwrap();
+            assert_eq!(toml.containers(), json.containers());
+        }
+
+        let err = SchemaRegistry::from_files(&["tests/data/bench_generated.rs"]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Unknown schema file format 'tests/data/bench_generated.rs'"
+        );
+    }
+}
@@ -0,0 +1,586 @@
+use super::ContainerSchema;
+use super::FieldDef;
+use super::SchemaRegistry;
+use crate::cmdc::CMDC_CODEC;
+use crate::codec::Codec;
+use crate::container::null_field;
+use crate::container::set_depth;
+use crate::error::Error;
+use crate::mdd::Container;
+use crate::mdd::Containers;
+use crate::mdd::Field;
+use crate::mdd::FieldType;
+use crate::mdd::Header;
+use crate::mdd::Value;
+use std::fmt;
+use std::sync::Arc;
+
+/// Data left out when transcoding containers to another schema.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Loss {
+    /// Location in the source containers, in the form of `Violation::path`.
+    pub path: String,
+    pub kind: LossKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LossKind {
+    /// No source definition for the container key, the container is dropped.
+    UnknownKey(i32),
+    /// The container is not defined in the target schema and is dropped.
+    DroppedContainer,
+    /// The field is not defined in the target schema.
+    DroppedField,
+    /// A field beyond the source definition, at the given position.
+    UndefinedField(usize),
+    /// The value does not convert to the target field type.
+    TypeChanged { from: FieldType, to: FieldType },
+    /// The field is a list in one schema and not in the other.
+    ListChanged,
+}
+
+impl fmt::Display for Loss {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.kind)
+    }
+}
+
+impl fmt::Display for LossKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LossKind::UnknownKey(key) => write!(f, "unknown container key {}", key),
+            LossKind::DroppedContainer => write!(f, "container not defined in target schema"),
+            LossKind::DroppedField => write!(f, "field not defined in target schema"),
+            LossKind::UndefinedField(idx) => write!(f, "field {} is not defined", idx),
+            LossKind::TypeChanged { from, to } => {
+                write!(f, "value of type {:?} does not convert to {:?}", from, to)
+            }
+            LossKind::ListChanged => write!(f, "list flag differs in target schema"),
+        }
+    }
+}
+
+impl SchemaRegistry {
+    /// Rewrite containers described by this registry to their layout in
+    /// `target` at the given versions.
+    ///
+    /// Containers and fields are matched by name, and keys and headers are
+    /// rewritten for the target. Fields new in the target are null. Values
+    /// that can't be carried over are left out and reported, scalar values
+    /// of a changed type are kept when they parse as the new type.
+    pub fn transcode<'a>(
+        &self,
+        containers: &Containers<'a>,
+        target: &SchemaRegistry,
+        schema_version: u16,
+        ext_version: u16,
+    ) -> Result<(Containers<'a>, Vec<Loss>), Error> {
+        let transcoder = Transcoder {
+            source: self,
+            target,
+            schema_version,
+            ext_version,
+        };
+        let mut losses = vec![];
+        let mut transcoded = vec![];
+        for (idx, container) in containers.containers.iter().enumerate() {
+            let name = match self.get(container.header.key) {
+                Some(def) => def.name.clone(),
+                None => container.header.key.to_string(),
+            };
+            let path = format!("{}[{}]", name, idx);
+            if let Some(mut container) = transcoder.container(container, &path, &mut losses)? {
+                let depth = container.header.depth;
+                set_depth(&mut container, depth);
+                transcoded.push(container);
+            }
+        }
+        Ok((
+            Containers {
+                containers: transcoded,
+            },
+            losses,
+        ))
+    }
+}
+
+struct Transcoder<'r> {
+    source: &'r SchemaRegistry,
+    target: &'r SchemaRegistry,
+    schema_version: u16,
+    ext_version: u16,
+}
+
+impl<'r> Transcoder<'r> {
+    fn container<'a>(
+        &self,
+        container: &Container<'a>,
+        path: &str,
+        losses: &mut Vec<Loss>,
+    ) -> Result<Option<Container<'a>>, Error> {
+        let mut loss = |path: String, kind| losses.push(Loss { path, kind });
+
+        let header = &container.header;
+        if self.source.get(header.key).is_none() {
+            loss(path.to_string(), LossKind::UnknownKey(header.key));
+            return Ok(None);
+        }
+        let source = self.source.lookup_header(header)?;
+        let def = match self.target.get_by_name(&source.name) {
+            Some(def) => def,
+            None => {
+                loss(path.to_string(), LossKind::DroppedContainer);
+                return Ok(None);
+            }
+        };
+        let target = self
+            .target
+            .lookup(def.key, self.schema_version, self.ext_version)?;
+
+        let count = container.field_count();
+        let fields = &container.fields[..count];
+        for (idx, _) in fields.iter().enumerate().filter(|(_, f)| !f.is_null) {
+            match source.fields.get(idx) {
+                None => loss(format!("{}.{}", path, idx), LossKind::UndefinedField(idx)),
+                Some(def) if target.field_index(&def.name).is_none() => {
+                    loss(format!("{}.{}", path, def.name), LossKind::DroppedField)
+                }
+                _ => {}
+            }
+        }
+
+        let mut transcoded = Vec::with_capacity(target.fields.len());
+        for to in &target.fields {
+            let from = source
+                .field_index(&to.name)
+                .and_then(|idx| Some((&source.fields[idx], fields.get(idx)?)))
+                .filter(|(_, field)| !field.is_null);
+            transcoded.push(match from {
+                Some((from, field)) => {
+                    let path = format!("{}.{}", path, to.name);
+                    self.field(field, from, to, &path, losses)?
+                }
+                None => null_field(to.field_type.clone()),
+            });
+        }
+
+        let header = Header {
+            version: header.version,
+            total_field: total_field(&target)?,
+            depth: header.depth,
+            key: def.key,
+            schema_version: self.schema_version,
+            ext_version: self.ext_version,
+        };
+        Ok(Some(Container::new(header, transcoded)))
+    }
+
+    fn field<'a>(
+        &self,
+        field: &Field<'a>,
+        from: &FieldDef,
+        to: &FieldDef,
+        path: &str,
+        losses: &mut Vec<Loss>,
+    ) -> Result<Field<'a>, Error> {
+        let mut loss = |kind| {
+            losses.push(Loss {
+                path: path.to_string(),
+                kind,
+            })
+        };
+        let null = || null_field(to.field_type.clone());
+
+        if from.is_list != to.is_list {
+            loss(LossKind::ListChanged);
+            return Ok(null());
+        }
+        let codec = field
+            .codec
+            .ok_or_else(|| Error::DecodeError("No codec".into()))?;
+
+        if !from.is_struct() && !to.is_struct() {
+            if let Some(value) = &field.value {
+                return Ok(match convert(field, value, to) {
+                    Some(value) => Field {
+                        data: b"",
+                        field_type: to.field_type.clone(),
+                        value: Some(value),
+                        is_multi: to.is_list,
+                        ..field.clone()
+                    },
+                    None => {
+                        loss(LossKind::TypeChanged {
+                            from: from.field_type.clone(),
+                            to: to.field_type.clone(),
+                        });
+                        null()
+                    }
+                });
+            }
+            let transcoded = Field {
+                field_type: to.field_type.clone(),
+                is_multi: to.is_list,
+                value: None,
+                ..field.clone()
+            };
+            // Values are kept when their text parses as the new type, such
+            // as integers widened or read as decimals
+            if from.field_type != to.field_type && codec.decode_field(&transcoded).is_err() {
+                loss(LossKind::TypeChanged {
+                    from: from.field_type.clone(),
+                    to: to.field_type.clone(),
+                });
+                return Ok(null());
+            }
+            return Ok(transcoded);
+        }
+        if from.is_struct() != to.is_struct() {
+            loss(LossKind::TypeChanged {
+                from: from.field_type.clone(),
+                to: to.field_type.clone(),
+            });
+            return Ok(null());
+        }
+
+        let value = match &field.value {
+            Some(value) => value.clone(),
+            None => codec.decode_field(&Field {
+                field_type: FieldType::Struct,
+                is_multi: from.is_list,
+                value: None,
+                ..field.clone()
+            })?,
+        };
+        let value = match value {
+            Value::List(values) => {
+                let mut transcoded = Vec::with_capacity(values.len());
+                for (idx, value) in values.iter().enumerate() {
+                    let path = format!("{}[{}]", path, idx);
+                    transcoded.push(match value {
+                        Some(Value::Struct(containers)) => {
+                            self.containers(containers, &path, losses)?
+                        }
+                        _ => None,
+                    });
+                }
+                Some(Value::List(transcoded))
+            }
+            Value::Struct(containers) => self.containers(&containers, path, losses)?,
+            _ => None,
+        };
+
+        Ok(match value {
+            Some(value) => Field {
+                data: b"",
+                field_type: FieldType::Struct,
+                value: Some(value),
+                codec: field.codec,
+                is_multi: to.is_list,
+                is_container: true,
+                is_null: false,
+            },
+            None => null(),
+        })
+    }
+
+    /// Transcode the containers of a struct value, `None` when none are left.
+    fn containers<'a>(
+        &self,
+        containers: &Containers<'a>,
+        path: &str,
+        losses: &mut Vec<Loss>,
+    ) -> Result<Option<Value<'a>>, Error> {
+        let containers = &containers.containers;
+        let mut transcoded = vec![];
+        for (idx, container) in containers.iter().enumerate() {
+            let path = match containers.len() {
+                1 => path.to_string(),
+                _ => format!("{}[{}]", path, idx),
+            };
+            if let Some(container) = self.container(container, &path, losses)? {
+                transcoded.push(container);
+            }
+        }
+        Ok(match transcoded.is_empty() {
+            true => None,
+            false => Some(Value::Struct(Containers {
+                containers: transcoded,
+            })),
+        })
+    }
+}
+
+/// Convert the value of a field built from values to the target type,
+/// through its cMDC text as fields with data are.
+fn convert<'a>(field: &Field<'a>, value: &Value<'a>, to: &FieldDef) -> Option<Value<'a>> {
+    if field.field_type == to.field_type && field.is_multi == to.is_list {
+        return Some(value.clone());
+    }
+    let text = CMDC_CODEC.encode_field(field).ok()?;
+    let value = CMDC_CODEC
+        .decode_field(&Field {
+            data: &text,
+            field_type: to.field_type.clone(),
+            value: None,
+            codec: Some(&CMDC_CODEC),
+            is_multi: to.is_list,
+            is_container: false,
+            is_null: false,
+        })
+        .ok()?;
+    detach(value)
+}
+
+/// Detach a scalar value from the text it was decoded from.
+fn detach(value: Value) -> Option<Value<'static>> {
+    Some(match value {
+        Value::Struct(_) => return None,
+        Value::String(v) => Value::String(v),
+        Value::Int8(v) => Value::Int8(v),
+        Value::Int16(v) => Value::Int16(v),
+        Value::Int32(v) => Value::Int32(v),
+        Value::Int64(v) => Value::Int64(v),
+        Value::UInt8(v) => Value::UInt8(v),
+        Value::UInt16(v) => Value::UInt16(v),
+        Value::UInt32(v) => Value::UInt32(v),
+        Value::UInt64(v) => Value::UInt64(v),
+        Value::Bool(v) => Value::Bool(v),
+        Value::Decimal(v) => Value::Decimal(v),
+        Value::DateTime(v) => Value::DateTime(v),
+        Value::Date(v) => Value::Date(v),
+        Value::Time(v) => Value::Time(v),
+        Value::ObjectId(v) => Value::ObjectId(v),
+        Value::List(values) => Value::List(
+            values
+                .into_iter()
+                .map(|v| match v {
+                    Some(v) => detach(v).map(Some),
+                    None => Some(None),
+                })
+                .collect::<Option<_>>()?,
+        ),
+    })
+}
+
+fn total_field(schema: &Arc<ContainerSchema>) -> Result<u8, Error> {
+    u8::try_from(schema.fields.len()).map_err(|_| {
+        Error::EncodeError(format!(
+            "Too many fields {} in container '{}'",
+            schema.fields.len(),
+            schema.name
+        ))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::container::ContainerBuilder;
+    use crate::schema::ContainerDef;
+
+    fn source() -> SchemaRegistry {
+        let mut registry = SchemaRegistry::new();
+        registry
+            .add(
+                ContainerDef::new("MtxBalanceInfo", 330)
+                    .field(FieldDef::new("TemplateId", FieldType::UInt8))
+                    .field(FieldDef::new("ResourceId", FieldType::UInt32))
+                    .field(FieldDef::new("Name", FieldType::String))
+                    .field(FieldDef::new("ClassId", FieldType::UInt32).introduced_in(5200)),
+            )
+            .unwrap();
+        registry
+            .add(
+                ContainerDef::new("MtxResponseWallet", 263)
+                    .field(FieldDef::new("Result", FieldType::UInt32))
+                    .field(FieldDef::new_struct("BalanceArray", "MtxBalanceInfo").list())
+                    .field(FieldDef::new("Tags", FieldType::String).list())
+                    .field(FieldDef::new_struct("Session", "MtxSession")),
+            )
+            .unwrap();
+        registry
+            .add(
+                ContainerDef::new("MtxSession", 400)
+                    .field(FieldDef::new("SessionId", FieldType::String)),
+            )
+            .unwrap();
+        registry.check().unwrap();
+        registry
+    }
+
+    fn target() -> SchemaRegistry {
+        let mut target = SchemaRegistry::new();
+        target
+            .add(
+                ContainerDef::new("MtxBalanceInfo", 1330)
+                    .field(FieldDef::new("ResourceId", FieldType::UInt32))
+                    .field(FieldDef::new("TemplateId", FieldType::Decimal))
+                    .field(FieldDef::new("Name", FieldType::UInt32))
+                    .field(FieldDef::new("Amount", FieldType::Decimal)),
+            )
+            .unwrap();
+        target
+            .add(
+                ContainerDef::new("MtxResponseWallet", 263)
+                    .field(FieldDef::new("Result", FieldType::UInt32))
+                    .field(FieldDef::new_struct("BalanceArray", "MtxBalanceInfo").list())
+                    .field(FieldDef::new("Tags", FieldType::String))
+                    .field(FieldDef::new("Session", FieldType::String)),
+            )
+            .unwrap();
+        target
+    }
+
+    #[test]
+    fn test_transcode_versions() {
+        // Same registry, from a version with ClassId to one without
+        let registry = source();
+        let data = b"<1,4,0,330,5222,2>[200,4,(4:main),7]<1,3,0,330,5100,2>[201,5,]";
+        let containers = CMDC_CODEC.decode(data).unwrap();
+
+        let (transcoded, losses) = registry.transcode(&containers, &registry, 5100, 2).unwrap();
+        assert_eq!(
+            CMDC_CODEC.encode(&transcoded).unwrap(),
+            b"<1,3,0,330,5100,2>[200,4,(4:main)]<1,3,0,330,5100,2>[201,5,]"
+        );
+        let losses: Vec<String> = losses.iter().map(|l| l.to_string()).collect();
+        assert_eq!(
+            losses,
+            ["MtxBalanceInfo[0].ClassId: field not defined in target schema"]
+        );
+
+        // And back, the new field is null
+        let (transcoded, losses) = registry.transcode(&transcoded, &registry, 5222, 2).unwrap();
+        assert_eq!(
+            CMDC_CODEC.encode(&transcoded).unwrap(),
+            b"<1,4,0,330,5222,2>[200,4,(4:main),]<1,4,0,330,5222,2>[201,5,,]"
+        );
+        assert!(losses.is_empty());
+    }
+
+    #[test]
+    fn test_transcode_registries() {
+        // The target renumbers MtxBalanceInfo, widens TemplateId, reorders
+        // and retypes fields, and has no MtxSession
+        let target = target();
+        let data = b"<1,4,0,263,5222,2>[0,{<1,4,1,330,5222,2>[200,4,(4:main),7],<1,2,1,330,5222,2>[201,5]},{(1:a)},<1,1,1,400,5222,2>[(2:s1)]]<1,1,0,400,5222,2>[(2:s2)]<1,0,0,999,5222,2>[]";
+        let containers = CMDC_CODEC.decode(data).unwrap();
+        let (transcoded, losses) = source().transcode(&containers, &target, 1, 0).unwrap();
+        assert_eq!(
+            String::from_utf8(CMDC_CODEC.encode(&transcoded).unwrap()).unwrap(),
+            "<1,4,0,263,1,0>[0,{<1,4,1,1330,1,0>[4,200,,],<1,4,1,1330,1,0>[5,201,,]},,]"
+        );
+        let losses: Vec<String> = losses.iter().map(|l| l.to_string()).collect();
+        assert_eq!(
+            losses,
+            [
+                "MtxResponseWallet[0].BalanceArray[0].ClassId: field not defined in target schema",
+                "MtxResponseWallet[0].BalanceArray[0].Name: value of type String does not convert to UInt32",
+                "MtxResponseWallet[0].Tags: list flag differs in target schema",
+                "MtxResponseWallet[0].Session: value of type Struct does not convert to String",
+                "MtxSession[1]: container not defined in target schema",
+                "999[2]: unknown container key 999",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_transcode_undefined_fields() {
+        let registry = source();
+        let data = b"<1,5,0,400,5222,2>[(2:s1),,3]";
+        let containers = CMDC_CODEC.decode(data).unwrap();
+        let (transcoded, losses) = registry.transcode(&containers, &registry, 5222, 2).unwrap();
+        assert_eq!(
+            CMDC_CODEC.encode(&transcoded).unwrap(),
+            b"<1,1,0,400,5222,2>[(2:s1)]"
+        );
+        assert_eq!(
+            losses,
+            [Loss {
+                path: "MtxSession[0].2".into(),
+                kind: LossKind::UndefinedField(2),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_transcode_built_container() {
+        let container = ContainerBuilder::new(330, 5222, 2)
+            .field(0, &7u8)
+            .unwrap()
+            .field(1, &9u32)
+            .unwrap()
+            .field(2, &"main".to_string())
+            .unwrap()
+            .build()
+            .unwrap();
+        let containers = Containers {
+            containers: vec![container],
+        };
+
+        let registry = source();
+        let (transcoded, losses) = registry.transcode(&containers, &registry, 5222, 2).unwrap();
+        assert_eq!(
+            CMDC_CODEC.encode(&transcoded).unwrap(),
+            b"<1,4,0,330,5222,2>[7,9,(4:main),]"
+        );
+        assert!(losses.is_empty());
+
+        let (transcoded, losses) = registry.transcode(&containers, &target(), 1, 0).unwrap();
+        assert_eq!(
+            CMDC_CODEC.encode(&transcoded).unwrap(),
+            b"<1,4,0,1330,1,0>[9,7,,]"
+        );
+        let losses: Vec<String> = losses.iter().map(|l| l.to_string()).collect();
+        assert_eq!(
+            losses,
+            ["MtxBalanceInfo[0].Name: value of type String does not convert to UInt32"]
+        );
+
+        // Struct values are carried over too
+        let wallet = ContainerBuilder::new(263, 5222, 2)
+            .field(1, &vec![containers.clone()])
+            .unwrap()
+            .build()
+            .unwrap();
+        let (transcoded, losses) = registry
+            .transcode(
+                &Containers {
+                    containers: vec![wallet],
+                },
+                &target(),
+                1,
+                0,
+            )
+            .unwrap();
+        assert_eq!(
+            CMDC_CODEC.encode(&transcoded).unwrap(),
+            b"<1,4,0,263,1,0>[,{<1,4,1,1330,1,0>[9,7,,]},,]"
+        );
+        assert_eq!(losses[0].path, "MtxResponseWallet[0].BalanceArray[0].Name");
+        assert_eq!(losses.len(), 1);
+
+        // A single field built from a value has no data but is kept
+        let wallet = ContainerBuilder::new(263, 5222, 2)
+            .field(0, &7u32)
+            .unwrap()
+            .build()
+            .unwrap();
+        let (transcoded, losses) = registry
+            .transcode(
+                &Containers {
+                    containers: vec![wallet],
+                },
+                &target(),
+                1,
+                0,
+            )
+            .unwrap();
+        assert_eq!(
+            CMDC_CODEC.encode(&transcoded).unwrap(),
+            b"<1,4,0,263,1,0>[7,,,]"
+        );
+        assert!(losses.is_empty());
+    }
+}
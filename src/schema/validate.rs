@@ -0,0 +1,443 @@
+use super::ContainerSchema;
+use super::FieldDef;
+use super::SchemaRegistry;
+use crate::mdd::Container;
+use crate::mdd::Containers;
+use crate::mdd::Field;
+use crate::mdd::FieldType;
+use crate::mdd::Value;
+use std::fmt;
+use std::sync::Arc;
+
+/// A mismatch between decoded containers and their definitions.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Violation {
+    /// Location of the mismatch, such as `MtxResponseWallet[0].BalanceArray[1].TemplateId`.
+    /// Containers are named after their definition or by key when unknown,
+    /// list elements and concatenated containers are indexed.
+    pub path: String,
+    pub kind: ViolationKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ViolationKind {
+    /// No definition for the container key.
+    UnknownKey(i32),
+    /// A struct field holds a container not derived from the declared one.
+    NotSubtype { expected: String, found: String },
+    /// Header `total_field` differs from the number of defined fields.
+    TotalFieldMismatch { expected: usize, found: u8 },
+    /// The body has more fields than the definition.
+    TooManyFields { expected: usize, found: usize },
+    /// A field value does not parse as its declared type, or a value set on
+    /// the field is of another type.
+    InvalidValue(String),
+    /// A list value in a field not defined as a list.
+    UnexpectedList,
+    /// A required field is null or left out.
+    MissingRequired,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.kind)
+    }
+}
+
+impl fmt::Display for ViolationKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ViolationKind::UnknownKey(key) => write!(f, "unknown container key {}", key),
+            ViolationKind::NotSubtype { expected, found } => {
+                write!(f, "container '{}' is not a '{}'", found, expected)
+            }
+            ViolationKind::TotalFieldMismatch { expected, found } => write!(
+                f,
+                "header total_field is {} but {} fields are defined",
+                found, expected
+            ),
+            ViolationKind::TooManyFields { expected, found } => {
+                write!(f, "{} fields found but {} are defined", found, expected)
+            }
+            ViolationKind::InvalidValue(msg) => write!(f, "invalid value, {}", msg),
+            ViolationKind::UnexpectedList => write!(f, "list value in a non-list field"),
+            ViolationKind::MissingRequired => write!(f, "required field is missing"),
+        }
+    }
+}
+
+impl SchemaRegistry {
+    /// Check decoded containers against their definitions, reporting every
+    /// mismatch found. Works on containers with or without a schema applied.
+    pub fn validate(&self, containers: &Containers) -> Vec<Violation> {
+        let mut violations = vec![];
+        for (idx, container) in containers.containers.iter().enumerate() {
+            let name = match self.get(container.header.key) {
+                Some(def) => def.name.clone(),
+                None => container.header.key.to_string(),
+            };
+            let path = format!("{}[{}]", name, idx);
+            self.validate_container(container, None, &path, &mut violations);
+        }
+        violations
+    }
+
+    fn validate_container(
+        &self,
+        container: &Container,
+        declared: Option<&str>,
+        path: &str,
+        violations: &mut Vec<Violation>,
+    ) {
+        let mut violation = |path: &str, kind| {
+            violations.push(Violation {
+                path: path.to_string(),
+                kind,
+            })
+        };
+
+        let header = &container.header;
+        let schema = match self.get(header.key) {
+            Some(_) => self.lookup_header(header).ok(),
+            None => {
+                violation(path, ViolationKind::UnknownKey(header.key));
+                // Unknown containers in struct fields are checked as the
+                // declared container
+                declared
+                    .and_then(|name| self.get_by_name(name))
+                    .and_then(|def| {
+                        self.lookup(def.key, header.schema_version, header.ext_version)
+                            .ok()
+                    })
+            }
+        };
+        let schema: Arc<ContainerSchema> = match schema {
+            Some(schema) => schema,
+            None => return,
+        };
+
+        if let Some(declared) = declared {
+            if !schema.is_a(declared) {
+                violation(
+                    path,
+                    ViolationKind::NotSubtype {
+                        expected: declared.to_string(),
+                        found: schema.name.clone(),
+                    },
+                );
+                return;
+            }
+        }
+
+        if header.total_field as usize != schema.fields.len() {
+            violation(
+                path,
+                ViolationKind::TotalFieldMismatch {
+                    expected: schema.fields.len(),
+                    found: header.total_field,
+                },
+            );
+        }
+        let count = container.field_count();
+        if count > schema.fields.len() {
+            violation(
+                path,
+                ViolationKind::TooManyFields {
+                    expected: schema.fields.len(),
+                    found: count,
+                },
+            );
+        }
+
+        for (idx, def) in schema.fields.iter().enumerate() {
+            let path = format!("{}.{}", path, def.name);
+            match container.fields[..count].get(idx) {
+                Some(field) if !field.is_null => self.validate_field(field, def, &path, violations),
+                _ if def.is_required => violations.push(Violation {
+                    path,
+                    kind: ViolationKind::MissingRequired,
+                }),
+                _ => {}
+            }
+        }
+    }
+
+    fn validate_field(
+        &self,
+        field: &Field,
+        def: &FieldDef,
+        path: &str,
+        violations: &mut Vec<Violation>,
+    ) {
+        let mut violation = |kind| {
+            violations.push(Violation {
+                path: path.to_string(),
+                kind,
+            })
+        };
+
+        // Fields built or decoded with a value are checked as they are,
+        // others are decoded from a typed copy, leaving the field as it is
+        let decoded;
+        let value = match &field.value {
+            Some(value) => value,
+            None => {
+                let codec = match field.codec {
+                    Some(codec) => codec,
+                    None => return violation(ViolationKind::InvalidValue("No codec".into())),
+                };
+                let field = Field {
+                    field_type: def.field_type.clone(),
+                    is_multi: field.is_multi || def.is_list,
+                    ..field.clone()
+                };
+                decoded = match codec.decode_field(&field) {
+                    Ok(value) => value,
+                    Err(e) => return violation(ViolationKind::InvalidValue(e.to_string())),
+                };
+                &decoded
+            }
+        };
+
+        let values: Vec<(Option<usize>, &Value)> = match value {
+            Value::List(_) if !def.is_list => return violation(ViolationKind::UnexpectedList),
+            Value::List(values) => values
+                .iter()
+                .enumerate()
+                .filter_map(|(idx, value)| value.as_ref().map(|value| (Some(idx), value)))
+                .collect(),
+            _ if def.is_list => {
+                return violation(ViolationKind::InvalidValue(format!(
+                    "Value type mismatch, expected List but found {}",
+                    value.type_name()
+                )))
+            }
+            value => vec![(None, value)],
+        };
+        let declared = def.struct_name.as_deref();
+        for (idx, value) in values {
+            let path = match idx {
+                Some(idx) => format!("{}[{}]", path, idx),
+                None => path.to_string(),
+            };
+            match value {
+                Value::Struct(containers) if def.field_type == FieldType::Struct => {
+                    self.validate_struct(containers, declared, &path, violations);
+                }
+                value if value.type_name() != format!("{:?}", def.field_type) => {
+                    violations.push(Violation {
+                        path,
+                        kind: ViolationKind::InvalidValue(format!(
+                            "Value type mismatch, expected {:?} but found {}",
+                            def.field_type,
+                            value.type_name()
+                        )),
+                    });
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn validate_struct(
+        &self,
+        containers: &Containers,
+        declared: Option<&str>,
+        path: &str,
+        violations: &mut Vec<Violation>,
+    ) {
+        let containers = &containers.containers;
+        for (idx, container) in containers.iter().enumerate() {
+            let path = match containers.len() {
+                1 => path.to_string(),
+                _ => format!("{}[{}]", path, idx),
+            };
+            self.validate_container(container, declared, &path, violations);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmdc::CMDC_CODEC;
+    use crate::codec::Codec;
+    use crate::container::ContainerBuilder;
+    use crate::object_id::ObjectId;
+    use crate::schema::ContainerDef;
+
+    fn registry() -> SchemaRegistry {
+        let mut registry = SchemaRegistry::new();
+        registry
+            .add(
+                ContainerDef::new("MtxBalanceInfo", 330)
+                    .field(FieldDef::new("TemplateId", FieldType::UInt32).required())
+                    .field(FieldDef::new("ResourceId", FieldType::UInt32))
+                    .field(FieldDef::new("Amount", FieldType::Decimal)),
+            )
+            .unwrap();
+        registry
+            .add(
+                ContainerDef::new("MtxResponseWallet", 263)
+                    .field(FieldDef::new("Result", FieldType::UInt32).required())
+                    .field(FieldDef::new_struct("BalanceArray", "MtxBalanceInfo").list())
+                    .field(FieldDef::new("ExternalId", FieldType::String))
+                    .field(FieldDef::new("Flags", FieldType::Bool))
+                    .field(FieldDef::new("Owner", FieldType::ObjectId).required()),
+            )
+            .unwrap();
+        registry
+            .add(
+                ContainerDef::new("MtxResultInfo", 452)
+                    .field(FieldDef::new("ResultCode", FieldType::UInt32)),
+            )
+            .unwrap();
+        registry
+    }
+
+    fn violations(data: &[u8]) -> Vec<String> {
+        let containers = CMDC_CODEC.decode_containers(data).unwrap();
+        registry()
+            .validate(&containers)
+            .iter()
+            .map(|v| v.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_validate_valid() {
+        let data = b"<1,5,0,263,5222,2>[2,{<1,3,1,330,5222,2>[200,4,1.5],<1,3,1,330,5222,2>[202]},(6:555555),1,0:1:5:277]<1,1,0,452,5222,2>[1]<1,1,0,452,5222,2>[]";
+        assert!(violations(data).is_empty());
+
+        // Typed containers validate the same
+        let registry = registry();
+        let containers = CMDC_CODEC.decode_with_schema(data, &registry).unwrap();
+        assert!(registry.validate(&containers).is_empty());
+    }
+
+    #[test]
+    fn test_validate_header() {
+        let data =
+            b"<1,7,0,263,5222,2>[2,,,,0:1:5:277,7]<1,1,0,208,5222,2>[1]<1,1,0,452,5222,2>[1]";
+        let containers = CMDC_CODEC.decode_containers(data).unwrap();
+        let violations = registry().validate(&containers);
+        assert_eq!(
+            violations,
+            [
+                Violation {
+                    path: "MtxResponseWallet[0]".to_string(),
+                    kind: ViolationKind::TotalFieldMismatch {
+                        expected: 5,
+                        found: 7
+                    },
+                },
+                Violation {
+                    path: "MtxResponseWallet[0]".to_string(),
+                    kind: ViolationKind::TooManyFields {
+                        expected: 5,
+                        found: 6
+                    },
+                },
+                Violation {
+                    path: "208[1]".to_string(),
+                    kind: ViolationKind::UnknownKey(208),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_fields() {
+        let data = b"<1,5,0,263,5222,2>[,{<1,3,1,330,5222,2>[200,4,1.5],<1,3,1,330,5222,2>[,x]},{(3:abc)},2]";
+        assert_eq!(
+            violations(data),
+            [
+                "MtxResponseWallet[0].Result: required field is missing",
+                "MtxResponseWallet[0].BalanceArray[1].TemplateId: required field is missing",
+                "MtxResponseWallet[0].BalanceArray[1].ResourceId: invalid value, invalid digit found in string",
+                "MtxResponseWallet[0].ExternalId: list value in a non-list field",
+                "MtxResponseWallet[0].Flags: invalid value, Invalid bool value '2', 0 or 1 expected",
+                "MtxResponseWallet[0].Owner: required field is missing",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_nested_containers() {
+        let data = b"<1,5,0,263,5222,2>[2,{<1,1,1,452,5222,2>[1],<1,3,1,330,5222,2>[1]<1,2,1,331,5222,2>[1,2]},,,0:1:5:277]";
+        assert_eq!(
+            violations(data),
+            [
+                "MtxResponseWallet[0].BalanceArray[0]: container 'MtxResultInfo' is not a 'MtxBalanceInfo'",
+                "MtxResponseWallet[0].BalanceArray[1][1]: unknown container key 331",
+                "MtxResponseWallet[0].BalanceArray[1][1]: header total_field is 2 but 3 fields are defined",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_built_container() {
+        let registry = registry();
+        let balance = ContainerBuilder::new(330, 5222, 2)
+            .total_field(3)
+            .field(0, &200u32)
+            .unwrap()
+            .build()
+            .unwrap();
+        let container = ContainerBuilder::new(263, 5222, 2)
+            .field(0, &2u32)
+            .unwrap()
+            .field(
+                1,
+                &vec![Containers {
+                    containers: vec![balance],
+                }],
+            )
+            .unwrap()
+            .field(4, &ObjectId::new(0, 1, 5, 277))
+            .unwrap()
+            .build()
+            .unwrap();
+        let containers = Containers {
+            containers: vec![container],
+        };
+        assert!(registry.validate(&containers).is_empty());
+
+        let container = ContainerBuilder::new(263, 5222, 2)
+            .total_field(5)
+            .field(0, &"2".to_string())
+            .unwrap()
+            .field(2, &vec![1u32])
+            .unwrap()
+            .build()
+            .unwrap();
+        let containers = Containers {
+            containers: vec![container],
+        };
+        let violations: Vec<String> = registry
+            .validate(&containers)
+            .iter()
+            .map(|v| v.to_string())
+            .collect();
+        assert_eq!(
+            violations,
+            [
+                "MtxResponseWallet[0].Result: invalid value, Value type mismatch, expected UInt32 but found String",
+                "MtxResponseWallet[0].ExternalId: list value in a non-list field",
+                "MtxResponseWallet[0].Owner: required field is missing",
+            ]
+        );
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_validate_json_container() {
+        use crate::json::JsonCodec;
+
+        let registry = registry();
+        let json = JsonCodec::new(&registry, 5222, 2);
+        let data = br#"{"$":"MtxResponseWallet","Result":2,"BalanceArray":[{"$":"MtxBalanceInfo","TemplateId":200,"Amount":1.5}],"ExternalId":"555555","Owner":"0:1:5:277"}"#;
+        let containers = json.decode(data).unwrap();
+        assert!(registry.validate(&containers).is_empty());
+    }
+}
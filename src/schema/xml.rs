@@ -0,0 +1,352 @@
+//! Loader for MATRIXX-style XML data container definitions.
+//!
+//! ```xml
+//! <data_container_definitions>
+//!   <container name="MtxBalanceInfo" key="330" base="MtxBase" extension="false">
+//!     <field name="TemplateId" datatype="uint32" required="true"/>
+//!     <field name="ThresholdArray" datatype="struct" struct_type="MtxThreshold" array="true"/>
+//!     <field name="ClassId" datatype="uint32" created="5200"/>
+//!   </container>
+//! </data_container_definitions>
+//! ```
+//!
+//! `datatype` takes the `FieldType` names, `created` is the version the
+//! field was introduced in. `required` fields must not be null.
+
+use super::ContainerDef;
+use super::FieldDef;
+use super::SchemaRegistry;
+use crate::error::Error;
+use crate::mdd::FieldType;
+use std::path::Path;
+
+impl SchemaRegistry {
+    /// Load container definitions from XML files. Struct types and base
+    /// containers may refer to containers defined in any of the files.
+    pub fn from_xml_files<P: AsRef<Path>>(paths: &[P]) -> Result<SchemaRegistry, Error> {
+        let mut registry = SchemaRegistry::new();
+        for path in paths {
+            registry.load_xml_file(path)?;
+        }
+        registry.check()?;
+        Ok(registry)
+    }
+
+    pub fn load_xml_file<P: AsRef<Path>>(&mut self, path: P) -> Result<(), Error> {
+        let path = path.as_ref();
+        let xml = std::fs::read_to_string(path)?;
+        self.load_xml(&xml)
+            .map_err(|e| Error::SchemaError(format!("{}: {}", path.display(), e)))
+    }
+
+    /// Add the containers defined in an XML document.
+    pub fn load_xml(&mut self, xml: &str) -> Result<(), Error> {
+        let doc = roxmltree::Document::parse(xml)
+            .map_err(|e| Error::SchemaError(format!("Invalid XML, {}", e)))?;
+
+        for node in doc.root_element().children().filter(|n| n.is_element()) {
+            if node.tag_name().name() != "container" {
+                return Err(error(
+                    &doc,
+                    node,
+                    format!("unexpected element <{}>", node.tag_name().name()),
+                ));
+            }
+            let container = parse_container(&doc, node)?;
+            self.add(container)
+                .map_err(|e| error(&doc, node, e.to_string()))?;
+        }
+        Ok(())
+    }
+}
+
+fn parse_container(
+    doc: &roxmltree::Document,
+    node: roxmltree::Node,
+) -> Result<ContainerDef, Error> {
+    let name = required(doc, node, "name")?;
+    let key = required(doc, node, "key")?;
+    let key = key.parse::<i32>().map_err(|_| {
+        error(
+            doc,
+            node,
+            format!("invalid key '{}' for container '{}'", key, name),
+        )
+    })?;
+
+    let mut container = ContainerDef::new(name, key);
+    container.base = node.attribute("base").map(|s| s.to_string());
+    container.is_extension = parse_bool(doc, node, "extension")?;
+
+    for child in node.children().filter(|n| n.is_element()) {
+        if child.tag_name().name() != "field" {
+            return Err(error(
+                doc,
+                child,
+                format!(
+                    "unexpected element <{}> in container '{}'",
+                    child.tag_name().name(),
+                    name
+                ),
+            ));
+        }
+        container.fields.push(parse_field(doc, child, name)?);
+    }
+
+    Ok(container)
+}
+
+fn parse_field(
+    doc: &roxmltree::Document,
+    node: roxmltree::Node,
+    container: &str,
+) -> Result<FieldDef, Error> {
+    let name = required(doc, node, "name")?;
+    let datatype = required(doc, node, "datatype")?;
+    let field_type = parse_datatype(datatype).ok_or_else(|| {
+        error(
+            doc,
+            node,
+            format!(
+                "unknown datatype '{}' for field '{}' in container '{}'",
+                datatype, name, container
+            ),
+        )
+    })?;
+
+    let mut field = FieldDef::new(name, field_type);
+    field.is_list = parse_bool(doc, node, "array")?;
+    field.is_required = parse_bool(doc, node, "required")?;
+    field.struct_name = node.attribute("struct_type").map(|s| s.to_string());
+    if let Some(created) = node.attribute("created") {
+        field.introduced_in = created.parse::<u16>().map_err(|_| {
+            error(
+                doc,
+                node,
+                format!("invalid version '{}' for field '{}'", created, name),
+            )
+        })?;
+    }
+
+    match (field.is_struct(), &field.struct_name) {
+        (true, None) => Err(error(
+            doc,
+            node,
+            format!(
+                "missing struct_type for struct field '{}' in container '{}'",
+                name, container
+            ),
+        )),
+        (false, Some(_)) => Err(error(
+            doc,
+            node,
+            format!(
+                "struct_type is only allowed on struct fields, field '{}' in container '{}'",
+                name, container
+            ),
+        )),
+        _ => Ok(field),
+    }
+}
+
+fn parse_datatype(datatype: &str) -> Option<FieldType> {
+    match datatype.to_ascii_lowercase().as_str() {
+        "unknown" => None,
+        "objectid" => Some(FieldType::ObjectId),
+        "boolean" => Some(FieldType::Bool),
+        s => s.parse().ok(),
+    }
+}
+
+fn parse_bool(doc: &roxmltree::Document, node: roxmltree::Node, name: &str) -> Result<bool, Error> {
+    match node.attribute(name) {
+        None | Some("false") | Some("0") => Ok(false),
+        Some("true") | Some("1") => Ok(true),
+        Some(v) => Err(error(
+            doc,
+            node,
+            format!("invalid boolean '{}' for attribute '{}'", v, name),
+        )),
+    }
+}
+
+fn required<'a>(
+    doc: &roxmltree::Document,
+    node: roxmltree::Node<'a, '_>,
+    name: &str,
+) -> Result<&'a str, Error> {
+    node.attribute(name).ok_or_else(|| {
+        error(
+            doc,
+            node,
+            format!(
+                "missing attribute '{}' on <{}>",
+                name,
+                node.tag_name().name()
+            ),
+        )
+    })
+}
+
+fn error(doc: &roxmltree::Document, node: roxmltree::Node, msg: String) -> Error {
+    let pos = doc.text_pos_at(node.range().start);
+    Error::SchemaError(format!("Invalid schema at line {}, {}", pos.row, msg))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    const MTX_DEFS: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<data_container_definitions>
+  <container name="MtxBalanceInfo" key="330">
+    <field name="TemplateId" datatype="uint32" required="true"/>
+    <field name="ResourceId" datatype="uint32"/>
+    <field name="ClassId" datatype="uint32" created="5200"/>
+    <field name="Amount" datatype="decimal" created="5300"/>
+  </container>
+  <container name="MtxResponseWallet" key="263">
+    <field name="Result" datatype="uint32"/>
+    <field name="BalanceArray" datatype="struct" struct_type="MtxBalanceInfo" array="true"/>
+    <field name="ExternalId" datatype="string"/>
+  </container>
+</data_container_definitions>
+"#;
+
+    const CUSTOM_DEFS: &str = r#"<data_container_definitions>
+  <container name="CustomBalanceInfo" key="5001" base="MtxBalanceInfo" extension="true">
+    <field name="Region" datatype="string" created="1"/>
+    <field name="Tags" datatype="string" array="true" created="2"/>
+    <field name="Owner" datatype="ObjectId" created="2"/>
+  </container>
+</data_container_definitions>
+"#;
+
+    #[test]
+    fn test_load_xml() {
+        let mut registry = SchemaRegistry::new();
+        registry.load_xml(MTX_DEFS).unwrap();
+        registry.check().unwrap();
+
+        assert_eq!(registry.len(), 2);
+        let wallet = registry.get_by_name("MtxResponseWallet").unwrap();
+        assert_eq!(wallet.key, 263);
+        assert_eq!(wallet.fields.len(), 3);
+
+        let balances = &wallet.fields[1];
+        assert_eq!(balances.name, "BalanceArray");
+        assert_eq!(balances.field_type, FieldType::Struct);
+        assert!(balances.is_list);
+        assert_eq!(balances.struct_name.as_deref(), Some("MtxBalanceInfo"));
+
+        let schema = registry.lookup(330, 5222, 2).unwrap();
+        assert_eq!(schema.fields.len(), 3);
+        assert_eq!(schema.fields[2].introduced_in, 5200);
+        assert!(schema.fields[0].is_required);
+        assert!(!schema.fields[1].is_required);
+    }
+
+    #[test]
+    fn test_load_xml_extension() {
+        let mut registry = SchemaRegistry::new();
+        registry.load_xml(MTX_DEFS).unwrap();
+        registry.load_xml(CUSTOM_DEFS).unwrap();
+        registry.check().unwrap();
+
+        let custom = registry.get(5001).unwrap();
+        assert_eq!(custom.name, "CustomBalanceInfo");
+        assert_eq!(custom.base.as_deref(), Some("MtxBalanceInfo"));
+        assert!(custom.is_extension);
+        assert_eq!(custom.fields[1].field_type, FieldType::String);
+        assert!(custom.fields[1].is_list);
+        assert_eq!(custom.fields[2].field_type, FieldType::ObjectId);
+
+        // Base fields follow the schema version, extension fields the
+        // extension version
+        let schema = registry.lookup(5001, 5222, 1).unwrap();
+        assert_eq!(schema.fields.len(), 4);
+        assert_eq!(schema.field_index("Region"), Some(3));
+        let schema = registry.lookup(5001, 5300, 2).unwrap();
+        assert_eq!(schema.fields.len(), 7);
+    }
+
+    #[test]
+    fn test_load_xml_files() {
+        let dir = std::env::temp_dir().join(format!("rust_mdd_xml_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let custom = dir.join("custom.xml");
+        let mtx = dir.join("mtx.xml");
+        std::fs::File::create(&custom)
+            .unwrap()
+            .write_all(CUSTOM_DEFS.as_bytes())
+            .unwrap();
+        std::fs::File::create(&mtx)
+            .unwrap()
+            .write_all(MTX_DEFS.as_bytes())
+            .unwrap();
+
+        // Files can refer to containers defined in files loaded later
+        let registry = SchemaRegistry::from_xml_files(&[&custom, &mtx]).unwrap();
+        assert_eq!(registry.len(), 3);
+
+        let err = SchemaRegistry::from_xml_files(&[&custom]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Unknown base container 'MtxBalanceInfo' for container 'CustomBalanceInfo'"
+        );
+
+        let err = SchemaRegistry::from_xml_files(&[dir.join("missing.xml")]).unwrap_err();
+        assert!(matches!(err, Error::IoError(_)));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_load_invalid_xml() {
+        let cases = [
+            (
+                "<defs><container name=\"A\" key=\"1\">",
+                "Invalid XML, the root node was opened but never closed",
+            ),
+            (
+                "<defs>\n<container key=\"1\"/></defs>",
+                "Invalid schema at line 2, missing attribute 'name' on <container>",
+            ),
+            (
+                "<defs>\n<container name=\"A\" key=\"x\"/></defs>",
+                "Invalid schema at line 2, invalid key 'x' for container 'A'",
+            ),
+            (
+                "<defs>\n<box/></defs>",
+                "Invalid schema at line 2, unexpected element <box>",
+            ),
+            (
+                "<defs><container name=\"A\" key=\"1\">\n<field name=\"B\" datatype=\"float\"/></container></defs>",
+                "Invalid schema at line 2, unknown datatype 'float' for field 'B' in container 'A'",
+            ),
+            (
+                "<defs><container name=\"A\" key=\"1\">\n<field name=\"B\" datatype=\"struct\"/></container></defs>",
+                "Invalid schema at line 2, missing struct_type for struct field 'B' in container 'A'",
+            ),
+            (
+                "<defs><container name=\"A\" key=\"1\">\n<field name=\"B\" datatype=\"string\" array=\"yes\"/></container></defs>",
+                "Invalid schema at line 2, invalid boolean 'yes' for attribute 'array'",
+            ),
+            (
+                "<defs><container name=\"A\" key=\"1\">\n<field name=\"B\" datatype=\"string\" created=\"-1\"/></container></defs>",
+                "Invalid schema at line 2, invalid version '-1' for field 'B'",
+            ),
+            (
+                "<defs><container name=\"A\" key=\"1\"/>\n<container name=\"B\" key=\"1\"/></defs>",
+                "Invalid schema at line 2, Duplicate container key 1",
+            ),
+        ];
+
+        for (xml, expected) in cases {
+            let mut registry = SchemaRegistry::new();
+            let err = registry.load_xml(xml).unwrap_err();
+            assert_eq!(err.to_string(), expected);
+        }
+    }
+}
@@ -0,0 +1,297 @@
+use super::value::is_blank;
+use super::value::unexpected_text;
+use super::value::XML_VALUE_CODEC;
+use super::XmlCodec;
+use crate::error::Error;
+use crate::mdd::Container;
+use crate::mdd::Containers;
+use crate::mdd::Value;
+use crate::named::NamedCodec;
+use crate::named::NamedFields;
+use crate::named::ValueCodec;
+use roxmltree::Node;
+
+/// Document element holding several containers.
+pub(crate) const CONTAINERS: &str = "Containers";
+
+/// Attribute of a container element whose header depth is not its nesting
+/// depth.
+pub(crate) const DEPTH: &str = "depth";
+
+impl XmlCodec<'_> {
+    /// Decode a container element, or the containers of a `<Containers>`
+    /// element.
+    pub fn decode_containers<'a>(&self, data: &'a [u8]) -> Result<Containers<'a>, Error> {
+        let xml = std::str::from_utf8(data)?;
+        let doc = roxmltree::Document::parse(xml)
+            .map_err(|e| Error::DecodeError(format!("Invalid XML, {}", e)))?;
+        let root = doc.root_element();
+        match root.tag_name().name() {
+            CONTAINERS => self.decode_elements(data, root, None, 0),
+            _ => Ok(Containers {
+                containers: vec![self.decode_element(data, root, None, 0)?],
+            }),
+        }
+    }
+
+    /// The container elements in the element of a struct field.
+    fn decode_elements<'a>(
+        &self,
+        data: &'a [u8],
+        node: Node,
+        declared: Option<&str>,
+        depth: i8,
+    ) -> Result<Containers<'a>, Error> {
+        let mut containers = vec![];
+        for child in elements(node)? {
+            containers.push(self.decode_element(data, child, declared, depth)?);
+        }
+        Ok(Containers { containers })
+    }
+
+    /// The elements of a list of structs, `<null/>` elements are empty and
+    /// elements of several containers are `<Containers>` elements.
+    fn decode_element_list<'a>(
+        &self,
+        data: &'a [u8],
+        node: Node,
+        declared: Option<&str>,
+        depth: i8,
+    ) -> Result<Value<'a>, Error> {
+        let mut values = vec![];
+        for child in elements(node)? {
+            let containers = match child.tag_name().name() {
+                "null" => {
+                    values.push(None);
+                    continue;
+                }
+                CONTAINERS => self.decode_elements(data, child, declared, depth)?,
+                _ => Containers {
+                    containers: vec![self.decode_element(data, child, declared, depth)?],
+                },
+            };
+            values.push(Some(Value::Struct(containers)));
+        }
+        Ok(Value::List(values))
+    }
+
+    fn decode_element<'a>(
+        &self,
+        data: &'a [u8],
+        node: Node,
+        declared: Option<&str>,
+        depth: i8,
+    ) -> Result<Container<'a>, Error> {
+        let schema = self.lookup(node.tag_name().name(), declared)?;
+        let mut fields = NamedFields::new(schema, &XML_VALUE_CODEC);
+        for child in elements(node)? {
+            let field = fields.field(child.tag_name().name())?;
+            let def = field.def;
+            if def.is_struct() {
+                let depth = depth.checked_add(1).ok_or_else(|| {
+                    Error::DecodeError(format!(
+                        "Invalid XML, containers nested deeper than {}",
+                        i8::MAX
+                    ))
+                })?;
+                let declared = def.struct_name.as_deref();
+                field.set_value(match def.is_list {
+                    true => self.decode_element_list(data, child, declared, depth),
+                    false => self
+                        .decode_elements(data, child, declared, depth)
+                        .map(Value::Struct),
+                })?;
+                continue;
+            }
+            match content(data, child) {
+                // Nothing to keep for empty elements, such as empty strings
+                b"" => field.set_value(match def.is_list {
+                    true => Ok(Value::List(vec![])),
+                    false => XML_VALUE_CODEC.decode_value(&def.field_type, b""),
+                })?,
+                content => field.set_data(content),
+            }
+        }
+        let header_depth = match node.attribute(DEPTH) {
+            Some(value) => value.parse().map_err(|_| {
+                Error::DecodeError(format!("Invalid XML container depth '{}'", value))
+            })?,
+            None => depth,
+        };
+        fields.finish(header_depth)
+    }
+}
+
+/// The child elements, text other than whitespace is not expected.
+fn elements<'a, 'input>(node: Node<'a, 'input>) -> Result<Vec<Node<'a, 'input>>, Error> {
+    let mut elements = vec![];
+    for child in node.children() {
+        match child.node_type() {
+            roxmltree::NodeType::Element => elements.push(child),
+            roxmltree::NodeType::Text if !is_blank(child) => {
+                return Err(unexpected_text(child));
+            }
+            _ => {}
+        }
+    }
+    Ok(elements)
+}
+
+/// The raw content of an element, between its start and end tags.
+fn content<'a>(data: &'a [u8], node: Node) -> &'a [u8] {
+    match (node.first_child(), node.last_child()) {
+        (Some(first), Some(last)) => &data[first.range().start..last.range().end],
+        _ => b"",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codec::Codec;
+    use crate::mdd::FieldType;
+    use crate::schema::ContainerDef;
+    use crate::schema::FieldDef;
+    use crate::schema::SchemaRegistry;
+
+    fn registry() -> SchemaRegistry {
+        let mut registry = SchemaRegistry::new();
+        registry
+            .add(
+                ContainerDef::new("MtxBalanceInfo", 330)
+                    .field(FieldDef::new("TemplateId", FieldType::UInt32))
+                    .field(FieldDef::new("Name", FieldType::String)),
+            )
+            .unwrap();
+        registry
+            .add(
+                ContainerDef::new("MtxResponseWallet", 263)
+                    .field(FieldDef::new("Result", FieldType::UInt32))
+                    .field(FieldDef::new_struct("BalanceArray", "MtxBalanceInfo").list())
+                    .field(FieldDef::new("Flags", FieldType::Bool).list()),
+            )
+            .unwrap();
+        registry
+    }
+
+    #[test]
+    fn test_decode_element() {
+        let registry = registry();
+        let codec = XmlCodec::new(&registry, 5222, 2);
+        let data = br#"<?xml version="1.0"?>
+<MtxResponseWallet>
+  <!-- wallet -->
+  <Flags><value>true</value><null/></Flags>
+  <BalanceArray>
+    <MtxBalanceInfo><Name>a &amp; b</Name></MtxBalanceInfo>
+    <null/>
+  </BalanceArray>
+</MtxResponseWallet>"#;
+        let containers = codec.decode_containers(data).unwrap();
+        assert_eq!(containers.containers.len(), 1);
+        let container = &containers.containers[0];
+        assert_eq!(container.header.key, 263);
+        assert_eq!(container.header.total_field, 3);
+        assert!(container.fields[0].is_null);
+
+        let balances = container.fields[1].get_value().unwrap().unwrap();
+        let balances = balances.as_list().unwrap();
+        assert_eq!(balances.len(), 2);
+        assert!(balances[1].is_none());
+        let balance = &balances[0]
+            .as_ref()
+            .unwrap()
+            .as_struct()
+            .unwrap()
+            .containers[0];
+        assert_eq!(balance.header.depth, 1);
+        assert_eq!(balance.fields[1].data, b"a &amp; b");
+        let name = XML_VALUE_CODEC.decode_field(&balance.fields[1]).unwrap();
+        assert_eq!(name.as_string(), Some("a & b"));
+
+        let flags = &container.fields[2];
+        assert_eq!(flags.data, b"<value>true</value><null/>");
+        let flags = XML_VALUE_CODEC.decode_field(flags).unwrap();
+        let flags = flags.as_list().unwrap();
+        assert_eq!(flags[0].as_ref().unwrap().as_bool(), Some(true));
+        assert!(flags[1].is_none());
+    }
+
+    #[test]
+    fn test_decode_containers() {
+        let registry = registry();
+        let codec = XmlCodec::new(&registry, 5222, 2);
+        let data =
+            b"<Containers><MtxBalanceInfo><Name/></MtxBalanceInfo><MtxBalanceInfo/></Containers>";
+        let containers = codec.decode_containers(data).unwrap();
+        assert_eq!(containers.containers.len(), 2);
+        let name = &containers.containers[0].fields[1];
+        assert!(!name.is_null);
+        let name = name.get_value().unwrap().unwrap();
+        assert_eq!(name.as_string(), Some(""));
+        assert!(containers.containers[1].fields.is_empty());
+        assert_eq!(containers.containers[1].header.total_field, 2);
+    }
+
+    #[test]
+    fn test_decode_invalid() {
+        let registry = registry();
+        let codec = XmlCodec::new(&registry, 5222, 2);
+        let cases: &[(&[u8], &str)] = &[
+            (
+                b"<MtxBalanceInfo>",
+                "Invalid XML, the root node was opened but never closed",
+            ),
+            (b"<MtxRequest/>", "Unknown container 'MtxRequest'"),
+            (
+                b"<MtxBalanceInfo><Id>1</Id></MtxBalanceInfo>",
+                "Unknown field 'Id' in container 'MtxBalanceInfo'",
+            ),
+            (
+                b"<MtxBalanceInfo><Name/><Name>a</Name></MtxBalanceInfo>",
+                "Duplicate field 'Name' in container 'MtxBalanceInfo'",
+            ),
+            (
+                b"<MtxBalanceInfo>a<Name/></MtxBalanceInfo>",
+                "Invalid XML, unexpected text 'a'",
+            ),
+            (
+                b"<MtxBalanceInfo><TemplateId/></MtxBalanceInfo>",
+                "Invalid field 'TemplateId' in container 'MtxBalanceInfo', Invalid XML uint32 value ''",
+            ),
+            (
+                b"<MtxResponseWallet><BalanceArray><MtxResponseWallet/></BalanceArray></MtxResponseWallet>",
+                "Invalid field 'BalanceArray' in container 'MtxResponseWallet', Container 'MtxResponseWallet' is not a 'MtxBalanceInfo'",
+            ),
+            (
+                br#"<MtxBalanceInfo depth="-129"/>"#,
+                "Invalid XML container depth '-129'",
+            ),
+        ];
+        for (data, expected) in cases {
+            let err = codec.decode_containers(data).unwrap_err();
+            assert_eq!(err.to_string(), *expected);
+        }
+    }
+
+    #[test]
+    fn test_decode_nesting_limit() {
+        let mut registry = SchemaRegistry::new();
+        registry
+            .add(ContainerDef::new("Node", 500).field(FieldDef::new_struct("Child", "Node")))
+            .unwrap();
+        let codec = XmlCodec::new(&registry, 5222, 2);
+
+        // Containers nested deeper than the i8 header depth
+        let data = format!(
+            "{}<Node/>{}",
+            "<Node><Child>".repeat(130),
+            "</Child></Node>".repeat(130)
+        );
+        let err = codec.decode(data.as_bytes()).unwrap_err();
+        assert!(matches!(err, Error::DecodeError(_)));
+        assert!(err
+            .to_string()
+            .ends_with("Invalid field 'Child' in container 'Node', Invalid XML, containers nested deeper than 127"));
+    }
+}
@@ -0,0 +1,137 @@
+use super::decode::CONTAINERS;
+use super::decode::DEPTH;
+use super::value::XmlValueCodec;
+use super::value::XML_VALUE_CODEC;
+use super::XmlCodec;
+use crate::error::Error;
+use crate::mdd::Containers;
+use crate::named::NamedCodec;
+use crate::schema::SchemaRegistry;
+
+impl XmlCodec<'_> {
+    /// Encode a single container as the document element, other numbers of
+    /// containers in a `<Containers>` element.
+    pub fn encode_containers(
+        &self,
+        buffer: &mut Vec<u8>,
+        containers: &Containers,
+    ) -> Result<(), Error> {
+        self.encode_containers_at(buffer, containers, 0)
+    }
+}
+
+impl NamedCodec for XmlCodec<'_> {
+    type Values = XmlValueCodec;
+
+    const LIST: [&'static [u8]; 4] = [b"", b"", b"<null/>", b""];
+
+    fn values(&self) -> &'static XmlValueCodec {
+        &XML_VALUE_CODEC
+    }
+
+    fn registry(&self) -> &SchemaRegistry {
+        self.registry
+    }
+
+    fn versions(&self) -> (u16, u16) {
+        (self.schema_version, self.ext_version)
+    }
+
+    fn start_container(&self, buffer: &mut Vec<u8>, name: &str, depth: Option<i8>) {
+        match depth {
+            Some(depth) => {
+                buffer.push(b'<');
+                buffer.extend_from_slice(name.as_bytes());
+                buffer.extend_from_slice(format!(" {}=\"{}\">", DEPTH, depth).as_bytes());
+            }
+            None => start_tag(buffer, name),
+        }
+    }
+
+    fn end_container(&self, buffer: &mut Vec<u8>, name: &str) {
+        end_tag(buffer, name);
+    }
+
+    fn start_field(&self, buffer: &mut Vec<u8>, name: &str) {
+        start_tag(buffer, name);
+    }
+
+    fn end_field(&self, buffer: &mut Vec<u8>, name: &str) {
+        end_tag(buffer, name);
+    }
+
+    fn encode_containers_at(
+        &self,
+        buffer: &mut Vec<u8>,
+        containers: &Containers,
+        depth: i8,
+    ) -> Result<(), Error> {
+        match containers.containers.as_slice() {
+            [container] => self.encode_container(buffer, container, depth),
+            containers => {
+                start_tag(buffer, CONTAINERS);
+                for container in containers {
+                    self.encode_container(buffer, container, depth)?;
+                }
+                end_tag(buffer, CONTAINERS);
+                Ok(())
+            }
+        }
+    }
+
+    fn encode_struct(
+        &self,
+        buffer: &mut Vec<u8>,
+        containers: &Containers,
+        depth: i8,
+    ) -> Result<(), Error> {
+        for container in &containers.containers {
+            self.encode_container(buffer, container, depth)?;
+        }
+        Ok(())
+    }
+}
+
+fn start_tag(buffer: &mut Vec<u8>, name: &str) {
+    buffer.push(b'<');
+    buffer.extend_from_slice(name.as_bytes());
+    buffer.push(b'>');
+}
+
+fn end_tag(buffer: &mut Vec<u8>, name: &str) {
+    buffer.extend_from_slice(b"</");
+    buffer.extend_from_slice(name.as_bytes());
+    buffer.push(b'>');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmdc::CMDC_CODEC;
+    use crate::codec::Codec;
+    use crate::named::tests::registry;
+
+    #[test]
+    fn test_xml_round_trip() {
+        let registry = registry();
+        let codec = XmlCodec::new(&registry, 5222, 2);
+        let data = "<MtxResponseWallet><Result>2</Result><BalanceArray><MtxBalanceInfo><TemplateId>200</TemplateId><Amount>-1258291032.242187000</Amount><Updated>2021-09-07T08:00:25.000000Z</Updated></MtxBalanceInfo><null/><MtxBalanceInfo><Name>Tab\t&lt;quoted&gt; &amp; ü&#13;</Name></MtxBalanceInfo></BalanceArray><ExternalId></ExternalId><Flags><value>true</value><null/><value>false</value></Flags><Balance><MtxBalanceInfo><Amount>1E+3</Amount></MtxBalanceInfo></Balance></MtxResponseWallet>";
+        let containers = codec.decode(data.as_bytes()).unwrap();
+        let encoded = codec.encode(&containers).unwrap();
+        assert_eq!(String::from_utf8(encoded).unwrap(), data);
+    }
+
+    #[test]
+    fn test_encode_invalid_text() {
+        let registry = registry();
+        let codec = XmlCodec::new(&registry, 5222, 2);
+        let containers = CMDC_CODEC
+            .decode(b"<1,2,0,330,5222,2>[,(2:a\x01)]")
+            .unwrap();
+        let err = codec.encode(&containers).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid field 'Name' in container 'MtxBalanceInfo', Character U+0001 cannot be written in XML"
+        );
+    }
+}
@@ -0,0 +1,78 @@
+//! MDD containers as XML documents, with an element per container named
+//! after its definition and an element per field.
+//!
+//! ```xml
+//! <MtxResponseWallet>
+//!   <Result>2</Result>
+//!   <BalanceArray><MtxBalanceInfo><Amount>17485760.0</Amount></MtxBalanceInfo><null/></BalanceArray>
+//!   <Flags><value>true</value><null/></Flags>
+//! </MtxResponseWallet>
+//! ```
+//!
+//! Null fields are left out, an empty element is an empty string. A struct
+//! field holds its container elements, other lists hold `<value>`
+//! elements, and empty list elements are `<null/>`. Several containers, at
+//! the top level or in a list element, are wrapped in a `<Containers>`
+//! element. Containers whose header depth is not their nesting depth keep
+//! it in a `depth` attribute.
+//!
+//! Values are written in their cMDC form, with `true` and `false` for
+//! booleans.
+
+pub mod decode;
+pub mod encode;
+pub mod value;
+
+use crate::codec::Codec;
+use crate::error::Error;
+use crate::mdd::Containers;
+use crate::mdd::Field;
+use crate::mdd::Value;
+use crate::named::NamedCodec;
+use crate::schema::SchemaRegistry;
+use value::XML_VALUE_CODEC;
+
+/// Codec for XML containers, which are matched to their definitions by
+/// element name. Decoded containers get the layout of the given versions.
+#[derive(Debug, Clone)]
+pub struct XmlCodec<'r> {
+    registry: &'r SchemaRegistry,
+    schema_version: u16,
+    ext_version: u16,
+}
+
+impl<'r> XmlCodec<'r> {
+    pub fn new(registry: &'r SchemaRegistry, schema_version: u16, ext_version: u16) -> Self {
+        XmlCodec {
+            registry,
+            schema_version,
+            ext_version,
+        }
+    }
+}
+
+impl Codec for XmlCodec<'_> {
+    fn name(&self) -> &'static str {
+        "xml"
+    }
+
+    fn decode<'a>(&self, data: &'a [u8]) -> Result<Containers<'a>, Error> {
+        self.decode_containers(data)
+    }
+
+    fn encode(&self, containers: &Containers) -> Result<Vec<u8>, Error> {
+        let mut buffer = Vec::new();
+        self.encode_containers(&mut buffer, containers)?;
+        Ok(buffer)
+    }
+
+    /// Struct fields are decoded with their container, as elements of a
+    /// list do not make a document on their own.
+    fn decode_field<'a>(&self, field: &Field<'a>) -> Result<Value<'a>, Error> {
+        XML_VALUE_CODEC.decode_field(field)
+    }
+
+    fn encode_field(&self, field: &Field) -> Result<Vec<u8>, Error> {
+        self.encode_named_field(field)
+    }
+}
@@ -0,0 +1,382 @@
+use crate::codec::Codec;
+use crate::error::Error;
+use crate::mdd::Containers;
+use crate::mdd::Field;
+use crate::mdd::FieldType;
+use crate::mdd::Value;
+use crate::named::decode_value_field;
+use crate::named::encode_value_field;
+use crate::named::ValueCodec;
+use std::borrow::Cow;
+use std::str::FromStr;
+
+/// Reads and writes the content of field elements, `<value>` elements for
+/// lists. Struct fields are left to `XmlCodec`.
+pub(crate) static XML_VALUE_CODEC: XmlValueCodec = XmlValueCodec {};
+
+#[derive(Debug)]
+pub(crate) struct XmlValueCodec {}
+
+impl Codec for XmlValueCodec {
+    fn name(&self) -> &'static str {
+        "xml"
+    }
+
+    fn decode<'a>(&self, _data: &'a [u8]) -> Result<Containers<'a>, Error> {
+        Err(Error::DecodeError(
+            "XML containers are decoded with a schema by XmlCodec".into(),
+        ))
+    }
+
+    fn encode(&self, _containers: &Containers) -> Result<Vec<u8>, Error> {
+        Err(Error::EncodeError(
+            "XML containers are encoded with a schema by XmlCodec".into(),
+        ))
+    }
+
+    fn decode_field<'a>(&self, field: &Field<'a>) -> Result<Value<'a>, Error> {
+        decode_value_field(self, field)
+    }
+
+    fn encode_field(&self, field: &Field) -> Result<Vec<u8>, Error> {
+        encode_value_field(self, field)
+    }
+}
+
+impl ValueCodec for XmlValueCodec {
+    /// Decode the content of a field element.
+    fn decode_value<'a>(&self, field_type: &FieldType, data: &[u8]) -> Result<Value<'a>, Error> {
+        self.parse_value(field_type, &text(data)?)
+    }
+
+    /// Decode the `<value>` and `<null/>` elements of a list field.
+    fn decode_list<'a>(
+        &self,
+        field_type: &FieldType,
+        data: &[u8],
+    ) -> Result<Vec<Option<Value<'a>>>, Error> {
+        fragment(std::str::from_utf8(data)?, |node| {
+            let mut values = vec![];
+            for child in node.children() {
+                match child.node_type() {
+                    roxmltree::NodeType::Element => {}
+                    roxmltree::NodeType::Text if is_blank(child) => continue,
+                    roxmltree::NodeType::Text => return Err(unexpected_text(child)),
+                    _ => continue,
+                }
+                match child.tag_name().name() {
+                    "value" => {
+                        values.push(Some(self.parse_value(field_type, &element_text(child)?)?))
+                    }
+                    "null" => values.push(None),
+                    name => {
+                        return Err(Error::DecodeError(format!(
+                            "Invalid XML list, unexpected element <{}>",
+                            name
+                        )))
+                    }
+                }
+            }
+            Ok(values)
+        })
+    }
+
+    /// Encode the content of a field element.
+    fn encode_value(&self, field_type: &FieldType, value: &Value) -> Result<Vec<u8>, Error> {
+        if let Value::List(values) = value {
+            return self.encode_list(field_type, values);
+        }
+        let mismatch = || {
+            Error::TypeMismatch(format!(
+                "Value type mismatch, expected {:?} but found {}",
+                field_type,
+                value.type_name()
+            ))
+        };
+        let text = |v: Option<String>| v.map(String::into_bytes).ok_or_else(mismatch);
+        let data = match field_type {
+            FieldType::String => {
+                let mut data = Vec::new();
+                write_text(&mut data, value.as_string().ok_or_else(mismatch)?)?;
+                data
+            }
+            FieldType::Int8 => text(value.as_int8().map(|v| v.to_string()))?,
+            FieldType::Int16 => text(value.as_int16().map(|v| v.to_string()))?,
+            FieldType::Int32 => text(value.as_int32().map(|v| v.to_string()))?,
+            FieldType::Int64 => text(value.as_int64().map(|v| v.to_string()))?,
+            FieldType::UInt8 => text(value.as_uint8().map(|v| v.to_string()))?,
+            FieldType::UInt16 => text(value.as_uint16().map(|v| v.to_string()))?,
+            FieldType::UInt32 => text(value.as_uint32().map(|v| v.to_string()))?,
+            FieldType::UInt64 => text(value.as_uint64().map(|v| v.to_string()))?,
+            FieldType::Bool => text(value.as_bool().map(|v| v.to_string()))?,
+            FieldType::Decimal => text(value.as_decimal().map(|v| v.to_string()))?,
+            FieldType::DateTime => text(value.as_datetime().map(|v| v.to_string()))?,
+            FieldType::Date => text(value.as_date().map(|v| v.to_string()))?,
+            FieldType::Time => text(value.as_time().map(|v| v.to_string()))?,
+            FieldType::ObjectId => text(value.as_object_id().map(|v| v.to_string()))?,
+            FieldType::Struct => {
+                return Err(Error::UnsupportedFieldType(
+                    "XML struct fields are encoded with a schema by XmlCodec".into(),
+                ))
+            }
+            FieldType::Unknown => {
+                return Err(Error::UnsupportedFieldType(
+                    "Unsupported field type Unknown, cannot encode field".into(),
+                ))
+            }
+        };
+        Ok(data)
+    }
+}
+
+impl XmlValueCodec {
+    fn parse_value<'a>(&self, field_type: &FieldType, text: &str) -> Result<Value<'a>, Error> {
+        let invalid = || {
+            Error::DecodeError(format!(
+                "Invalid XML {} value '{}'",
+                field_type.name(),
+                text
+            ))
+        };
+        match field_type {
+            FieldType::String => Ok(Value::String(text.to_string())),
+            FieldType::Int8 => Ok(Value::Int8(number(text).ok_or_else(invalid)?)),
+            FieldType::Int16 => Ok(Value::Int16(number(text).ok_or_else(invalid)?)),
+            FieldType::Int32 => Ok(Value::Int32(number(text).ok_or_else(invalid)?)),
+            FieldType::Int64 => Ok(Value::Int64(number(text).ok_or_else(invalid)?)),
+            FieldType::UInt8 => Ok(Value::UInt8(number(text).ok_or_else(invalid)?)),
+            FieldType::UInt16 => Ok(Value::UInt16(number(text).ok_or_else(invalid)?)),
+            FieldType::UInt32 => Ok(Value::UInt32(number(text).ok_or_else(invalid)?)),
+            FieldType::UInt64 => Ok(Value::UInt64(number(text).ok_or_else(invalid)?)),
+            FieldType::Bool => match text {
+                "true" => Ok(Value::Bool(true)),
+                "false" => Ok(Value::Bool(false)),
+                _ => Err(invalid()),
+            },
+            FieldType::Decimal => Ok(Value::Decimal(number(text).ok_or_else(invalid)?)),
+            FieldType::DateTime => Ok(Value::DateTime(text.parse()?)),
+            FieldType::Date => Ok(Value::Date(text.parse()?)),
+            FieldType::Time => Ok(Value::Time(text.parse()?)),
+            FieldType::ObjectId => Ok(Value::ObjectId(text.parse()?)),
+            FieldType::Struct => Err(Error::UnsupportedFieldType(
+                "XML struct fields are decoded with a schema by XmlCodec".into(),
+            )),
+            FieldType::Unknown => Err(Error::UnsupportedFieldType(
+                "Unsupported field type Unknown, cannot decode field".into(),
+            )),
+        }
+    }
+
+    pub(crate) fn encode_list(
+        &self,
+        field_type: &FieldType,
+        values: &[Option<Value>],
+    ) -> Result<Vec<u8>, Error> {
+        let mut data = Vec::new();
+        for value in values {
+            match value {
+                Some(value) => {
+                    data.extend_from_slice(b"<value>");
+                    data.extend_from_slice(&self.encode_value(field_type, value)?);
+                    data.extend_from_slice(b"</value>");
+                }
+                None => data.extend_from_slice(b"<null/>"),
+            }
+        }
+        Ok(data)
+    }
+}
+
+/// Character data of the content of an element, references and CDATA
+/// sections are resolved.
+fn text(data: &[u8]) -> Result<Cow<'_, str>, Error> {
+    let s = std::str::from_utf8(data)?;
+    if !s.contains(['&', '<']) {
+        return Ok(Cow::Borrowed(s));
+    }
+    fragment(s, element_text).map(Cow::Owned)
+}
+
+/// Parse the content of an element on its own.
+fn fragment<T>(
+    content: &str,
+    f: impl FnOnce(roxmltree::Node) -> Result<T, Error>,
+) -> Result<T, Error> {
+    let xml = format!("<v>{}</v>", content);
+    let doc = roxmltree::Document::parse(&xml)
+        .map_err(|e| Error::DecodeError(format!("Invalid XML, {}", e)))?;
+    f(doc.root_element())
+}
+
+pub(crate) fn element_text(node: roxmltree::Node) -> Result<String, Error> {
+    let mut text = String::new();
+    for child in node.children() {
+        match child.node_type() {
+            roxmltree::NodeType::Text => text.push_str(child.text().unwrap_or_default()),
+            roxmltree::NodeType::Element => {
+                return Err(Error::DecodeError(format!(
+                    "Invalid XML value, unexpected element <{}>",
+                    child.tag_name().name()
+                )))
+            }
+            _ => {}
+        }
+    }
+    Ok(text)
+}
+
+pub(crate) fn is_blank(node: roxmltree::Node) -> bool {
+    node.text().is_none_or(|text| text.trim().is_empty())
+}
+
+pub(crate) fn unexpected_text(node: roxmltree::Node) -> Error {
+    Error::DecodeError(format!(
+        "Invalid XML, unexpected text '{}'",
+        node.text().unwrap_or_default().trim()
+    ))
+}
+
+/// A number as `T`, surrounding whitespace and signs other than `-` are
+/// not accepted.
+fn number<T: FromStr>(text: &str) -> Option<T> {
+    match text.as_bytes().first() {
+        Some(c) if c.is_ascii_digit() || *c == b'-' => text.parse().ok(),
+        _ => None,
+    }
+}
+
+/// Write a string as XML character data. Carriage returns are written as
+/// references to be kept by parsers.
+pub(crate) fn write_text(data: &mut Vec<u8>, s: &str) -> Result<(), Error> {
+    for c in s.chars() {
+        match c {
+            '&' => data.extend_from_slice(b"&amp;"),
+            '<' => data.extend_from_slice(b"&lt;"),
+            '>' => data.extend_from_slice(b"&gt;"),
+            '\r' => data.extend_from_slice(b"&#13;"),
+            '\t' | '\n' => data.push(c as u8),
+            c if (c as u32) < 0x20 || c == '\u{fffe}' || c == '\u{ffff}' => {
+                return Err(Error::EncodeError(format!(
+                    "Character U+{:04X} cannot be written in XML",
+                    c as u32
+                )))
+            }
+            c => {
+                let mut buf = [0; 4];
+                data.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(field_type: FieldType, data: &[u8]) -> Value<'static> {
+        let value = XML_VALUE_CODEC.decode_value(&field_type, data).unwrap();
+        let encoded = XML_VALUE_CODEC.encode_value(&field_type, &value).unwrap();
+        assert_eq!(
+            String::from_utf8_lossy(&encoded),
+            String::from_utf8_lossy(data)
+        );
+        value
+    }
+
+    #[test]
+    fn test_values() {
+        assert_eq!(round_trip(FieldType::Int8, b"-12").as_int8(), Some(-12));
+        let v = round_trip(FieldType::UInt64, b"18446744073709551615");
+        assert_eq!(v.as_uint64(), Some(u64::MAX));
+        assert_eq!(round_trip(FieldType::Bool, b"false").as_bool(), Some(false));
+        let v = round_trip(
+            FieldType::String,
+            "a &lt;b&gt; &amp; c&#13;\n\té".as_bytes(),
+        );
+        assert_eq!(v.as_string(), Some("a <b> & c\r\n\té"));
+        assert_eq!(round_trip(FieldType::String, b"").as_string(), Some(""));
+        let v = round_trip(FieldType::Decimal, b"-1258291032.242187000");
+        assert_eq!(v.as_decimal().unwrap().to_string(), "-1258291032.242187000");
+        round_trip(FieldType::DateTime, b"2021-09-07T08:00:25.123456-05:30");
+        round_trip(FieldType::ObjectId, b"0:1:5:277");
+        round_trip(FieldType::Date, b"2021-10-31");
+        round_trip(FieldType::Time, b"09:13:02.667997+01:00");
+
+        // References and CDATA sections are resolved
+        let v = XML_VALUE_CODEC
+            .decode_value(&FieldType::String, b"<![CDATA[<a>]]>&#x26;<!-- c -->&quot;")
+            .unwrap();
+        assert_eq!(v.as_string(), Some("<a>&\""));
+        let v = XML_VALUE_CODEC
+            .decode_value(&FieldType::Int32, b"&#52;2")
+            .unwrap();
+        assert_eq!(v.as_int32(), Some(42));
+    }
+
+    #[test]
+    fn test_list_value() {
+        let field = Field {
+            data: b"\n  <value>a</value><null/>\n  <value/>\n",
+            field_type: FieldType::String,
+            value: None,
+            codec: Some(&XML_VALUE_CODEC),
+            is_multi: true,
+            is_container: false,
+            is_null: false,
+        };
+        let value = XML_VALUE_CODEC.decode_field(&field).unwrap();
+        let values = value.as_list().unwrap();
+        assert_eq!(values.len(), 3);
+        assert!(values[1].is_none());
+        assert_eq!(values[2].as_ref().unwrap().as_string(), Some(""));
+        let encoded = XML_VALUE_CODEC
+            .encode_value(&FieldType::String, &value)
+            .unwrap();
+        assert_eq!(encoded, b"<value>a</value><null/><value></value>");
+    }
+
+    #[test]
+    fn test_invalid_values() {
+        let cases: [(FieldType, &[u8], &str); 6] = [
+            (FieldType::UInt8, b"256", "Invalid XML uint8 value '256'"),
+            (FieldType::Int32, b" 12", "Invalid XML int32 value ' 12'"),
+            (FieldType::Bool, b"1", "Invalid XML bool value '1'"),
+            (
+                FieldType::Decimal,
+                b"1.2.3",
+                "Invalid XML decimal value '1.2.3'",
+            ),
+            (
+                FieldType::String,
+                b"a<b>c</b>",
+                "Invalid XML value, unexpected element <b>",
+            ),
+            (
+                FieldType::String,
+                b"a &b; c",
+                "Invalid XML, unknown entity reference 'b' at 1:6",
+            ),
+        ];
+        for (field_type, data, expected) in cases {
+            let err = XML_VALUE_CODEC.decode_value(&field_type, data).unwrap_err();
+            assert_eq!(err.to_string(), expected);
+        }
+
+        let err = XML_VALUE_CODEC
+            .decode_list(&FieldType::Int32, b"<value>1</value>2")
+            .unwrap_err();
+        assert_eq!(err.to_string(), "Invalid XML, unexpected text '2'");
+        let err = XML_VALUE_CODEC
+            .decode_list(&FieldType::Int32, b"<item>1</item>")
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid XML list, unexpected element <item>"
+        );
+        let err = XML_VALUE_CODEC
+            .encode_value(&FieldType::String, &Value::String("a\u{1}".into()))
+            .unwrap_err();
+        assert_eq!(err.to_string(), "Character U+0001 cannot be written in XML");
+    }
+}
@@ -0,0 +1,111 @@
+//! Runs the `mdd` command line tool against schema files in a temporary
+//! directory.
+
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::Command;
+use std::process::Output;
+
+const OLD_DEFS: &str = r#"<data_container_definitions>
+  <container name="MtxBalanceInfo" key="330">
+    <field name="TemplateId" datatype="uint32"/>
+    <field name="ResourceId" datatype="uint16"/>
+  </container>
+</data_container_definitions>
+"#;
+
+const COMPATIBLE_DEFS: &str = r#"<data_container_definitions>
+  <container name="MtxBalanceInfo" key="330">
+    <field name="TemplateId" datatype="uint32"/>
+    <field name="ResourceId" datatype="uint32"/>
+    <field name="Amount" datatype="decimal" created="5300"/>
+  </container>
+</data_container_definitions>
+"#;
+
+const CUSTOM_DEFS: &str = r#"<data_container_definitions>
+  <container name="CustomBalanceInfo" key="5001" base="MtxBalanceInfo" extension="true">
+    <field name="Region" datatype="string" array="true"/>
+  </container>
+</data_container_definitions>
+"#;
+
+const BREAKING_DEFS: &str = r#"<data_container_definitions>
+  <container name="MtxBalanceInfo" key="330">
+    <field name="ResourceId" datatype="uint16"/>
+    <field name="TemplateId" datatype="string"/>
+  </container>
+  <container name="CustomBalanceInfo" key="5001" base="MtxBalanceInfo" extension="true">
+    <field name="Region" datatype="string"/>
+  </container>
+</data_container_definitions>
+"#;
+
+fn mdd(args: &[&Path]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_mdd"))
+        .arg("diff")
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn write(dir: &Path, name: &str, contents: &str) -> PathBuf {
+    let path = dir.join(name);
+    std::fs::write(&path, contents).unwrap();
+    path
+}
+
+#[test]
+fn test_diff() {
+    let dir = std::env::temp_dir().join(format!("rust_mdd_cli_{}", std::process::id()));
+    let old_dir = dir.join("old");
+    std::fs::create_dir_all(&old_dir).unwrap();
+    write(&old_dir, "mtx.xml", OLD_DEFS);
+    write(&old_dir, "custom.xml", CUSTOM_DEFS);
+    write(&old_dir, "README", "Not a schema file");
+    let compatible = write(&dir, "compatible.xml", COMPATIBLE_DEFS);
+    let breaking = write(&dir, "breaking.xml", BREAKING_DEFS);
+
+    let output = mdd(&[&old_dir, &old_dir]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "0 changes, 0 breaking\n"
+    );
+
+    let output = mdd(&[&old_dir.join("mtx.xml"), &compatible]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "compatible: MtxBalanceInfo.ResourceId: type changed from uint16 to uint32\n\
+         compatible: MtxBalanceInfo.Amount: field added at position 2\n\
+         2 changes, 0 breaking\n"
+    );
+
+    let output = mdd(&[&old_dir, &breaking]);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "breaking: MtxBalanceInfo.TemplateId: field moved from position 0 to 1\n\
+         breaking: MtxBalanceInfo.TemplateId: type changed from uint32 to string\n\
+         breaking: MtxBalanceInfo.ResourceId: field moved from position 1 to 0\n\
+         breaking: CustomBalanceInfo.TemplateId: field moved from position 0 to 1\n\
+         breaking: CustomBalanceInfo.TemplateId: type changed from uint32 to string\n\
+         breaking: CustomBalanceInfo.ResourceId: field moved from position 1 to 0\n\
+         breaking: CustomBalanceInfo.Region: list changed from true to false\n\
+         7 changes, 7 breaking\n"
+    );
+
+    let output = mdd(&[&old_dir, &dir.join("missing.xml")]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).starts_with("error: "));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_usage() {
+    let output = Command::new(env!("CARGO_BIN_EXE_mdd")).output().unwrap();
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).starts_with("Usage: mdd"));
+}
@@ -0,0 +1,126 @@
+//! Compiles the code generated from `tests/data/bench_schema.toml` and
+//! round-trips the sample payloads of `benches/codec_bench.rs` with it.
+//! `sample_data_1` uses a different layout of container -6 than the schema,
+//! so it is only checked to be rejected.
+
+use rust_mdd::cmdc::CMDC_CODEC;
+use rust_mdd::codec::Codec;
+use rust_mdd::container::MddContainer;
+use rust_mdd::mdd::Container;
+use rust_mdd::mdd::Containers;
+
+#[allow(dead_code)]
+mod generated {
+    include!("data/bench_generated.rs");
+}
+
+use generated::*;
+
+#[derive(Debug, PartialEq)]
+#[allow(clippy::large_enum_variant)]
+enum Message {
+    SessionContext(MtxSessionContext),
+    RouteInfo(MtxRouteInfo),
+    ResponseWallet(MtxResponseWallet),
+    ResponseSubscriber(MtxResponseSubscriber),
+    SubscriberObject(MtxSubscriberObject),
+}
+
+impl Message {
+    fn from_container(container: &Container) -> Message {
+        match container.header.key {
+            -6 => Message::SessionContext(MtxSessionContext::from_container(container).unwrap()),
+            -5 => Message::RouteInfo(MtxRouteInfo::from_container(container).unwrap()),
+            263 => Message::ResponseWallet(MtxResponseWallet::from_container(container).unwrap()),
+            626 => Message::ResponseSubscriber(
+                MtxResponseSubscriber::from_container(container).unwrap(),
+            ),
+            208 => {
+                Message::SubscriberObject(MtxSubscriberObject::from_container(container).unwrap())
+            }
+            key => panic!("Unexpected container key {}", key),
+        }
+    }
+
+    fn to_container(&self) -> Container<'static> {
+        match self {
+            Message::SessionContext(v) => v.to_container(),
+            Message::RouteInfo(v) => v.to_container(),
+            Message::ResponseWallet(v) => v.to_container(),
+            Message::ResponseSubscriber(v) => v.to_container(),
+            Message::SubscriberObject(v) => v.to_container(),
+        }
+        .unwrap()
+    }
+}
+
+fn decode(data: &[u8]) -> Vec<Message> {
+    let containers = CMDC_CODEC.decode(data).unwrap();
+    containers
+        .containers
+        .iter()
+        .map(Message::from_container)
+        .collect()
+}
+
+fn round_trip(data: &[u8]) -> Vec<Message> {
+    let messages = decode(data);
+    let containers = Containers {
+        containers: messages.iter().map(Message::to_container).collect(),
+    };
+    let encoded = CMDC_CODEC.encode(&containers).unwrap();
+    assert_eq!(decode(&encoded), messages);
+    messages
+}
+
+#[test]
+fn test_sample_data_1_schema_mismatch() {
+    // This payload declares 18 fields for key -6 with integers in fields 0
+    // and 1 and a nested container in field 2, while the schema declares 8
+    // fields starting with two strings and a uint8, so it cannot decode into
+    // the generated `MtxSessionContext`.
+    let containers = CMDC_CODEC
+        .decode(b"<1,18,0,-6,5222,2>[1,20,<1,2,0,452,5222,2>[100],4]")
+        .unwrap();
+    let err = MtxSessionContext::from_container(&containers.containers[0]).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Invalid field 'session_id' of 'MtxSessionContext', Invalid string value"
+    );
+}
+
+#[test]
+fn test_round_trip_sample_data_2() {
+    let messages = round_trip(b"<1,8,0,-6,5222,2>[,,2,(5:AMF-1),(4:eMBB),(11:SouthWestUK),1]<1,1,0,-5,5222,2>[1000001]<1,7,0,263,5222,2>[2,{<1,5,1,330,5222,2>[4,200,1,17485760.0,17485824.0]},(6:555555),0,0.0,64.0,200]<1,11,0,626,5222,2>[{1,3,1},,{<1,17,1,624,5222,2>[17485824.0,200,1,(21:Data: Asset + Overage),0:1:5:277,(7:2000000),3,0,,,,,,,,0]},,{(13:HXS0:1:52:409)},{<1,8,1,1000,5222,2>[4,(18:Triple Play Bundle),1,0,,,,0]},{<1,5,1,1277,5222,2>[4,(17:999 - 1200TB Plan),1,<1,6,1,-11,5222,2>[800000.0,1200.0,300000.0,5000000000.0,100000.0,5000000000.0]<1,0,0,1257,5222,2>[]]},,{<1,3,1,1360,5222,2>[,(5:Usage),(5:Usage)]},{<1,2,1,627,5222,2>[(13:HXS0:1:52:408),1]<1,29,0,208,5222,2>[0:1:5:279,(7:1000001),0:1:5:283,,4,0:1:5:278,0:1:5:277,(7:2000000),,,,{<1,14,1,209,5222,2>[,1000,2,1,4,2021-09-07T08:00:25.000000Z,2021-10-07T08:00:25.000000Z,1,0.0,,,-1258291032.242187,,0]},{<1,12,1,567,5222,2>[17485824.0,200,0,0,1,0.0,,1,,1,0]},2021-09-09T16:37:19.000000Z,,(13:HXS0:1:52:409),,,,,1,,,0:1:5:281,,1,2]}]<1,29,0,208,5222,2>[0:1:5:279,(7:1000001),0:1:5:283,,0,0:1:5:280,0:1:5:279,(7:1000001),,,,,,2021-09-09T16:37:19.000000Z,0,(13:HXS0:1:52:408),,,,,1,,,0:1:5:281,,1,1,(26:00000000000000594134:00000)]");
+    assert_eq!(messages.len(), 5);
+
+    let Message::ResponseSubscriber(subscriber) = &messages[3] else {
+        panic!("Not a subscriber response");
+    };
+    let details = &subscriber.plan_array[0].details.0;
+    assert_eq!(details.len(), 2);
+    let AnyMtxPlanDetail::MtxUsageThresholds(thresholds) = &details[0] else {
+        panic!("Not usage thresholds");
+    };
+    assert_eq!(thresholds.data_cap, Some("5000000000.0".parse().unwrap()));
+    assert!(matches!(details[1], AnyMtxPlanDetail::MtxPlanExtension(_)));
+
+    let objects = &subscriber.object_array[0].0;
+    let AnyMtxObject::MtxSubscriberObject(object) = &objects[1] else {
+        panic!("Not a subscriber object");
+    };
+    assert_eq!(object.balance_cycle_array[0].cycle_id, Some(1000));
+}
+
+#[test]
+fn test_round_trip_sample_data_3() {
+    let messages = round_trip(b"<1,8,0,-6,5222,2>[,,,(5:AMF-1),(4:eMBB),(11:SouthWestUK),1]<1,1,0,-5,5222,2>[1000001]<1,7,0,263,5222,2>[2,{<1,5,1,330,5222,2>[200,4,1],<1,5,1,330,5222,2>[202,6,2]},(6:555555)]<1,11,0,626,5222,2>[{1,3,1},,{<1,17,1,624,5222,2>[17485824.0,200,1,(21:Data: Asset + Overage),0:1:5:277,(7:2000000),3,0,,,,,,,,0]},0:1:5:144,{(13:HXS0:1:52:409)}]<1,29,0,208,5222,2>[]");
+    assert_eq!(messages.len(), 5);
+
+    let Message::ResponseWallet(wallet) = &messages[2] else {
+        panic!("Not a wallet response");
+    };
+    assert_eq!(wallet.balance_array.len(), 2);
+    assert_eq!(wallet.balance_array[1].template_id, Some(202));
+    assert_eq!(wallet.external_id.as_deref(), Some("555555"));
+}
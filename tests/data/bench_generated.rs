@@ -0,0 +1,1334 @@
+// @generated by rust_mdd::codegen for schema version 5222, extension version 2.
+// Do not edit.
+
+#[allow(unused_imports)]
+use rust_mdd::bigdecimal::BigDecimal;
+#[allow(unused_imports)]
+use rust_mdd::container::{
+    check_key, from_struct_value, read_field, to_struct_value, ContainerBuilder, MddContainer,
+    Structs,
+};
+#[allow(unused_imports)]
+use rust_mdd::datetime::{Date, DateTime, Time};
+use rust_mdd::error::Error;
+use rust_mdd::mdd::{Container, FieldType, FromValue, ToValue, Value};
+#[allow(unused_imports)]
+use rust_mdd::object_id::ObjectId;
+
+/// `MtxUsageThresholds`, container key -11.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MtxUsageThresholds {
+    /// `DataLimit`
+    pub data_limit: Option<BigDecimal>,
+    /// `VoiceLimit`
+    pub voice_limit: Option<BigDecimal>,
+    /// `SmsLimit`
+    pub sms_limit: Option<BigDecimal>,
+    /// `DataCap`
+    pub data_cap: Option<BigDecimal>,
+    /// `RoamingLimit`
+    pub roaming_limit: Option<BigDecimal>,
+    /// `HardCap`
+    pub hard_cap: Option<BigDecimal>,
+}
+
+impl<'a> MddContainer<'a> for MtxUsageThresholds {
+    const KEY: i32 = -11;
+    const SCHEMA_VERSION: u16 = 5222;
+    const EXT_VERSION: u16 = 2;
+
+    fn from_container(container: &Container<'a>) -> Result<Self, Error> {
+        check_key(container, "MtxUsageThresholds", Self::KEY)?;
+        Ok(MtxUsageThresholds {
+            data_limit: read_field(container, "MtxUsageThresholds", "data_limit", 0, Some("DataLimit"))?,
+            voice_limit: read_field(container, "MtxUsageThresholds", "voice_limit", 1, Some("VoiceLimit"))?,
+            sms_limit: read_field(container, "MtxUsageThresholds", "sms_limit", 2, Some("SmsLimit"))?,
+            data_cap: read_field(container, "MtxUsageThresholds", "data_cap", 3, Some("DataCap"))?,
+            roaming_limit: read_field(container, "MtxUsageThresholds", "roaming_limit", 4, Some("RoamingLimit"))?,
+            hard_cap: read_field(container, "MtxUsageThresholds", "hard_cap", 5, Some("HardCap"))?,
+        })
+    }
+
+    fn to_container(&self) -> Result<Container<'static>, Error> {
+        ContainerBuilder::new(Self::KEY, Self::SCHEMA_VERSION, Self::EXT_VERSION)
+            .total_field(6)
+            .field(0, &self.data_limit)?
+            .field(1, &self.voice_limit)?
+            .field(2, &self.sms_limit)?
+            .field(3, &self.data_cap)?
+            .field(4, &self.roaming_limit)?
+            .field(5, &self.hard_cap)?
+            .build()
+    }
+}
+
+impl<'a> FromValue<'a> for MtxUsageThresholds {
+    fn from_value(value: &Value<'a>) -> Result<Self, Error> {
+        from_struct_value(value)
+    }
+}
+
+impl ToValue for MtxUsageThresholds {
+    fn field_type() -> FieldType {
+        FieldType::Struct
+    }
+
+    fn to_value(&self) -> Result<Option<Value<'static>>, Error> {
+        to_struct_value(self)
+    }
+}
+
+/// `MtxSessionContext`, container key -6.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MtxSessionContext {
+    /// `SessionId`
+    pub session_id: Option<String>,
+    /// `TraceId`
+    pub trace_id: Option<String>,
+    /// `Priority`
+    pub priority: Option<u8>,
+    /// `NetworkFunction`
+    pub network_function: Option<String>,
+    /// `SliceType`
+    pub slice_type: Option<String>,
+    /// `Region`
+    pub region: Option<String>,
+    /// `Status`
+    pub status: Option<u8>,
+    /// `Reserved`
+    pub reserved: Option<String>,
+}
+
+impl<'a> MddContainer<'a> for MtxSessionContext {
+    const KEY: i32 = -6;
+    const SCHEMA_VERSION: u16 = 5222;
+    const EXT_VERSION: u16 = 2;
+
+    fn from_container(container: &Container<'a>) -> Result<Self, Error> {
+        check_key(container, "MtxSessionContext", Self::KEY)?;
+        Ok(MtxSessionContext {
+            session_id: read_field(container, "MtxSessionContext", "session_id", 0, Some("SessionId"))?,
+            trace_id: read_field(container, "MtxSessionContext", "trace_id", 1, Some("TraceId"))?,
+            priority: read_field(container, "MtxSessionContext", "priority", 2, Some("Priority"))?,
+            network_function: read_field(container, "MtxSessionContext", "network_function", 3, Some("NetworkFunction"))?,
+            slice_type: read_field(container, "MtxSessionContext", "slice_type", 4, Some("SliceType"))?,
+            region: read_field(container, "MtxSessionContext", "region", 5, Some("Region"))?,
+            status: read_field(container, "MtxSessionContext", "status", 6, Some("Status"))?,
+            reserved: read_field(container, "MtxSessionContext", "reserved", 7, Some("Reserved"))?,
+        })
+    }
+
+    fn to_container(&self) -> Result<Container<'static>, Error> {
+        ContainerBuilder::new(Self::KEY, Self::SCHEMA_VERSION, Self::EXT_VERSION)
+            .total_field(8)
+            .field(0, &self.session_id)?
+            .field(1, &self.trace_id)?
+            .field(2, &self.priority)?
+            .field(3, &self.network_function)?
+            .field(4, &self.slice_type)?
+            .field(5, &self.region)?
+            .field(6, &self.status)?
+            .field(7, &self.reserved)?
+            .build()
+    }
+}
+
+impl<'a> FromValue<'a> for MtxSessionContext {
+    fn from_value(value: &Value<'a>) -> Result<Self, Error> {
+        from_struct_value(value)
+    }
+}
+
+impl ToValue for MtxSessionContext {
+    fn field_type() -> FieldType {
+        FieldType::Struct
+    }
+
+    fn to_value(&self) -> Result<Option<Value<'static>>, Error> {
+        to_struct_value(self)
+    }
+}
+
+/// `MtxRouteInfo`, container key -5.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MtxRouteInfo {
+    /// `RouteId`
+    pub route_id: Option<u32>,
+}
+
+impl<'a> MddContainer<'a> for MtxRouteInfo {
+    const KEY: i32 = -5;
+    const SCHEMA_VERSION: u16 = 5222;
+    const EXT_VERSION: u16 = 2;
+
+    fn from_container(container: &Container<'a>) -> Result<Self, Error> {
+        check_key(container, "MtxRouteInfo", Self::KEY)?;
+        Ok(MtxRouteInfo {
+            route_id: read_field(container, "MtxRouteInfo", "route_id", 0, Some("RouteId"))?,
+        })
+    }
+
+    fn to_container(&self) -> Result<Container<'static>, Error> {
+        ContainerBuilder::new(Self::KEY, Self::SCHEMA_VERSION, Self::EXT_VERSION)
+            .total_field(1)
+            .field(0, &self.route_id)?
+            .build()
+    }
+}
+
+impl<'a> FromValue<'a> for MtxRouteInfo {
+    fn from_value(value: &Value<'a>) -> Result<Self, Error> {
+        from_struct_value(value)
+    }
+}
+
+impl ToValue for MtxRouteInfo {
+    fn field_type() -> FieldType {
+        FieldType::Struct
+    }
+
+    fn to_value(&self) -> Result<Option<Value<'static>>, Error> {
+        to_struct_value(self)
+    }
+}
+
+/// `MtxObject`, container key 200.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MtxObject {}
+
+impl<'a> MddContainer<'a> for MtxObject {
+    const KEY: i32 = 200;
+    const SCHEMA_VERSION: u16 = 5222;
+    const EXT_VERSION: u16 = 2;
+
+    fn from_container(container: &Container<'a>) -> Result<Self, Error> {
+        check_key(container, "MtxObject", Self::KEY)?;
+        Ok(MtxObject {})
+    }
+
+    fn to_container(&self) -> Result<Container<'static>, Error> {
+        ContainerBuilder::new(Self::KEY, Self::SCHEMA_VERSION, Self::EXT_VERSION)
+            .total_field(0)
+            .build()
+    }
+}
+
+impl<'a> FromValue<'a> for MtxObject {
+    fn from_value(value: &Value<'a>) -> Result<Self, Error> {
+        from_struct_value(value)
+    }
+}
+
+impl ToValue for MtxObject {
+    fn field_type() -> FieldType {
+        FieldType::Struct
+    }
+
+    fn to_value(&self) -> Result<Option<Value<'static>>, Error> {
+        to_struct_value(self)
+    }
+}
+
+/// `MtxObject` or a container derived from it.
+#[derive(Debug, Clone, PartialEq)]
+#[allow(clippy::large_enum_variant, clippy::enum_variant_names)]
+pub enum AnyMtxObject {
+    MtxObject(MtxObject),
+    MtxSubscriberObject(MtxSubscriberObject),
+    MtxDeviceObject(MtxDeviceObject),
+}
+
+impl<'a> MddContainer<'a> for AnyMtxObject {
+    const KEY: i32 = MtxObject::KEY;
+    const SCHEMA_VERSION: u16 = 5222;
+    const EXT_VERSION: u16 = 2;
+
+    fn from_container(container: &Container<'a>) -> Result<Self, Error> {
+        match container.header.key {
+            200 => MtxObject::from_container(container).map(AnyMtxObject::MtxObject),
+            208 => MtxSubscriberObject::from_container(container).map(AnyMtxObject::MtxSubscriberObject),
+            627 => MtxDeviceObject::from_container(container).map(AnyMtxObject::MtxDeviceObject),
+            key => Err(Error::TypeMismatch(format!(
+                "Container key {} is not a 'MtxObject'",
+                key
+            ))),
+        }
+    }
+
+    fn to_container(&self) -> Result<Container<'static>, Error> {
+        match self {
+            AnyMtxObject::MtxObject(v) => v.to_container(),
+            AnyMtxObject::MtxSubscriberObject(v) => v.to_container(),
+            AnyMtxObject::MtxDeviceObject(v) => v.to_container(),
+        }
+    }
+}
+
+impl<'a> FromValue<'a> for AnyMtxObject {
+    fn from_value(value: &Value<'a>) -> Result<Self, Error> {
+        from_struct_value(value)
+    }
+}
+
+impl ToValue for AnyMtxObject {
+    fn field_type() -> FieldType {
+        FieldType::Struct
+    }
+
+    fn to_value(&self) -> Result<Option<Value<'static>>, Error> {
+        to_struct_value(self)
+    }
+}
+
+/// `MtxSubscriberObject`, container key 208.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MtxSubscriberObject {
+    /// `ObjectId`
+    pub object_id: Option<ObjectId>,
+    /// `ExternalId`
+    pub external_id: Option<String>,
+    /// `ParentObjectId`
+    pub parent_object_id: Option<ObjectId>,
+    /// `ContactName`
+    pub contact_name: Option<String>,
+    /// `Status`
+    pub status: Option<u32>,
+    /// `WalletObjectId`
+    pub wallet_object_id: Option<ObjectId>,
+    /// `OwnerObjectId`
+    pub owner_object_id: Option<ObjectId>,
+    /// `OwnerExternalId`
+    pub owner_external_id: Option<String>,
+    /// `FirstName`
+    pub first_name: Option<String>,
+    /// `LastName`
+    pub last_name: Option<String>,
+    /// `Email`
+    pub email: Option<String>,
+    /// `BalanceCycleArray`
+    pub balance_cycle_array: Vec<MtxBalanceCycleInfo>,
+    /// `BalanceAmountArray`
+    pub balance_amount_array: Vec<MtxBalanceAmountInfo>,
+    /// `ActivationTime`
+    pub activation_time: Option<DateTime>,
+    /// `Language`
+    pub language: Option<u32>,
+    /// `SessionId`
+    pub session_id: Option<String>,
+    /// `TimeZone`
+    pub time_zone: Option<String>,
+    /// `BillingCycle`
+    pub billing_cycle: Option<String>,
+    /// `Notes`
+    pub notes: Option<String>,
+    /// `Category`
+    pub category: Option<String>,
+    /// `ContactPreference`
+    pub contact_preference: Option<u32>,
+    /// `PromotionCode`
+    pub promotion_code: Option<String>,
+    /// `ReferralCode`
+    pub referral_code: Option<String>,
+    /// `GroupObjectId`
+    pub group_object_id: Option<ObjectId>,
+    /// `GroupName`
+    pub group_name: Option<String>,
+    /// `DeviceCount`
+    pub device_count: Option<u32>,
+    /// `BillingCycleId`
+    pub billing_cycle_id: Option<u32>,
+    /// `AccountNumber`
+    pub account_number: Option<String>,
+    /// `Comment`
+    pub comment: Option<String>,
+}
+
+impl<'a> MddContainer<'a> for MtxSubscriberObject {
+    const KEY: i32 = 208;
+    const SCHEMA_VERSION: u16 = 5222;
+    const EXT_VERSION: u16 = 2;
+
+    fn from_container(container: &Container<'a>) -> Result<Self, Error> {
+        check_key(container, "MtxSubscriberObject", Self::KEY)?;
+        Ok(MtxSubscriberObject {
+            object_id: read_field(container, "MtxSubscriberObject", "object_id", 0, Some("ObjectId"))?,
+            external_id: read_field(container, "MtxSubscriberObject", "external_id", 1, Some("ExternalId"))?,
+            parent_object_id: read_field(container, "MtxSubscriberObject", "parent_object_id", 2, Some("ParentObjectId"))?,
+            contact_name: read_field(container, "MtxSubscriberObject", "contact_name", 3, Some("ContactName"))?,
+            status: read_field(container, "MtxSubscriberObject", "status", 4, Some("Status"))?,
+            wallet_object_id: read_field(container, "MtxSubscriberObject", "wallet_object_id", 5, Some("WalletObjectId"))?,
+            owner_object_id: read_field(container, "MtxSubscriberObject", "owner_object_id", 6, Some("OwnerObjectId"))?,
+            owner_external_id: read_field(container, "MtxSubscriberObject", "owner_external_id", 7, Some("OwnerExternalId"))?,
+            first_name: read_field(container, "MtxSubscriberObject", "first_name", 8, Some("FirstName"))?,
+            last_name: read_field(container, "MtxSubscriberObject", "last_name", 9, Some("LastName"))?,
+            email: read_field(container, "MtxSubscriberObject", "email", 10, Some("Email"))?,
+            balance_cycle_array: read_field(container, "MtxSubscriberObject", "balance_cycle_array", 11, Some("BalanceCycleArray"))?,
+            balance_amount_array: read_field(container, "MtxSubscriberObject", "balance_amount_array", 12, Some("BalanceAmountArray"))?,
+            activation_time: read_field(container, "MtxSubscriberObject", "activation_time", 13, Some("ActivationTime"))?,
+            language: read_field(container, "MtxSubscriberObject", "language", 14, Some("Language"))?,
+            session_id: read_field(container, "MtxSubscriberObject", "session_id", 15, Some("SessionId"))?,
+            time_zone: read_field(container, "MtxSubscriberObject", "time_zone", 16, Some("TimeZone"))?,
+            billing_cycle: read_field(container, "MtxSubscriberObject", "billing_cycle", 17, Some("BillingCycle"))?,
+            notes: read_field(container, "MtxSubscriberObject", "notes", 18, Some("Notes"))?,
+            category: read_field(container, "MtxSubscriberObject", "category", 19, Some("Category"))?,
+            contact_preference: read_field(container, "MtxSubscriberObject", "contact_preference", 20, Some("ContactPreference"))?,
+            promotion_code: read_field(container, "MtxSubscriberObject", "promotion_code", 21, Some("PromotionCode"))?,
+            referral_code: read_field(container, "MtxSubscriberObject", "referral_code", 22, Some("ReferralCode"))?,
+            group_object_id: read_field(container, "MtxSubscriberObject", "group_object_id", 23, Some("GroupObjectId"))?,
+            group_name: read_field(container, "MtxSubscriberObject", "group_name", 24, Some("GroupName"))?,
+            device_count: read_field(container, "MtxSubscriberObject", "device_count", 25, Some("DeviceCount"))?,
+            billing_cycle_id: read_field(container, "MtxSubscriberObject", "billing_cycle_id", 26, Some("BillingCycleId"))?,
+            account_number: read_field(container, "MtxSubscriberObject", "account_number", 27, Some("AccountNumber"))?,
+            comment: read_field(container, "MtxSubscriberObject", "comment", 28, Some("Comment"))?,
+        })
+    }
+
+    fn to_container(&self) -> Result<Container<'static>, Error> {
+        ContainerBuilder::new(Self::KEY, Self::SCHEMA_VERSION, Self::EXT_VERSION)
+            .total_field(29)
+            .field(0, &self.object_id)?
+            .field(1, &self.external_id)?
+            .field(2, &self.parent_object_id)?
+            .field(3, &self.contact_name)?
+            .field(4, &self.status)?
+            .field(5, &self.wallet_object_id)?
+            .field(6, &self.owner_object_id)?
+            .field(7, &self.owner_external_id)?
+            .field(8, &self.first_name)?
+            .field(9, &self.last_name)?
+            .field(10, &self.email)?
+            .field(11, &self.balance_cycle_array)?
+            .field(12, &self.balance_amount_array)?
+            .field(13, &self.activation_time)?
+            .field(14, &self.language)?
+            .field(15, &self.session_id)?
+            .field(16, &self.time_zone)?
+            .field(17, &self.billing_cycle)?
+            .field(18, &self.notes)?
+            .field(19, &self.category)?
+            .field(20, &self.contact_preference)?
+            .field(21, &self.promotion_code)?
+            .field(22, &self.referral_code)?
+            .field(23, &self.group_object_id)?
+            .field(24, &self.group_name)?
+            .field(25, &self.device_count)?
+            .field(26, &self.billing_cycle_id)?
+            .field(27, &self.account_number)?
+            .field(28, &self.comment)?
+            .build()
+    }
+}
+
+impl<'a> FromValue<'a> for MtxSubscriberObject {
+    fn from_value(value: &Value<'a>) -> Result<Self, Error> {
+        from_struct_value(value)
+    }
+}
+
+impl ToValue for MtxSubscriberObject {
+    fn field_type() -> FieldType {
+        FieldType::Struct
+    }
+
+    fn to_value(&self) -> Result<Option<Value<'static>>, Error> {
+        to_struct_value(self)
+    }
+}
+
+/// `MtxBalanceCycleInfo`, container key 209.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MtxBalanceCycleInfo {
+    /// `Label`
+    pub label: Option<String>,
+    /// `CycleId`
+    pub cycle_id: Option<u32>,
+    /// `PeriodType`
+    pub period_type: Option<u32>,
+    /// `PeriodInterval`
+    pub period_interval: Option<u32>,
+    /// `CycleOffset`
+    pub cycle_offset: Option<u32>,
+    /// `StartTime`
+    pub start_time: Option<DateTime>,
+    /// `EndTime`
+    pub end_time: Option<DateTime>,
+    /// `State`
+    pub state: Option<u32>,
+    /// `Amount`
+    pub amount: Option<BigDecimal>,
+    /// `Currency`
+    pub currency: Option<String>,
+    /// `Description`
+    pub description: Option<String>,
+    /// `Adjustment`
+    pub adjustment: Option<BigDecimal>,
+    /// `Reason`
+    pub reason: Option<String>,
+    /// `Flags`
+    pub flags: Option<u32>,
+}
+
+impl<'a> MddContainer<'a> for MtxBalanceCycleInfo {
+    const KEY: i32 = 209;
+    const SCHEMA_VERSION: u16 = 5222;
+    const EXT_VERSION: u16 = 2;
+
+    fn from_container(container: &Container<'a>) -> Result<Self, Error> {
+        check_key(container, "MtxBalanceCycleInfo", Self::KEY)?;
+        Ok(MtxBalanceCycleInfo {
+            label: read_field(container, "MtxBalanceCycleInfo", "label", 0, Some("Label"))?,
+            cycle_id: read_field(container, "MtxBalanceCycleInfo", "cycle_id", 1, Some("CycleId"))?,
+            period_type: read_field(container, "MtxBalanceCycleInfo", "period_type", 2, Some("PeriodType"))?,
+            period_interval: read_field(container, "MtxBalanceCycleInfo", "period_interval", 3, Some("PeriodInterval"))?,
+            cycle_offset: read_field(container, "MtxBalanceCycleInfo", "cycle_offset", 4, Some("CycleOffset"))?,
+            start_time: read_field(container, "MtxBalanceCycleInfo", "start_time", 5, Some("StartTime"))?,
+            end_time: read_field(container, "MtxBalanceCycleInfo", "end_time", 6, Some("EndTime"))?,
+            state: read_field(container, "MtxBalanceCycleInfo", "state", 7, Some("State"))?,
+            amount: read_field(container, "MtxBalanceCycleInfo", "amount", 8, Some("Amount"))?,
+            currency: read_field(container, "MtxBalanceCycleInfo", "currency", 9, Some("Currency"))?,
+            description: read_field(container, "MtxBalanceCycleInfo", "description", 10, Some("Description"))?,
+            adjustment: read_field(container, "MtxBalanceCycleInfo", "adjustment", 11, Some("Adjustment"))?,
+            reason: read_field(container, "MtxBalanceCycleInfo", "reason", 12, Some("Reason"))?,
+            flags: read_field(container, "MtxBalanceCycleInfo", "flags", 13, Some("Flags"))?,
+        })
+    }
+
+    fn to_container(&self) -> Result<Container<'static>, Error> {
+        ContainerBuilder::new(Self::KEY, Self::SCHEMA_VERSION, Self::EXT_VERSION)
+            .total_field(14)
+            .field(0, &self.label)?
+            .field(1, &self.cycle_id)?
+            .field(2, &self.period_type)?
+            .field(3, &self.period_interval)?
+            .field(4, &self.cycle_offset)?
+            .field(5, &self.start_time)?
+            .field(6, &self.end_time)?
+            .field(7, &self.state)?
+            .field(8, &self.amount)?
+            .field(9, &self.currency)?
+            .field(10, &self.description)?
+            .field(11, &self.adjustment)?
+            .field(12, &self.reason)?
+            .field(13, &self.flags)?
+            .build()
+    }
+}
+
+impl<'a> FromValue<'a> for MtxBalanceCycleInfo {
+    fn from_value(value: &Value<'a>) -> Result<Self, Error> {
+        from_struct_value(value)
+    }
+}
+
+impl ToValue for MtxBalanceCycleInfo {
+    fn field_type() -> FieldType {
+        FieldType::Struct
+    }
+
+    fn to_value(&self) -> Result<Option<Value<'static>>, Error> {
+        to_struct_value(self)
+    }
+}
+
+/// `MtxResponseWallet`, container key 263.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MtxResponseWallet {
+    /// `Result`
+    pub result: Option<u32>,
+    /// `BalanceArray`
+    pub balance_array: Vec<MtxBalanceInfo>,
+    /// `ExternalId`
+    pub external_id: Option<String>,
+    /// `StatusFlags`
+    pub status_flags: Option<i32>,
+    /// `ReservedAmount`
+    pub reserved_amount: Option<BigDecimal>,
+    /// `AvailableAmount`
+    pub available_amount: Option<BigDecimal>,
+    /// `ResourceId`
+    pub resource_id: Option<u32>,
+}
+
+impl<'a> MddContainer<'a> for MtxResponseWallet {
+    const KEY: i32 = 263;
+    const SCHEMA_VERSION: u16 = 5222;
+    const EXT_VERSION: u16 = 2;
+
+    fn from_container(container: &Container<'a>) -> Result<Self, Error> {
+        check_key(container, "MtxResponseWallet", Self::KEY)?;
+        Ok(MtxResponseWallet {
+            result: read_field(container, "MtxResponseWallet", "result", 0, Some("Result"))?,
+            balance_array: read_field(container, "MtxResponseWallet", "balance_array", 1, Some("BalanceArray"))?,
+            external_id: read_field(container, "MtxResponseWallet", "external_id", 2, Some("ExternalId"))?,
+            status_flags: read_field(container, "MtxResponseWallet", "status_flags", 3, Some("StatusFlags"))?,
+            reserved_amount: read_field(container, "MtxResponseWallet", "reserved_amount", 4, Some("ReservedAmount"))?,
+            available_amount: read_field(container, "MtxResponseWallet", "available_amount", 5, Some("AvailableAmount"))?,
+            resource_id: read_field(container, "MtxResponseWallet", "resource_id", 6, Some("ResourceId"))?,
+        })
+    }
+
+    fn to_container(&self) -> Result<Container<'static>, Error> {
+        ContainerBuilder::new(Self::KEY, Self::SCHEMA_VERSION, Self::EXT_VERSION)
+            .total_field(7)
+            .field(0, &self.result)?
+            .field(1, &self.balance_array)?
+            .field(2, &self.external_id)?
+            .field(3, &self.status_flags)?
+            .field(4, &self.reserved_amount)?
+            .field(5, &self.available_amount)?
+            .field(6, &self.resource_id)?
+            .build()
+    }
+}
+
+impl<'a> FromValue<'a> for MtxResponseWallet {
+    fn from_value(value: &Value<'a>) -> Result<Self, Error> {
+        from_struct_value(value)
+    }
+}
+
+impl ToValue for MtxResponseWallet {
+    fn field_type() -> FieldType {
+        FieldType::Struct
+    }
+
+    fn to_value(&self) -> Result<Option<Value<'static>>, Error> {
+        to_struct_value(self)
+    }
+}
+
+/// `MtxBalanceInfo`, container key 330.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MtxBalanceInfo {
+    /// `TemplateId`
+    pub template_id: Option<u32>,
+    /// `ResourceId`
+    pub resource_id: Option<u32>,
+    /// `ClassId`
+    pub class_id: Option<u32>,
+    /// `AvailableAmount`
+    pub available_amount: Option<BigDecimal>,
+    /// `Amount`
+    pub amount: Option<BigDecimal>,
+}
+
+impl<'a> MddContainer<'a> for MtxBalanceInfo {
+    const KEY: i32 = 330;
+    const SCHEMA_VERSION: u16 = 5222;
+    const EXT_VERSION: u16 = 2;
+
+    fn from_container(container: &Container<'a>) -> Result<Self, Error> {
+        check_key(container, "MtxBalanceInfo", Self::KEY)?;
+        Ok(MtxBalanceInfo {
+            template_id: read_field(container, "MtxBalanceInfo", "template_id", 0, Some("TemplateId"))?,
+            resource_id: read_field(container, "MtxBalanceInfo", "resource_id", 1, Some("ResourceId"))?,
+            class_id: read_field(container, "MtxBalanceInfo", "class_id", 2, Some("ClassId"))?,
+            available_amount: read_field(container, "MtxBalanceInfo", "available_amount", 3, Some("AvailableAmount"))?,
+            amount: read_field(container, "MtxBalanceInfo", "amount", 4, Some("Amount"))?,
+        })
+    }
+
+    fn to_container(&self) -> Result<Container<'static>, Error> {
+        ContainerBuilder::new(Self::KEY, Self::SCHEMA_VERSION, Self::EXT_VERSION)
+            .total_field(5)
+            .field(0, &self.template_id)?
+            .field(1, &self.resource_id)?
+            .field(2, &self.class_id)?
+            .field(3, &self.available_amount)?
+            .field(4, &self.amount)?
+            .build()
+    }
+}
+
+impl<'a> FromValue<'a> for MtxBalanceInfo {
+    fn from_value(value: &Value<'a>) -> Result<Self, Error> {
+        from_struct_value(value)
+    }
+}
+
+impl ToValue for MtxBalanceInfo {
+    fn field_type() -> FieldType {
+        FieldType::Struct
+    }
+
+    fn to_value(&self) -> Result<Option<Value<'static>>, Error> {
+        to_struct_value(self)
+    }
+}
+
+/// `MtxResultInfo`, container key 452.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MtxResultInfo {
+    /// `ResultCode`
+    pub result_code: Option<u32>,
+    /// `ResultText`
+    pub result_text: Option<String>,
+}
+
+impl<'a> MddContainer<'a> for MtxResultInfo {
+    const KEY: i32 = 452;
+    const SCHEMA_VERSION: u16 = 5222;
+    const EXT_VERSION: u16 = 2;
+
+    fn from_container(container: &Container<'a>) -> Result<Self, Error> {
+        check_key(container, "MtxResultInfo", Self::KEY)?;
+        Ok(MtxResultInfo {
+            result_code: read_field(container, "MtxResultInfo", "result_code", 0, Some("ResultCode"))?,
+            result_text: read_field(container, "MtxResultInfo", "result_text", 1, Some("ResultText"))?,
+        })
+    }
+
+    fn to_container(&self) -> Result<Container<'static>, Error> {
+        ContainerBuilder::new(Self::KEY, Self::SCHEMA_VERSION, Self::EXT_VERSION)
+            .total_field(2)
+            .field(0, &self.result_code)?
+            .field(1, &self.result_text)?
+            .build()
+    }
+}
+
+impl<'a> FromValue<'a> for MtxResultInfo {
+    fn from_value(value: &Value<'a>) -> Result<Self, Error> {
+        from_struct_value(value)
+    }
+}
+
+impl ToValue for MtxResultInfo {
+    fn field_type() -> FieldType {
+        FieldType::Struct
+    }
+
+    fn to_value(&self) -> Result<Option<Value<'static>>, Error> {
+        to_struct_value(self)
+    }
+}
+
+/// `MtxBalanceAmountInfo`, container key 567.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MtxBalanceAmountInfo {
+    /// `Amount`
+    pub amount: Option<BigDecimal>,
+    /// `ResourceId`
+    pub resource_id: Option<u32>,
+    /// `IsCredit`
+    pub is_credit: Option<u32>,
+    /// `IsPrepaid`
+    pub is_prepaid: Option<u32>,
+    /// `ClassId`
+    pub class_id: Option<u32>,
+    /// `ReservedAmount`
+    pub reserved_amount: Option<BigDecimal>,
+    /// `Currency`
+    pub currency: Option<String>,
+    /// `State`
+    pub state: Option<u32>,
+    /// `Description`
+    pub description: Option<String>,
+    /// `Priority`
+    pub priority: Option<u32>,
+    /// `Flags`
+    pub flags: Option<u32>,
+    /// `Comment`
+    pub comment: Option<String>,
+}
+
+impl<'a> MddContainer<'a> for MtxBalanceAmountInfo {
+    const KEY: i32 = 567;
+    const SCHEMA_VERSION: u16 = 5222;
+    const EXT_VERSION: u16 = 2;
+
+    fn from_container(container: &Container<'a>) -> Result<Self, Error> {
+        check_key(container, "MtxBalanceAmountInfo", Self::KEY)?;
+        Ok(MtxBalanceAmountInfo {
+            amount: read_field(container, "MtxBalanceAmountInfo", "amount", 0, Some("Amount"))?,
+            resource_id: read_field(container, "MtxBalanceAmountInfo", "resource_id", 1, Some("ResourceId"))?,
+            is_credit: read_field(container, "MtxBalanceAmountInfo", "is_credit", 2, Some("IsCredit"))?,
+            is_prepaid: read_field(container, "MtxBalanceAmountInfo", "is_prepaid", 3, Some("IsPrepaid"))?,
+            class_id: read_field(container, "MtxBalanceAmountInfo", "class_id", 4, Some("ClassId"))?,
+            reserved_amount: read_field(container, "MtxBalanceAmountInfo", "reserved_amount", 5, Some("ReservedAmount"))?,
+            currency: read_field(container, "MtxBalanceAmountInfo", "currency", 6, Some("Currency"))?,
+            state: read_field(container, "MtxBalanceAmountInfo", "state", 7, Some("State"))?,
+            description: read_field(container, "MtxBalanceAmountInfo", "description", 8, Some("Description"))?,
+            priority: read_field(container, "MtxBalanceAmountInfo", "priority", 9, Some("Priority"))?,
+            flags: read_field(container, "MtxBalanceAmountInfo", "flags", 10, Some("Flags"))?,
+            comment: read_field(container, "MtxBalanceAmountInfo", "comment", 11, Some("Comment"))?,
+        })
+    }
+
+    fn to_container(&self) -> Result<Container<'static>, Error> {
+        ContainerBuilder::new(Self::KEY, Self::SCHEMA_VERSION, Self::EXT_VERSION)
+            .total_field(12)
+            .field(0, &self.amount)?
+            .field(1, &self.resource_id)?
+            .field(2, &self.is_credit)?
+            .field(3, &self.is_prepaid)?
+            .field(4, &self.class_id)?
+            .field(5, &self.reserved_amount)?
+            .field(6, &self.currency)?
+            .field(7, &self.state)?
+            .field(8, &self.description)?
+            .field(9, &self.priority)?
+            .field(10, &self.flags)?
+            .field(11, &self.comment)?
+            .build()
+    }
+}
+
+impl<'a> FromValue<'a> for MtxBalanceAmountInfo {
+    fn from_value(value: &Value<'a>) -> Result<Self, Error> {
+        from_struct_value(value)
+    }
+}
+
+impl ToValue for MtxBalanceAmountInfo {
+    fn field_type() -> FieldType {
+        FieldType::Struct
+    }
+
+    fn to_value(&self) -> Result<Option<Value<'static>>, Error> {
+        to_struct_value(self)
+    }
+}
+
+/// `MtxPurchasedOfferInfo`, container key 624.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MtxPurchasedOfferInfo {
+    /// `Amount`
+    pub amount: Option<BigDecimal>,
+    /// `ResourceId`
+    pub resource_id: Option<u32>,
+    /// `OfferType`
+    pub offer_type: Option<u32>,
+    /// `Name`
+    pub name: Option<String>,
+    /// `CatalogItemObjectId`
+    pub catalog_item_object_id: Option<ObjectId>,
+    /// `ExternalId`
+    pub external_id: Option<String>,
+    /// `ProductOfferId`
+    pub product_offer_id: Option<u32>,
+    /// `ProductOfferVersion`
+    pub product_offer_version: Option<u32>,
+    /// `StartTime`
+    pub start_time: Option<DateTime>,
+    /// `EndTime`
+    pub end_time: Option<DateTime>,
+    /// `CycleLength`
+    pub cycle_length: Option<u32>,
+    /// `CycleOffset`
+    pub cycle_offset: Option<u32>,
+    /// `Currency`
+    pub currency: Option<String>,
+    /// `Description`
+    pub description: Option<String>,
+    /// `Category`
+    pub category: Option<String>,
+    /// `Status`
+    pub status: Option<u8>,
+    /// `Notes`
+    pub notes: Option<String>,
+}
+
+impl<'a> MddContainer<'a> for MtxPurchasedOfferInfo {
+    const KEY: i32 = 624;
+    const SCHEMA_VERSION: u16 = 5222;
+    const EXT_VERSION: u16 = 2;
+
+    fn from_container(container: &Container<'a>) -> Result<Self, Error> {
+        check_key(container, "MtxPurchasedOfferInfo", Self::KEY)?;
+        Ok(MtxPurchasedOfferInfo {
+            amount: read_field(container, "MtxPurchasedOfferInfo", "amount", 0, Some("Amount"))?,
+            resource_id: read_field(container, "MtxPurchasedOfferInfo", "resource_id", 1, Some("ResourceId"))?,
+            offer_type: read_field(container, "MtxPurchasedOfferInfo", "offer_type", 2, Some("OfferType"))?,
+            name: read_field(container, "MtxPurchasedOfferInfo", "name", 3, Some("Name"))?,
+            catalog_item_object_id: read_field(container, "MtxPurchasedOfferInfo", "catalog_item_object_id", 4, Some("CatalogItemObjectId"))?,
+            external_id: read_field(container, "MtxPurchasedOfferInfo", "external_id", 5, Some("ExternalId"))?,
+            product_offer_id: read_field(container, "MtxPurchasedOfferInfo", "product_offer_id", 6, Some("ProductOfferId"))?,
+            product_offer_version: read_field(container, "MtxPurchasedOfferInfo", "product_offer_version", 7, Some("ProductOfferVersion"))?,
+            start_time: read_field(container, "MtxPurchasedOfferInfo", "start_time", 8, Some("StartTime"))?,
+            end_time: read_field(container, "MtxPurchasedOfferInfo", "end_time", 9, Some("EndTime"))?,
+            cycle_length: read_field(container, "MtxPurchasedOfferInfo", "cycle_length", 10, Some("CycleLength"))?,
+            cycle_offset: read_field(container, "MtxPurchasedOfferInfo", "cycle_offset", 11, Some("CycleOffset"))?,
+            currency: read_field(container, "MtxPurchasedOfferInfo", "currency", 12, Some("Currency"))?,
+            description: read_field(container, "MtxPurchasedOfferInfo", "description", 13, Some("Description"))?,
+            category: read_field(container, "MtxPurchasedOfferInfo", "category", 14, Some("Category"))?,
+            status: read_field(container, "MtxPurchasedOfferInfo", "status", 15, Some("Status"))?,
+            notes: read_field(container, "MtxPurchasedOfferInfo", "notes", 16, Some("Notes"))?,
+        })
+    }
+
+    fn to_container(&self) -> Result<Container<'static>, Error> {
+        ContainerBuilder::new(Self::KEY, Self::SCHEMA_VERSION, Self::EXT_VERSION)
+            .total_field(17)
+            .field(0, &self.amount)?
+            .field(1, &self.resource_id)?
+            .field(2, &self.offer_type)?
+            .field(3, &self.name)?
+            .field(4, &self.catalog_item_object_id)?
+            .field(5, &self.external_id)?
+            .field(6, &self.product_offer_id)?
+            .field(7, &self.product_offer_version)?
+            .field(8, &self.start_time)?
+            .field(9, &self.end_time)?
+            .field(10, &self.cycle_length)?
+            .field(11, &self.cycle_offset)?
+            .field(12, &self.currency)?
+            .field(13, &self.description)?
+            .field(14, &self.category)?
+            .field(15, &self.status)?
+            .field(16, &self.notes)?
+            .build()
+    }
+}
+
+impl<'a> FromValue<'a> for MtxPurchasedOfferInfo {
+    fn from_value(value: &Value<'a>) -> Result<Self, Error> {
+        from_struct_value(value)
+    }
+}
+
+impl ToValue for MtxPurchasedOfferInfo {
+    fn field_type() -> FieldType {
+        FieldType::Struct
+    }
+
+    fn to_value(&self) -> Result<Option<Value<'static>>, Error> {
+        to_struct_value(self)
+    }
+}
+
+/// `MtxResponseSubscriber`, container key 626.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MtxResponseSubscriber {
+    /// `StatusArray`
+    pub status_array: Vec<u8>,
+    /// `ExternalId`
+    pub external_id: Option<String>,
+    /// `PurchasedOfferArray`
+    pub purchased_offer_array: Vec<MtxPurchasedOfferInfo>,
+    /// `SubscriberObjectId`
+    pub subscriber_object_id: Option<ObjectId>,
+    /// `SessionIdArray`
+    pub session_id_array: Vec<String>,
+    /// `BundleArray`
+    pub bundle_array: Vec<MtxBundleInfo>,
+    /// `PlanArray`
+    pub plan_array: Vec<MtxPlanInfo>,
+    /// `Reserved`
+    pub reserved: Option<String>,
+    /// `UsageArray`
+    pub usage_array: Vec<MtxUsageInfo>,
+    /// `ObjectArray`
+    pub object_array: Vec<Structs<AnyMtxObject>>,
+    /// `Comment`
+    pub comment: Option<String>,
+}
+
+impl<'a> MddContainer<'a> for MtxResponseSubscriber {
+    const KEY: i32 = 626;
+    const SCHEMA_VERSION: u16 = 5222;
+    const EXT_VERSION: u16 = 2;
+
+    fn from_container(container: &Container<'a>) -> Result<Self, Error> {
+        check_key(container, "MtxResponseSubscriber", Self::KEY)?;
+        Ok(MtxResponseSubscriber {
+            status_array: read_field(container, "MtxResponseSubscriber", "status_array", 0, Some("StatusArray"))?,
+            external_id: read_field(container, "MtxResponseSubscriber", "external_id", 1, Some("ExternalId"))?,
+            purchased_offer_array: read_field(container, "MtxResponseSubscriber", "purchased_offer_array", 2, Some("PurchasedOfferArray"))?,
+            subscriber_object_id: read_field(container, "MtxResponseSubscriber", "subscriber_object_id", 3, Some("SubscriberObjectId"))?,
+            session_id_array: read_field(container, "MtxResponseSubscriber", "session_id_array", 4, Some("SessionIdArray"))?,
+            bundle_array: read_field(container, "MtxResponseSubscriber", "bundle_array", 5, Some("BundleArray"))?,
+            plan_array: read_field(container, "MtxResponseSubscriber", "plan_array", 6, Some("PlanArray"))?,
+            reserved: read_field(container, "MtxResponseSubscriber", "reserved", 7, Some("Reserved"))?,
+            usage_array: read_field(container, "MtxResponseSubscriber", "usage_array", 8, Some("UsageArray"))?,
+            object_array: read_field(container, "MtxResponseSubscriber", "object_array", 9, Some("ObjectArray"))?,
+            comment: read_field(container, "MtxResponseSubscriber", "comment", 10, Some("Comment"))?,
+        })
+    }
+
+    fn to_container(&self) -> Result<Container<'static>, Error> {
+        ContainerBuilder::new(Self::KEY, Self::SCHEMA_VERSION, Self::EXT_VERSION)
+            .total_field(11)
+            .field(0, &self.status_array)?
+            .field(1, &self.external_id)?
+            .field(2, &self.purchased_offer_array)?
+            .field(3, &self.subscriber_object_id)?
+            .field(4, &self.session_id_array)?
+            .field(5, &self.bundle_array)?
+            .field(6, &self.plan_array)?
+            .field(7, &self.reserved)?
+            .field(8, &self.usage_array)?
+            .field(9, &self.object_array)?
+            .field(10, &self.comment)?
+            .build()
+    }
+}
+
+impl<'a> FromValue<'a> for MtxResponseSubscriber {
+    fn from_value(value: &Value<'a>) -> Result<Self, Error> {
+        from_struct_value(value)
+    }
+}
+
+impl ToValue for MtxResponseSubscriber {
+    fn field_type() -> FieldType {
+        FieldType::Struct
+    }
+
+    fn to_value(&self) -> Result<Option<Value<'static>>, Error> {
+        to_struct_value(self)
+    }
+}
+
+/// `MtxDeviceObject`, container key 627.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MtxDeviceObject {
+    /// `SessionId`
+    pub session_id: Option<String>,
+    /// `IsPrimary`
+    pub is_primary: Option<bool>,
+}
+
+impl<'a> MddContainer<'a> for MtxDeviceObject {
+    const KEY: i32 = 627;
+    const SCHEMA_VERSION: u16 = 5222;
+    const EXT_VERSION: u16 = 2;
+
+    fn from_container(container: &Container<'a>) -> Result<Self, Error> {
+        check_key(container, "MtxDeviceObject", Self::KEY)?;
+        Ok(MtxDeviceObject {
+            session_id: read_field(container, "MtxDeviceObject", "session_id", 0, Some("SessionId"))?,
+            is_primary: read_field(container, "MtxDeviceObject", "is_primary", 1, Some("IsPrimary"))?,
+        })
+    }
+
+    fn to_container(&self) -> Result<Container<'static>, Error> {
+        ContainerBuilder::new(Self::KEY, Self::SCHEMA_VERSION, Self::EXT_VERSION)
+            .total_field(2)
+            .field(0, &self.session_id)?
+            .field(1, &self.is_primary)?
+            .build()
+    }
+}
+
+impl<'a> FromValue<'a> for MtxDeviceObject {
+    fn from_value(value: &Value<'a>) -> Result<Self, Error> {
+        from_struct_value(value)
+    }
+}
+
+impl ToValue for MtxDeviceObject {
+    fn field_type() -> FieldType {
+        FieldType::Struct
+    }
+
+    fn to_value(&self) -> Result<Option<Value<'static>>, Error> {
+        to_struct_value(self)
+    }
+}
+
+/// `MtxBundleInfo`, container key 1000.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MtxBundleInfo {
+    /// `BundleType`
+    pub bundle_type: Option<u32>,
+    /// `Name`
+    pub name: Option<String>,
+    /// `Quantity`
+    pub quantity: Option<u32>,
+    /// `Status`
+    pub status: Option<u32>,
+    /// `StartTime`
+    pub start_time: Option<DateTime>,
+    /// `EndTime`
+    pub end_time: Option<DateTime>,
+    /// `Description`
+    pub description: Option<String>,
+    /// `Priority`
+    pub priority: Option<u32>,
+}
+
+impl<'a> MddContainer<'a> for MtxBundleInfo {
+    const KEY: i32 = 1000;
+    const SCHEMA_VERSION: u16 = 5222;
+    const EXT_VERSION: u16 = 2;
+
+    fn from_container(container: &Container<'a>) -> Result<Self, Error> {
+        check_key(container, "MtxBundleInfo", Self::KEY)?;
+        Ok(MtxBundleInfo {
+            bundle_type: read_field(container, "MtxBundleInfo", "bundle_type", 0, Some("BundleType"))?,
+            name: read_field(container, "MtxBundleInfo", "name", 1, Some("Name"))?,
+            quantity: read_field(container, "MtxBundleInfo", "quantity", 2, Some("Quantity"))?,
+            status: read_field(container, "MtxBundleInfo", "status", 3, Some("Status"))?,
+            start_time: read_field(container, "MtxBundleInfo", "start_time", 4, Some("StartTime"))?,
+            end_time: read_field(container, "MtxBundleInfo", "end_time", 5, Some("EndTime"))?,
+            description: read_field(container, "MtxBundleInfo", "description", 6, Some("Description"))?,
+            priority: read_field(container, "MtxBundleInfo", "priority", 7, Some("Priority"))?,
+        })
+    }
+
+    fn to_container(&self) -> Result<Container<'static>, Error> {
+        ContainerBuilder::new(Self::KEY, Self::SCHEMA_VERSION, Self::EXT_VERSION)
+            .total_field(8)
+            .field(0, &self.bundle_type)?
+            .field(1, &self.name)?
+            .field(2, &self.quantity)?
+            .field(3, &self.status)?
+            .field(4, &self.start_time)?
+            .field(5, &self.end_time)?
+            .field(6, &self.description)?
+            .field(7, &self.priority)?
+            .build()
+    }
+}
+
+impl<'a> FromValue<'a> for MtxBundleInfo {
+    fn from_value(value: &Value<'a>) -> Result<Self, Error> {
+        from_struct_value(value)
+    }
+}
+
+impl ToValue for MtxBundleInfo {
+    fn field_type() -> FieldType {
+        FieldType::Struct
+    }
+
+    fn to_value(&self) -> Result<Option<Value<'static>>, Error> {
+        to_struct_value(self)
+    }
+}
+
+/// `MtxPlanDetail`, container key 1256.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MtxPlanDetail {}
+
+impl<'a> MddContainer<'a> for MtxPlanDetail {
+    const KEY: i32 = 1256;
+    const SCHEMA_VERSION: u16 = 5222;
+    const EXT_VERSION: u16 = 2;
+
+    fn from_container(container: &Container<'a>) -> Result<Self, Error> {
+        check_key(container, "MtxPlanDetail", Self::KEY)?;
+        Ok(MtxPlanDetail {})
+    }
+
+    fn to_container(&self) -> Result<Container<'static>, Error> {
+        ContainerBuilder::new(Self::KEY, Self::SCHEMA_VERSION, Self::EXT_VERSION)
+            .total_field(0)
+            .build()
+    }
+}
+
+impl<'a> FromValue<'a> for MtxPlanDetail {
+    fn from_value(value: &Value<'a>) -> Result<Self, Error> {
+        from_struct_value(value)
+    }
+}
+
+impl ToValue for MtxPlanDetail {
+    fn field_type() -> FieldType {
+        FieldType::Struct
+    }
+
+    fn to_value(&self) -> Result<Option<Value<'static>>, Error> {
+        to_struct_value(self)
+    }
+}
+
+/// `MtxPlanDetail` or a container derived from it.
+#[derive(Debug, Clone, PartialEq)]
+#[allow(clippy::large_enum_variant, clippy::enum_variant_names)]
+pub enum AnyMtxPlanDetail {
+    MtxPlanDetail(MtxPlanDetail),
+    MtxUsageThresholds(MtxUsageThresholds),
+    MtxPlanExtension(MtxPlanExtension),
+}
+
+impl<'a> MddContainer<'a> for AnyMtxPlanDetail {
+    const KEY: i32 = MtxPlanDetail::KEY;
+    const SCHEMA_VERSION: u16 = 5222;
+    const EXT_VERSION: u16 = 2;
+
+    fn from_container(container: &Container<'a>) -> Result<Self, Error> {
+        match container.header.key {
+            1256 => MtxPlanDetail::from_container(container).map(AnyMtxPlanDetail::MtxPlanDetail),
+            -11 => MtxUsageThresholds::from_container(container).map(AnyMtxPlanDetail::MtxUsageThresholds),
+            1257 => MtxPlanExtension::from_container(container).map(AnyMtxPlanDetail::MtxPlanExtension),
+            key => Err(Error::TypeMismatch(format!(
+                "Container key {} is not a 'MtxPlanDetail'",
+                key
+            ))),
+        }
+    }
+
+    fn to_container(&self) -> Result<Container<'static>, Error> {
+        match self {
+            AnyMtxPlanDetail::MtxPlanDetail(v) => v.to_container(),
+            AnyMtxPlanDetail::MtxUsageThresholds(v) => v.to_container(),
+            AnyMtxPlanDetail::MtxPlanExtension(v) => v.to_container(),
+        }
+    }
+}
+
+impl<'a> FromValue<'a> for AnyMtxPlanDetail {
+    fn from_value(value: &Value<'a>) -> Result<Self, Error> {
+        from_struct_value(value)
+    }
+}
+
+impl ToValue for AnyMtxPlanDetail {
+    fn field_type() -> FieldType {
+        FieldType::Struct
+    }
+
+    fn to_value(&self) -> Result<Option<Value<'static>>, Error> {
+        to_struct_value(self)
+    }
+}
+
+/// `MtxPlanExtension`, container key 1257.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MtxPlanExtension {}
+
+impl<'a> MddContainer<'a> for MtxPlanExtension {
+    const KEY: i32 = 1257;
+    const SCHEMA_VERSION: u16 = 5222;
+    const EXT_VERSION: u16 = 2;
+
+    fn from_container(container: &Container<'a>) -> Result<Self, Error> {
+        check_key(container, "MtxPlanExtension", Self::KEY)?;
+        Ok(MtxPlanExtension {})
+    }
+
+    fn to_container(&self) -> Result<Container<'static>, Error> {
+        ContainerBuilder::new(Self::KEY, Self::SCHEMA_VERSION, Self::EXT_VERSION)
+            .total_field(0)
+            .build()
+    }
+}
+
+impl<'a> FromValue<'a> for MtxPlanExtension {
+    fn from_value(value: &Value<'a>) -> Result<Self, Error> {
+        from_struct_value(value)
+    }
+}
+
+impl ToValue for MtxPlanExtension {
+    fn field_type() -> FieldType {
+        FieldType::Struct
+    }
+
+    fn to_value(&self) -> Result<Option<Value<'static>>, Error> {
+        to_struct_value(self)
+    }
+}
+
+/// `MtxPlanInfo`, container key 1277.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MtxPlanInfo {
+    /// `PlanType`
+    pub plan_type: Option<u32>,
+    /// `Name`
+    pub name: Option<String>,
+    /// `Quantity`
+    pub quantity: Option<u32>,
+    /// `Details`
+    pub details: Structs<AnyMtxPlanDetail>,
+    /// `Description`
+    pub description: Option<String>,
+}
+
+impl<'a> MddContainer<'a> for MtxPlanInfo {
+    const KEY: i32 = 1277;
+    const SCHEMA_VERSION: u16 = 5222;
+    const EXT_VERSION: u16 = 2;
+
+    fn from_container(container: &Container<'a>) -> Result<Self, Error> {
+        check_key(container, "MtxPlanInfo", Self::KEY)?;
+        Ok(MtxPlanInfo {
+            plan_type: read_field(container, "MtxPlanInfo", "plan_type", 0, Some("PlanType"))?,
+            name: read_field(container, "MtxPlanInfo", "name", 1, Some("Name"))?,
+            quantity: read_field(container, "MtxPlanInfo", "quantity", 2, Some("Quantity"))?,
+            details: read_field(container, "MtxPlanInfo", "details", 3, Some("Details"))?,
+            description: read_field(container, "MtxPlanInfo", "description", 4, Some("Description"))?,
+        })
+    }
+
+    fn to_container(&self) -> Result<Container<'static>, Error> {
+        ContainerBuilder::new(Self::KEY, Self::SCHEMA_VERSION, Self::EXT_VERSION)
+            .total_field(5)
+            .field(0, &self.plan_type)?
+            .field(1, &self.name)?
+            .field(2, &self.quantity)?
+            .field(3, &self.details)?
+            .field(4, &self.description)?
+            .build()
+    }
+}
+
+impl<'a> FromValue<'a> for MtxPlanInfo {
+    fn from_value(value: &Value<'a>) -> Result<Self, Error> {
+        from_struct_value(value)
+    }
+}
+
+impl ToValue for MtxPlanInfo {
+    fn field_type() -> FieldType {
+        FieldType::Struct
+    }
+
+    fn to_value(&self) -> Result<Option<Value<'static>>, Error> {
+        to_struct_value(self)
+    }
+}
+
+/// `MtxUsageInfo`, container key 1360.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MtxUsageInfo {
+    /// `UsageId`
+    pub usage_id: Option<String>,
+    /// `Name`
+    pub name: Option<String>,
+    /// `Label`
+    pub label: Option<String>,
+}
+
+impl<'a> MddContainer<'a> for MtxUsageInfo {
+    const KEY: i32 = 1360;
+    const SCHEMA_VERSION: u16 = 5222;
+    const EXT_VERSION: u16 = 2;
+
+    fn from_container(container: &Container<'a>) -> Result<Self, Error> {
+        check_key(container, "MtxUsageInfo", Self::KEY)?;
+        Ok(MtxUsageInfo {
+            usage_id: read_field(container, "MtxUsageInfo", "usage_id", 0, Some("UsageId"))?,
+            name: read_field(container, "MtxUsageInfo", "name", 1, Some("Name"))?,
+            label: read_field(container, "MtxUsageInfo", "label", 2, Some("Label"))?,
+        })
+    }
+
+    fn to_container(&self) -> Result<Container<'static>, Error> {
+        ContainerBuilder::new(Self::KEY, Self::SCHEMA_VERSION, Self::EXT_VERSION)
+            .total_field(3)
+            .field(0, &self.usage_id)?
+            .field(1, &self.name)?
+            .field(2, &self.label)?
+            .build()
+    }
+}
+
+impl<'a> FromValue<'a> for MtxUsageInfo {
+    fn from_value(value: &Value<'a>) -> Result<Self, Error> {
+        from_struct_value(value)
+    }
+}
+
+impl ToValue for MtxUsageInfo {
+    fn field_type() -> FieldType {
+        FieldType::Struct
+    }
+
+    fn to_value(&self) -> Result<Option<Value<'static>>, Error> {
+        to_struct_value(self)
+    }
+}